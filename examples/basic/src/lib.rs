@@ -1,4 +1,7 @@
-use geoarrow_viz::{model::GeoArrowFile, view::view::MapView};
+use geoarrow_viz::{
+    model::{GeoArrowFile, GeoPoint},
+    view::view::MapView,
+};
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen(start)]
@@ -9,7 +12,8 @@ pub fn main() {
         0,
         "2025-01-01".to_string(),
     );
-    let map_view = MapView::new(1, geoarrow_file, (10.0, 20.0), 15);
+    let map_view = MapView::new(1, geoarrow_file, (0.0, 0.0), 15)
+        .with_center(GeoPoint::new(20.0, 10.0));
 
     map_view.render_to_canvas("canvas").unwrap();
 
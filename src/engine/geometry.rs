@@ -1,22 +1,145 @@
+use std::cell::RefCell;
 use geojson::{Geometry, Value as GeoValue, Position};
 use crate::engine::RenderContext;
+use crate::model::{Bounds, GeoPoint};
+
+// Reusable screen-coordinate buffer carried by the RenderContext. Each geometry clears and
+// refills it instead of allocating a fresh Vec, so the capacity grows to the largest geometry
+// once and is then reused for the rest of the frame.
+#[derive(Default)]
+pub struct ScratchBuffer(RefCell<Vec<(f64, f64)>>);
+
+impl ScratchBuffer {
+    /// Run `f` with the cleared buffer. A nested call while the buffer is in use gets a
+    /// temporary Vec instead of panicking.
+    pub fn with<R>(&self, f: impl FnOnce(&mut Vec<(f64, f64)>) -> R) -> R {
+        match self.0.try_borrow_mut() {
+            Ok(mut buffer) => {
+                buffer.clear();
+                f(&mut buffer)
+            }
+            Err(_) => f(&mut Vec::new()),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.0.borrow().capacity()
+    }
+}
+
+// Clones start empty: the contents are only meaningful inside `with`
+impl Clone for ScratchBuffer {
+    fn clone(&self) -> Self {
+        ScratchBuffer::default()
+    }
+}
 
 // Pure geometry transformation functions
 
 // Higher-order coordinate transformation
-pub const transform_coordinates: fn(&RenderContext) -> fn(&[Position]) -> Vec<(f64, f64)> =
-    |context| |positions| {
-        positions.iter()
-            .map(|pos| transform_position(context, pos))
-            .collect()
+pub const transform_coordinates: fn(&RenderContext, &[Position]) -> Vec<(f64, f64)> =
+    |context, positions| {
+        let mut coords = Vec::with_capacity(positions.len());
+        transform_coordinates_into(context, positions, &mut coords);
+        coords
+    };
+
+// Allocation-free variant of `transform_coordinates` that overwrites `out`
+pub const transform_coordinates_into: fn(&RenderContext, &[Position], &mut Vec<(f64, f64)>) =
+    |context, positions, out| {
+        out.clear();
+        out.extend(positions.iter().map(|pos| transform_position(context, pos)));
+    };
+
+// Path transformation: densifies along great circles first when the context asks for it, so long
+// edges follow the curvature of the projection instead of cutting straight across it
+pub const project_path: fn(&RenderContext, &[Position]) -> Vec<(f64, f64)> =
+    |context, positions| {
+        let mut coords = Vec::with_capacity(positions.len());
+        project_path_into(context, positions, &mut coords);
+        coords
+    };
+
+// Allocation-free variant of `project_path` that overwrites `out`; densifying still allocates
+pub const project_path_into: fn(&RenderContext, &[Position], &mut Vec<(f64, f64)>) =
+    |context, positions, out| {
+        out.clear();
+        extend_projected_path(context, positions, out);
+    };
+
+// Appending variant of `project_path_into`, for packing several rings into one buffer
+pub const extend_projected_path: fn(&RenderContext, &[Position], &mut Vec<(f64, f64)>) =
+    |context, positions, out| {
+        match context.densify_threshold {
+            Some(max_segment_deg) => {
+                out.extend(densify_geodesic(positions, max_segment_deg).iter().map(|pos| transform_position(context, pos)))
+            }
+            None => out.extend(positions.iter().map(|pos| transform_position(context, pos))),
+        }
+    };
+
+// `extend_projected_path` for model points
+pub const extend_projected_points: fn(&RenderContext, &[GeoPoint], &mut Vec<(f64, f64)>) =
+    |context, points, out| {
+        match context.densify_threshold {
+            Some(_) => {
+                let positions: Vec<Position> = points.iter().map(|p| vec![p.lng, p.lat]).collect();
+                extend_projected_path(context, &positions, out)
+            }
+            None => out.extend(points.iter().map(|p| transform_point(context, p))),
+        }
     };
 
 // Transform single position to screen coordinates
 pub const transform_position: fn(&RenderContext, &Position) -> (f64, f64) =
-    |context, position| {
-        let x = position[0];
-        let y = position[1];
-        (crate::engine::RenderContext::world_to_screen)(context, x, y)
+    |context, position| transform_point(context, &GeoPoint::new(position[1], position[0]));
+
+// Transform a model point to screen coordinates, snapped to the context's coordinate quantum
+pub const transform_point: fn(&RenderContext, &GeoPoint) -> (f64, f64) =
+    |context, point| {
+        let (screen_x, screen_y) = (crate::engine::RenderContext::world_to_screen)(context, point.lng, point.lat);
+        match context.coord_quantum.filter(|quantum| *quantum > 0.0) {
+            Some(quantum) => snap_to_quantum(screen_x, screen_y, quantum),
+            None => (screen_x, screen_y),
+        }
+    };
+
+// Snap a screen coordinate to the nearest point of a `quantum`-pixel grid
+const snap_to_quantum: fn(f64, f64, f64) -> (f64, f64) =
+    |x, y, quantum| ((x / quantum).round() * quantum, (y / quantum).round() * quantum);
+
+// Tile quantization: maps world coordinates to integer tile-local positions on an `extent` x
+// `extent` grid (4096 for MVT), origin top-left with y growing southwards. Positions outside the
+// tile land outside `0..extent`, as MVT buffers allow. Consecutive vertices that fall on the same
+// grid position are collapsed to one.
+pub const quantize: fn(&[(f64, f64)], u32, &Bounds) -> Vec<(i32, i32)> =
+    |coords, extent, tile_bounds| {
+        let extent = extent as f64;
+        let (width, height) = (tile_bounds.max_x - tile_bounds.min_x, tile_bounds.max_y - tile_bounds.min_y);
+        let mut quantized: Vec<(i32, i32)> = coords.iter()
+            .map(|(x, y)| {
+                let qx = ((x - tile_bounds.min_x) / width * extent).round();
+                let qy = ((tile_bounds.max_y - y) / height * extent).round();
+                (qx as i32, qy as i32)
+            })
+            .collect();
+        quantized.dedup();
+        quantized
+    };
+
+// Inverse of `quantize`: integer tile-local positions back to world coordinates on the grid
+pub const dequantize: fn(&[(i32, i32)], u32, &Bounds) -> Vec<(f64, f64)> =
+    |positions, extent, tile_bounds| {
+        let extent = extent as f64;
+        let (width, height) = (tile_bounds.max_x - tile_bounds.min_x, tile_bounds.max_y - tile_bounds.min_y);
+        positions.iter()
+            .map(|(qx, qy)| {
+                (
+                    tile_bounds.min_x + *qx as f64 / extent * width,
+                    tile_bounds.max_y - *qy as f64 / extent * height,
+                )
+            })
+            .collect()
     };
 
 // Geometry-specific transformers
@@ -84,26 +207,487 @@ pub const filter_coordinates_in_bounds: fn(&[(f64, f64)], &RenderContext) -> Vec
     };
 
 // Coordinate transformation pipeline
-pub const create_coordinate_transformer: fn(&RenderContext) -> fn(&Geometry) -> Option<Vec<(f64, f64)>> =
-    |context| |geometry| {
-        let transform_coords = transform_coordinates(context);
+pub const create_coordinate_transformer: fn(&RenderContext, &Geometry) -> Option<Vec<(f64, f64)>> =
+    |context, geometry| {
+        let transform_coords = |coords: &[Position]| transform_coordinates(context, coords);
 
         match &geometry.value {
             GeoValue::Point(_) =>
                 extract_point_coordinates(geometry).map(|coords| transform_coords(&coords)),
             GeoValue::LineString(_) =>
-                extract_linestring_coordinates(geometry).map(|coords| transform_coords(&coords)),
+                extract_linestring_coordinates(geometry).map(|coords| project_path(context, &coords)),
             GeoValue::MultiPoint(_) =>
                 extract_multipoint_coordinates(geometry).map(|coords| transform_coords(&coords)),
             _ => None,
         }
     };
 
-// Polygon-specific transformer (returns outer ring only for simplicity)
-pub const create_polygon_transformer: fn(&RenderContext) -> fn(&Geometry) -> Option<Vec<(f64, f64)>> =
-    |context| |geometry| {
-        let transform_coords = transform_coordinates(context);
-
+// Polygon-specific transformer: the outer ring followed by its holes
+pub const create_polygon_transformer: fn(&RenderContext, &Geometry) -> Option<Vec<Vec<(f64, f64)>>> =
+    |context, geometry| {
         extract_polygon_coordinates(geometry)
-            .and_then(|rings| rings.first().map(|outer_ring| transform_coords(outer_ring)))
-    };
\ No newline at end of file
+            .map(|rings| rings.iter().map(|ring| project_path(context, ring)).collect())
+    };
+
+// Screen-space decimation: drops each vertex whose projected distance from the last kept vertex
+// is under `min_px` pixels, so detail tracks what is visible at the current viewport. The first
+// and last vertices are always kept, which also keeps closed rings closed; a ring that would drop
+// below 4 vertices is returned unchanged.
+pub const simplify_screen_space: fn(&[Position], f64, &RenderContext) -> Vec<Position> =
+    |coords, min_px, context| {
+        if coords.len() <= 2 || min_px <= 0.0 {
+            return coords.to_vec();
+        }
+
+        let last = coords.len() - 1;
+        let mut kept = vec![coords[0].clone()];
+        let mut anchor = transform_position(context, &coords[0]);
+        for position in &coords[1..last] {
+            let screen = transform_position(context, position);
+            if (screen.0 - anchor.0).hypot(screen.1 - anchor.1) >= min_px {
+                kept.push(position.clone());
+                anchor = screen;
+            }
+        }
+        kept.push(coords[last].clone());
+
+        if kept.len() < 4 && coords[0] == coords[last] {
+            return coords.to_vec();
+        }
+        kept
+    };
+
+// Geodesic densification: inserts great-circle vertices so that no edge spans more than
+// `max_segment_deg` degrees of arc. Original vertices are kept untouched.
+pub const densify_geodesic: fn(&[Position], f64) -> Vec<Position> =
+    |positions, max_segment_deg| {
+        if positions.len() < 2 || max_segment_deg <= 0.0 || !max_segment_deg.is_finite() {
+            return positions.to_vec();
+        }
+
+        let mut densified = Vec::with_capacity(positions.len());
+        for pair in positions.windows(2) {
+            let (start, end) = (&pair[0], &pair[1]);
+            densified.push(start.clone());
+
+            let a = to_unit_vector(start);
+            let b = to_unit_vector(end);
+            let dot = (a.0 * b.0 + a.1 * b.1 + a.2 * b.2).clamp(-1.0, 1.0);
+            let angle = dot.acos();
+            let segments = (angle.to_degrees() / max_segment_deg).ceil() as usize;
+            if segments < 2 || angle.sin().abs() < f64::EPSILON {
+                continue;
+            }
+
+            for step in 1..segments {
+                let f = step as f64 / segments as f64;
+                let wa = ((1.0 - f) * angle).sin() / angle.sin();
+                let wb = (f * angle).sin() / angle.sin();
+                densified.push(from_unit_vector((
+                    wa * a.0 + wb * b.0,
+                    wa * a.1 + wb * b.1,
+                    wa * a.2 + wb * b.2,
+                )));
+            }
+        }
+        if let Some(last) = positions.last() {
+            densified.push(last.clone());
+        }
+        densified
+    };
+
+const to_unit_vector: fn(&Position) -> (f64, f64, f64) =
+    |position| {
+        let (lng, lat) = (position[0].to_radians(), position[1].to_radians());
+        (lat.cos() * lng.cos(), lat.cos() * lng.sin(), lat.sin())
+    };
+
+const from_unit_vector: fn((f64, f64, f64)) -> Position =
+    |(x, y, z)| {
+        let lat = z.atan2((x * x + y * y).sqrt());
+        let lng = y.atan2(x);
+        vec![lng.to_degrees(), lat.to_degrees()]
+    };
+
+// Ring nesting for flat ring lists: returns each outer ring index with the indices of its holes.
+// Rings are visited from largest to smallest; a ring's parent is the smallest already-visited
+// ring containing it. Rings nested inside a hole are islands and become outer rings again.
+pub const build_ring_hierarchy: fn(&[Vec<GeoPoint>]) -> Vec<(usize, Vec<usize>)> =
+    |rings| {
+        let mut order: Vec<usize> = (0..rings.len()).collect();
+        order.sort_by(|&a, &b| {
+            signed_ring_area(&rings[b]).abs().total_cmp(&signed_ring_area(&rings[a]).abs())
+        });
+
+        // parent[i] = enclosing ring, is_hole[i] = ring is an interior ring of its parent
+        let mut parent: Vec<Option<usize>> = vec![None; rings.len()];
+        let mut is_hole = vec![false; rings.len()];
+        for (position, &ring) in order.iter().enumerate() {
+            let Some(probe) = rings[ring].first() else { continue };
+            parent[ring] = order[..position]
+                .iter()
+                .rev()
+                .copied()
+                .find(|&candidate| ring_contains_point(&rings[candidate], probe));
+            is_hole[ring] = parent[ring].is_some_and(|p| !is_hole[p]);
+        }
+
+        (0..rings.len())
+            .filter(|&ring| !is_hole[ring])
+            .map(|outer| {
+                let holes = (0..rings.len())
+                    .filter(|&ring| is_hole[ring] && parent[ring] == Some(outer))
+                    .collect();
+                (outer, holes)
+            })
+            .collect()
+    };
+
+// Shoelace area in degree space; positive for counter-clockwise rings
+pub const signed_ring_area: fn(&[GeoPoint]) -> f64 =
+    |ring| {
+        ring.iter()
+            .zip(ring.iter().cycle().skip(1))
+            .map(|(a, b)| a.lng * b.lat - b.lng * a.lat)
+            .sum::<f64>()
+            / 2.0
+    };
+
+// Even-odd ray casting point-in-ring test
+pub const ring_contains_point: fn(&[GeoPoint], &GeoPoint) -> bool =
+    |ring, point| {
+        let mut inside = false;
+        let mut j = ring.len().wrapping_sub(1);
+        for i in 0..ring.len() {
+            let (a, b) = (&ring[i], &ring[j]);
+            if (a.lat > point.lat) != (b.lat > point.lat)
+                && point.lng < (b.lng - a.lng) * (point.lat - a.lat) / (b.lat - a.lat) + a.lng
+            {
+                inside = !inside;
+            }
+            j = i;
+        }
+        inside
+    };
+
+// Polygon difference, subject minus clip, by Greiner–Hormann clipping. Returns closed rings: the
+// subject unchanged when the two don't overlap, nothing when the clip covers it, and the subject
+// plus the reversed clip as a hole when the clip lies strictly inside. Degenerate overlaps, where
+// a vertex lies exactly on the other ring's boundary, are not handled.
+pub const difference: fn(&[GeoPoint], &[GeoPoint]) -> Vec<Vec<GeoPoint>> =
+    |subject, clip| {
+        let (subject, clip) = (open_ring(subject), open_ring(clip));
+        if subject.len() < 3 {
+            return Vec::new();
+        }
+        if clip.len() < 3 {
+            return vec![closed_ring(subject.to_vec())];
+        }
+
+        let crossings = ring_crossings(subject, clip);
+        if crossings.is_empty() {
+            return if ring_contains_point(clip, &subject[0]) {
+                Vec::new()
+            } else if ring_contains_point(subject, &clip[0]) {
+                let mut hole = clip.to_vec();
+                if signed_ring_area(&hole).signum() == signed_ring_area(subject).signum() {
+                    hole.reverse();
+                }
+                vec![closed_ring(subject.to_vec()), closed_ring(hole)]
+            } else {
+                vec![closed_ring(subject.to_vec())]
+            };
+        }
+
+        // The subject's flags are inverted so traversal follows it outside the clip, and the clip
+        // inside the subject
+        let lists = [
+            crossing_list(subject, clip, &crossings, 0, true),
+            crossing_list(clip, subject, &crossings, 1, false),
+        ];
+        let mut visited = vec![false; crossings.len()];
+        let mut rings = Vec::new();
+        for start in 0..crossings.len() {
+            if visited[start] {
+                continue;
+            }
+            let mut ring = vec![crossings[start].point.clone()];
+            let (mut side, mut crossing) = (0, start);
+            loop {
+                visited[crossing] = true;
+                let (list, positions) = &lists[side];
+                let mut index = positions[crossing];
+                let forward = list[index].entry;
+                loop {
+                    index = if forward { (index + 1) % list.len() } else { (index + list.len() - 1) % list.len() };
+                    ring.push(list[index].point.clone());
+                    if let Some(next) = list[index].crossing {
+                        crossing = next;
+                        break;
+                    }
+                }
+                side = 1 - side;
+                if crossing == start || visited[crossing] {
+                    break;
+                }
+            }
+            rings.push(closed_ring(ring));
+        }
+        rings
+    };
+
+// Boundary vertex in a Greiner–Hormann list. Crossings carry the index of the intersection they
+// stand for, shared with their twin in the other ring's list.
+struct ClipVertex {
+    point: GeoPoint,
+    crossing: Option<usize>,
+    entry: bool,
+}
+
+// Intersection of subject edge `edges[0]` with clip edge `edges[1]`, at fraction `alphas[i]`
+// along each edge
+struct RingCrossing {
+    point: GeoPoint,
+    edges: [usize; 2],
+    alphas: [f64; 2],
+}
+
+// Ring vertices without the repeated closing vertex
+fn open_ring(ring: &[GeoPoint]) -> &[GeoPoint] {
+    match ring {
+        [first, .., last] if first.lat == last.lat && first.lng == last.lng => &ring[..ring.len() - 1],
+        _ => ring,
+    }
+}
+
+fn closed_ring(mut ring: Vec<GeoPoint>) -> Vec<GeoPoint> {
+    if let (Some(first), Some(last)) = (ring.first(), ring.last())
+        && (first.lat != last.lat || first.lng != last.lng)
+    {
+        ring.push(first.clone());
+    }
+    ring
+}
+
+// Proper crossings between the edges of two open rings
+fn ring_crossings(subject: &[GeoPoint], clip: &[GeoPoint]) -> Vec<RingCrossing> {
+    let mut crossings = Vec::new();
+    for i in 0..subject.len() {
+        let (p1, p2) = (&subject[i], &subject[(i + 1) % subject.len()]);
+        for j in 0..clip.len() {
+            let (q1, q2) = (&clip[j], &clip[(j + 1) % clip.len()]);
+            let (rx, ry) = (p2.lng - p1.lng, p2.lat - p1.lat);
+            let (sx, sy) = (q2.lng - q1.lng, q2.lat - q1.lat);
+            let denominator = rx * sy - ry * sx;
+            if denominator == 0.0 {
+                continue;
+            }
+            let (dx, dy) = (q1.lng - p1.lng, q1.lat - p1.lat);
+            let t = (dx * sy - dy * sx) / denominator;
+            let u = (dx * ry - dy * rx) / denominator;
+            if t > 0.0 && t < 1.0 && u > 0.0 && u < 1.0 {
+                crossings.push(RingCrossing {
+                    point: GeoPoint::new(p1.lat + t * ry, p1.lng + t * rx),
+                    edges: [i, j],
+                    alphas: [t, u],
+                });
+            }
+        }
+    }
+    crossings
+}
+
+// One ring's vertices with its crossings spliced in along each edge, flagged as entering or
+// leaving `other`. Also returns where each crossing landed in the list.
+fn crossing_list(
+    ring: &[GeoPoint],
+    other: &[GeoPoint],
+    crossings: &[RingCrossing],
+    side: usize,
+    invert: bool,
+) -> (Vec<ClipVertex>, Vec<usize>) {
+    let mut list = Vec::with_capacity(ring.len() + crossings.len());
+    let mut positions = vec![0; crossings.len()];
+    let mut inside = ring_contains_point(other, &ring[0]);
+    for (edge, vertex) in ring.iter().enumerate() {
+        list.push(ClipVertex { point: vertex.clone(), crossing: None, entry: false });
+        let mut on_edge: Vec<usize> = (0..crossings.len()).filter(|&c| crossings[c].edges[side] == edge).collect();
+        on_edge.sort_by(|&a, &b| crossings[a].alphas[side].total_cmp(&crossings[b].alphas[side]));
+        for crossing in on_edge {
+            let entering = !inside;
+            positions[crossing] = list.len();
+            list.push(ClipVertex { point: crossings[crossing].point.clone(), crossing: Some(crossing), entry: entering != invert });
+            inside = !inside;
+        }
+    }
+    (list, positions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::view::view::MapStyle;
+
+    fn screen_area(points: &[(f64, f64)]) -> f64 {
+        let sum: f64 = points
+            .iter()
+            .zip(points.iter().cycle().skip(1))
+            .map(|((x1, y1), (x2, y2))| x1 * y2 - x2 * y1)
+            .sum();
+        sum.abs() / 2.0
+    }
+
+    #[test]
+    fn test_densified_polygon_follows_great_circle() {
+        let polygon = Geometry::new(GeoValue::Polygon(vec![vec![
+            vec![-60.0, 0.0],
+            vec![60.0, 0.0],
+            vec![60.0, 60.0],
+            vec![-60.0, 60.0],
+            vec![-60.0, 0.0],
+        ]]));
+        let context = RenderContext::new(
+            Bounds::new(-180.0, -90.0, 180.0, 90.0),
+            (720.0, 360.0),
+            0,
+            MapStyle::default(),
+        );
+
+        let plain = create_polygon_transformer(&context, &polygon).unwrap().remove(0);
+        let densified = create_polygon_transformer(&context.clone().with_densify_threshold(5.0), &polygon).unwrap().remove(0);
+
+        assert_eq!(plain.len(), 5);
+        assert!(densified.len() > plain.len());
+        assert_eq!(densified.first(), plain.first());
+        assert_eq!(densified.last(), plain.last());
+        // The northern edge bows poleward along the great circle, enlarging the filled area
+        assert!(screen_area(&densified) > screen_area(&plain) * 1.05);
+    }
+
+    #[test]
+    fn test_simplify_screen_space_drops_subpixel_vertices() {
+        // 1° is 10px at this viewport
+        let context = RenderContext::new(Bounds::new(0.0, 0.0, 10.0, 10.0), (100.0, 100.0), 5, MapStyle::default());
+        let line = vec![
+            vec![0.0, 0.0],
+            vec![0.1, 0.0], // 1px from the start
+            vec![0.2, 0.1], // still under 3px from the start
+            vec![1.0, 0.0], // 10px away
+            vec![5.0, 5.0],
+            vec![5.05, 5.0], // last vertex is always kept
+        ];
+
+        let simplified = simplify_screen_space(&line, 3.0, &context);
+
+        assert_eq!(simplified, vec![vec![0.0, 0.0], vec![1.0, 0.0], vec![5.0, 5.0], vec![5.05, 5.0]]);
+        assert_eq!(simplify_screen_space(&line, 0.0, &context), line);
+    }
+
+    #[test]
+    fn test_quantize_snaps_to_tile_grid() {
+        // 16 x 16 grid over a 16° tile: one cell per degree
+        let tile = Bounds::new(0.0, 0.0, 16.0, 16.0);
+        let coords = [(3.1, 12.9), (2.9, 13.1), (8.0, 8.0), (17.0, -1.0)];
+
+        let quantized = quantize(&coords, 16, &tile);
+
+        // The first two share a cell and collapse into one vertex
+        assert_eq!(quantized, vec![(3, 3), (8, 8), (17, 17)]);
+        assert_eq!(dequantize(&quantized, 16, &tile), vec![(3.0, 13.0), (8.0, 8.0), (17.0, -1.0)]);
+    }
+
+    #[test]
+    fn test_coord_quantum_absorbs_subpixel_changes() {
+        let path = vec![vec![-10.3, 4.7], vec![12.9, -8.1], vec![33.3, 21.2]];
+        let viewport = |offset: f64| {
+            RenderContext::new(
+                Bounds::new(-50.0 + offset, -40.0, 50.0 + offset, 40.0),
+                (800.0, 640.0),
+                3,
+                MapStyle::default(),
+            )
+        };
+
+        // Shifting by 0.001° moves every vertex by 0.008 px
+        let (a, b) = (viewport(0.0), viewport(0.001));
+        assert_ne!(transform_coordinates(&a, &path), transform_coordinates(&b, &path));
+
+        let (a, b) = (a.with_coord_quantum(0.25), b.with_coord_quantum(0.25));
+        let quantized = transform_coordinates(&a, &path);
+        assert_eq!(quantized, transform_coordinates(&b, &path));
+        assert!(quantized.iter().all(|(x, y)| (x * 4.0).fract() == 0.0 && (y * 4.0).fract() == 0.0));
+    }
+
+    #[test]
+    fn test_scratch_buffer_reuses_allocation_across_features() {
+        let context = RenderContext::new(Bounds::new(-180.0, -90.0, 180.0, 90.0), (1024.0, 512.0), 2, MapStyle::default());
+        let features: Vec<Vec<Position>> = (0..10_000)
+            .map(|i| (0..(3 + i % 17)).map(|j| vec![(i % 360) as f64 - 180.0, j as f64]).collect())
+            .collect();
+
+        // Per-geometry transform: every feature gets its own heap allocation
+        let fresh: Vec<_> = features.iter().map(|positions| transform_coordinates(&context, positions)).collect();
+        assert_eq!(fresh.iter().filter(|coords| coords.capacity() > 0).count(), 10_000);
+
+        // Scratch transform: only grows when a feature is longer than any seen so far
+        let mut reallocations = 0;
+        let mut last_capacity = context.scratch.capacity();
+        for (positions, expected) in features.iter().zip(&fresh) {
+            context.scratch.with(|coords| {
+                transform_coordinates_into(&context, positions, coords);
+                assert_eq!(coords, expected);
+            });
+            if context.scratch.capacity() != last_capacity {
+                reallocations += 1;
+                last_capacity = context.scratch.capacity();
+            }
+        }
+        assert!(reallocations <= 5, "scratch buffer reallocated {} times", reallocations);
+    }
+
+    fn square_ring(min_lng: f64, min_lat: f64, size: f64) -> Vec<GeoPoint> {
+        vec![
+            GeoPoint::new(min_lat, min_lng),
+            GeoPoint::new(min_lat, min_lng + size),
+            GeoPoint::new(min_lat + size, min_lng + size),
+            GeoPoint::new(min_lat + size, min_lng),
+            GeoPoint::new(min_lat, min_lng),
+        ]
+    }
+
+    #[test]
+    fn test_build_ring_hierarchy() {
+        let rings = vec![
+            square_ring(1.0, 1.0, 2.0),   // hole of 1
+            square_ring(0.0, 0.0, 10.0),  // outer
+            square_ring(20.0, 0.0, 5.0),  // separate outer
+            square_ring(5.0, 5.0, 3.0),   // hole of 1
+        ];
+
+        let hierarchy = build_ring_hierarchy(&rings);
+
+        assert_eq!(hierarchy, vec![(1, vec![0, 3]), (2, vec![])]);
+    }
+
+    #[test]
+    fn test_difference_cuts_corner_or_hole() {
+        let area = |rings: &[Vec<GeoPoint>]| rings.iter().map(|ring| signed_ring_area(ring).abs()).collect::<Vec<_>>();
+        let subject = square_ring(0.0, 0.0, 10.0);
+
+        // Overlapping a corner leaves an L-shape
+        let l_shape = difference(&subject, &square_ring(8.0, 8.0, 4.0));
+        assert_eq!(l_shape.len(), 1);
+        assert_eq!(l_shape[0].len(), 7);
+        assert!((area(&l_shape)[0] - 96.0).abs() < 1e-9);
+        assert!(!ring_contains_point(&l_shape[0], &GeoPoint::new(9.0, 9.0)));
+        assert!(ring_contains_point(&l_shape[0], &GeoPoint::new(1.0, 9.0)));
+
+        // A square strictly inside becomes a hole wound the other way
+        let holed = difference(&subject, &square_ring(4.0, 4.0, 2.0));
+        assert_eq!(area(&holed), vec![100.0, 4.0]);
+        assert!(signed_ring_area(&holed[0]) * signed_ring_area(&holed[1]) < 0.0);
+
+        assert_eq!(difference(&subject, &square_ring(20.0, 20.0, 2.0)), vec![subject.clone()]);
+        assert!(difference(&square_ring(4.0, 4.0, 2.0), &subject).is_empty());
+    }
+}
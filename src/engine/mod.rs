@@ -1,13 +1,24 @@
-use geojson::{Feature, Geometry, Value as GeoValue};
-use web_sys::CanvasRenderingContext2d;
-use crate::model::{Bounds, GeoArrowResult};
+#![allow(non_upper_case_globals)]
+
+use geojson::{Feature, Geometry, Position, Value as GeoValue};
+use crate::model::{Bounds, FeatureGeometry, GeoArrowResult, GeoFeature, GeoPoint, LayerStyle};
 use crate::view::view::MapStyle;
-use crate::error::GeoArrowError;
+use std::cell::Cell;
+use target::{DrawTarget, StateCachingTarget};
 
 pub mod renderer;
 pub mod geometry;
 pub mod transforms;
 pub mod tiles;
+pub mod target;
+pub mod raster;
+pub mod overlay;
+pub mod text;
+pub mod projection;
+pub mod spatial;
+
+pub use overlay::dissolve;
+pub use spatial::{density_grid, features_near_line};
 
 // Higher-level rendering pipeline function
 pub type RenderPipeline<T> = fn(T) -> GeoArrowResult<()>;
@@ -18,140 +29,321 @@ pub struct RenderContext {
     pub viewport_bounds: Bounds,
     pub canvas_size: (f64, f64),
     pub zoom_level: u8,
+    // Degrees the map is turned about the canvas center, as in `Viewport::rotation`
+    pub rotation: f64,
     pub style: MapStyle,
+    // Colors, widths and opacities the features are drawn with; starts out matching `style`
+    pub layer_style: LayerStyle,
+    // Maximum edge length in degrees before lines/rings are densified ahead of projection
+    pub densify_threshold: Option<f64>,
+    // Screen-space polygon that all feature drawing is clipped to
+    pub clip_mask: Option<Vec<(f64, f64)>>,
+    // Maximum number of features drawn per render; the rest are summarized by a count badge
+    pub draw_cap: Option<usize>,
+    // Repeat features in the adjacent worlds (±360° longitude) that overlap the viewport
+    pub world_copies: bool,
+    // Grid size in pixels that projected coordinates are rounded to, so sub-pixel viewport
+    // changes during panning don't make features shimmer
+    pub coord_quantum: Option<f64>,
+    // Projected-coordinate buffer reused across geometries to avoid per-geometry allocation
+    pub scratch: geometry::ScratchBuffer,
+    // Milliseconds a budgeted render may spend before deferring the rest to the next frame
+    pub frame_budget_ms: Option<f64>,
+    // Time source for the frame budget
+    pub clock: FrameClock,
+    // Item a budgeted render resumes from; reset to 0 once a render completes
+    pub resume_index: Cell<usize>,
+    // Outline-only inspection mode: nothing is filled and every geometry is stroked with
+    // `wireframe_color` at `wireframe_width`, whatever the styles say
+    pub wireframe: bool,
+    pub wireframe_color: String,
+    pub wireframe_width: f64,
 }
 
+// Millisecond clock reading from an arbitrary origin
+pub type FrameClock = fn() -> f64;
+
+// `performance.now()` in the browser, a process-wide monotonic clock elsewhere
+pub const now_ms: FrameClock = || {
+    #[cfg(target_arch = "wasm32")]
+    {
+        web_sys::window().and_then(|window| window.performance()).map_or(0.0, |performance| performance.now())
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        static ORIGIN: std::sync::OnceLock<std::time::Instant> = std::sync::OnceLock::new();
+        ORIGIN.get_or_init(std::time::Instant::now).elapsed().as_secs_f64() * 1000.0
+    }
+};
+
 // Functional transformation types
 pub type GeometryTransform = fn(&Geometry, &RenderContext) -> Vec<(f64, f64)>;
-pub type PointRenderer = fn(&[(f64, f64)], &RenderContext, &CanvasRenderingContext2d) -> GeoArrowResult<()>;
-pub type LineRenderer = fn(&[(f64, f64)], &RenderContext, &CanvasRenderingContext2d) -> GeoArrowResult<()>;
-pub type PolygonRenderer = fn(&[(f64, f64)], &RenderContext, &CanvasRenderingContext2d) -> GeoArrowResult<()>;
+pub type PointRenderer = fn(&[(f64, f64)], &RenderContext, &dyn DrawTarget) -> GeoArrowResult<()>;
+pub type LineRenderer = fn(&[(f64, f64)], &RenderContext, &dyn DrawTarget) -> GeoArrowResult<()>;
+pub type PolygonRenderer = fn(&[(f64, f64)], &RenderContext, &dyn DrawTarget) -> GeoArrowResult<()>;
 
 // Main rendering pipeline
-pub const create_render_pipeline: fn(&RenderContext) -> RenderPipeline<&[Feature]> =
-    |context| |features| render_features(features, context);
+pub fn create_render_pipeline<'a>(context: &'a RenderContext, target: &'a dyn DrawTarget) -> impl Fn(&[Feature]) -> GeoArrowResult<()> + 'a {
+    move |features| render_features(features, context, target)
+}
 
 // Core feature rendering function
-pub const render_features: fn(&[Feature], &RenderContext) -> GeoArrowResult<()> =
-    |features, context| {
-        features.iter()
-            .map(|feature| render_single_feature(feature, context))
-            .collect::<Result<Vec<_>, _>>()
-            .map(|_| ())
+pub const render_features: fn(&[Feature], &RenderContext, &dyn DrawTarget) -> GeoArrowResult<()> =
+    |features, context, target| {
+        render_capped(context, target, features.len(), &|i, target| render_single_feature(&features[i], context, target))?;
+        Ok(())
     };
 
 // Single feature rendering
-pub const render_single_feature: fn(&Feature, &RenderContext) -> GeoArrowResult<()> =
-    |feature, context| {
-        match &feature.geometry {
-            Some(geometry) => render_geometry(geometry, context),
-            None => Ok(()),
+pub const render_single_feature: fn(&Feature, &RenderContext, &dyn DrawTarget) -> GeoArrowResult<()> =
+    |feature, context, target| {
+        let Some(geometry) = &feature.geometry else {
+            return Ok(());
+        };
+        if !context.world_copies {
+            return render_geometry(geometry, context, target);
         }
+        context.world_offsets()
+            .into_iter()
+            .try_for_each(|offset| render_geometry(geometry, &context.for_world_copy(offset), target))
     };
 
 // Geometry dispatch function
-pub const render_geometry: fn(&Geometry, &RenderContext) -> GeoArrowResult<()> =
-    |geometry, context| {
+pub const render_geometry: fn(&Geometry, &RenderContext, &dyn DrawTarget) -> GeoArrowResult<()> =
+    |geometry, context, target| {
         match &geometry.value {
-            GeoValue::Point(_) => render_point_geometry(geometry, context),
-            GeoValue::LineString(_) => render_linestring_geometry(geometry, context),
-            GeoValue::Polygon(_) => render_polygon_geometry(geometry, context),
-            GeoValue::MultiPoint(_) => render_multipoint_geometry(geometry, context),
-            GeoValue::MultiLineString(_) => render_multilinestring_geometry(geometry, context),
-            GeoValue::MultiPolygon(_) => render_multipolygon_geometry(geometry, context),
+            GeoValue::Point(_) => render_point_geometry(geometry, context, target),
+            GeoValue::LineString(_) => render_linestring_geometry(geometry, context, target),
+            GeoValue::Polygon(_) => render_polygon_geometry(geometry, context, target),
+            GeoValue::MultiPoint(_) => render_multipoint_geometry(geometry, context, target),
+            GeoValue::MultiLineString(_) => render_multilinestring_geometry(geometry, context, target),
+            GeoValue::MultiPolygon(_) => render_multipolygon_geometry(geometry, context, target),
             GeoValue::GeometryCollection(geometries) => {
                 geometries.iter()
-                    .map(|geom| render_geometry(geom, context))
+                    .map(|geom| render_geometry(geom, context, target))
                     .collect::<Result<Vec<_>, _>>()
                     .map(|_| ())
             }
         }
     };
 
+// Project positions into the context's scratch buffer and draw them with `draw`. Paths
+// (`as_path`) are densified first when the context asks for it.
+const render_positions: fn(&RenderContext, &dyn DrawTarget, &[Position], bool, PointRenderer) -> GeoArrowResult<()> =
+    |context, target, positions, as_path, draw| {
+        context.scratch.with(|coords| {
+            if as_path {
+                geometry::project_path_into(context, positions, coords);
+            } else {
+                geometry::transform_coordinates_into(context, positions, coords);
+            }
+            draw(coords, context, target)
+        })
+    };
+
+// Project a polygon's rings back to back into the context's scratch buffer, recording where
+// each one ends, and draw them as one path so holes are cut out
+fn render_polygon_rings<R>(
+    context: &RenderContext,
+    target: &dyn DrawTarget,
+    rings: &[R],
+    project: impl Fn(&R, &mut Vec<(f64, f64)>),
+) -> GeoArrowResult<()> {
+    context.scratch.with(|coords| {
+        let ring_ends: Vec<usize> = rings.iter()
+            .map(|ring| {
+                project(ring, coords);
+                coords.len()
+            })
+            .collect();
+        renderer::render_polygon_packed(coords, &ring_ends, context, target)
+    })
+}
+
 // Geometry rendering implementations using the functional pipeline
-const render_point_geometry: fn(&Geometry, &RenderContext) -> GeoArrowResult<()> =
-    |geometry, context| {
-        geometry::create_coordinate_transformer(context)(geometry)
-            .map(|coords| render_with_canvas(context, |canvas_ctx| {
-                renderer::render_points(&coords, context, canvas_ctx)
-            }))
-            .unwrap_or(Ok(()))
-    };
-
-const render_linestring_geometry: fn(&Geometry, &RenderContext) -> GeoArrowResult<()> =
-    |geometry, context| {
-        geometry::create_coordinate_transformer(context)(geometry)
-            .map(|coords| render_with_canvas(context, |canvas_ctx| {
-                renderer::render_linestring(&coords, context, canvas_ctx)
-            }))
-            .unwrap_or(Ok(()))
-    };
-
-const render_polygon_geometry: fn(&Geometry, &RenderContext) -> GeoArrowResult<()> =
-    |geometry, context| {
-        geometry::create_polygon_transformer(context)(geometry)
-            .map(|coords| render_with_canvas(context, |canvas_ctx| {
-                renderer::render_polygon(&coords, context, canvas_ctx)
-            }))
-            .unwrap_or(Ok(()))
-    };
-
-const render_multipoint_geometry: fn(&Geometry, &RenderContext) -> GeoArrowResult<()> =
-    |geometry, context| {
-        geometry::extract_multipoint_coordinates(geometry)
-            .map(|positions| {
-                let transformer = geometry::transform_coordinates(context);
-                let coords = transformer(&positions);
-                render_with_canvas(context, |canvas_ctx| {
-                    renderer::render_points(&coords, context, canvas_ctx)
+const render_point_geometry: fn(&Geometry, &RenderContext, &dyn DrawTarget) -> GeoArrowResult<()> =
+    |geometry, context, target| {
+        match &geometry.value {
+            GeoValue::Point(position) => {
+                render_positions(context, target, std::slice::from_ref(position), false, renderer::render_points)
+            }
+            _ => Ok(()),
+        }
+    };
+
+const render_linestring_geometry: fn(&Geometry, &RenderContext, &dyn DrawTarget) -> GeoArrowResult<()> =
+    |geometry, context, target| {
+        match &geometry.value {
+            GeoValue::LineString(positions) => render_positions(context, target, positions, true, renderer::render_linestring),
+            _ => Ok(()),
+        }
+    };
+
+const render_polygon_geometry: fn(&Geometry, &RenderContext, &dyn DrawTarget) -> GeoArrowResult<()> =
+    |geometry, context, target| {
+        match &geometry.value {
+            GeoValue::Polygon(rings) => render_polygon_rings(context, target, rings, |ring, coords| {
+                geometry::extend_projected_path(context, ring, coords)
+            }),
+            _ => Ok(()),
+        }
+    };
+
+const render_multipoint_geometry: fn(&Geometry, &RenderContext, &dyn DrawTarget) -> GeoArrowResult<()> =
+    |geometry, context, target| {
+        match &geometry.value {
+            GeoValue::MultiPoint(positions) => render_positions(context, target, positions, false, renderer::render_points),
+            _ => Ok(()),
+        }
+    };
+
+const render_multilinestring_geometry: fn(&Geometry, &RenderContext, &dyn DrawTarget) -> GeoArrowResult<()> =
+    |geometry, context, target| {
+        match &geometry.value {
+            GeoValue::MultiLineString(lines) => lines.iter()
+                .try_for_each(|line| render_positions(context, target, line, true, renderer::render_linestring)),
+            _ => Ok(()),
+        }
+    };
+
+const render_multipolygon_geometry: fn(&Geometry, &RenderContext, &dyn DrawTarget) -> GeoArrowResult<()> =
+    |geometry, context, target| {
+        match &geometry.value {
+            GeoValue::MultiPolygon(polygons) => polygons.iter().try_for_each(|rings| {
+                render_polygon_rings(context, target, rings, |ring, coords| {
+                    geometry::extend_projected_path(context, ring, coords)
                 })
+            }),
+            _ => Ok(()),
+        }
+    };
+
+// Draw a decoded model geometry straight onto a target. Polygon holes are cut out with the
+// even-odd rule, like the GeoJSON pipeline.
+pub const render_feature_geometry: fn(&FeatureGeometry, &RenderContext, &dyn DrawTarget) -> GeoArrowResult<()> =
+    |geometry, context, target| {
+        // Nothing to draw, so don't touch the target's state either
+        if geometry.is_empty() {
+            return Ok(());
+        }
+        let draw = |points: &[GeoPoint], render: PointRenderer| {
+            context.scratch.with(|coords| {
+                coords.extend(points.iter().map(|p| geometry::transform_point(context, p)));
+                render(coords, context, target)
             })
-            .unwrap_or(Ok(()))
-    };
-
-const render_multilinestring_geometry: fn(&Geometry, &RenderContext) -> GeoArrowResult<()> =
-    |geometry, context| {
-        geometry::extract_multilinestring_coordinates(geometry)
-            .map(|line_strings| {
-                let transformer = geometry::transform_coordinates(context);
-                line_strings.iter()
-                    .map(|line| {
-                        let coords = transformer(line);
-                        render_with_canvas(context, |canvas_ctx| {
-                            renderer::render_linestring(&coords, context, canvas_ctx)
-                        })
-                    })
-                    .collect::<Result<Vec<_>, _>>()
-                    .map(|_| ())
-            })
-            .unwrap_or(Ok(()))
-    };
-
-const render_multipolygon_geometry: fn(&Geometry, &RenderContext) -> GeoArrowResult<()> =
-    |geometry, context| {
-        geometry::extract_multipolygon_coordinates(geometry)
-            .map(|polygons| {
-                let transformer = geometry::transform_coordinates(context);
-                polygons.iter()
-                    .filter_map(|rings| rings.first()) // Only render outer ring for simplicity
-                    .map(|outer_ring| {
-                        let coords = transformer(outer_ring);
-                        render_with_canvas(context, |canvas_ctx| {
-                            renderer::render_polygon(&coords, context, canvas_ctx)
-                        })
-                    })
-                    .collect::<Result<Vec<_>, _>>()
-                    .map(|_| ())
+        };
+        // Lines and rings are densified first when the context asks for it
+        let line = |points: &[GeoPoint]| {
+            context.scratch.with(|coords| {
+                geometry::extend_projected_points(context, points, coords);
+                renderer::render_linestring(coords, context, target)
             })
-            .unwrap_or(Ok(()))
+        };
+        let polygon = |rings: &[Vec<GeoPoint>]| {
+            render_polygon_rings(context, target, rings, |ring, coords| geometry::extend_projected_points(context, ring, coords))
+        };
+
+        match geometry {
+            FeatureGeometry::Point(point) => draw(std::slice::from_ref(point), renderer::render_points),
+            FeatureGeometry::MultiPoint(points) => draw(points, renderer::render_points),
+            FeatureGeometry::LineString(points) => line(points),
+            FeatureGeometry::MultiLineString(lines) => lines.iter().try_for_each(|points| line(points)),
+            FeatureGeometry::Polygon(rings) => polygon(rings),
+            FeatureGeometry::MultiPolygon(polygons) => polygons.iter().try_for_each(|rings| polygon(rings)),
+        }
     };
 
-// Canvas context helper function (placeholder - needs actual canvas access)
-const render_with_canvas: fn(&RenderContext, fn(&CanvasRenderingContext2d) -> GeoArrowResult<()>) -> GeoArrowResult<()> =
-    |_context, _render_fn| {
-        // This is a placeholder - in practice, we need access to the actual canvas context
-        // This would be injected or passed down from the MapView
-        Ok(())
+// Draw one feature. LineStrings carrying the line style's per-vertex width property are
+// drawn tapered.
+const render_feature: fn(&GeoFeature, &RenderContext, &dyn DrawTarget) -> GeoArrowResult<()> =
+    |feature, context, target| {
+        let geometry = feature.render_geometry()?;
+        let widths = context.layer_style.line_style.width_property.as_ref()
+            .and_then(|key| feature.properties.get(key))
+            .and_then(|value| value.as_array().map(|widths| widths.iter().filter_map(|w| w.as_f64()).collect::<Vec<f64>>()));
+        match (geometry, widths) {
+            (FeatureGeometry::LineString(points), Some(widths)) => context.scratch.with(|coords| {
+                coords.extend(points.iter().map(|p| geometry::transform_point(context, p)));
+                renderer::render_tapered_linestring(coords, &widths, context, target)
+            }),
+            (geometry, _) => render_feature_geometry(geometry, context, target),
+        }
+    };
+
+// Draw many features sharing one context in input order, so the caller's draw order (last is
+// topmost) holds. The state-caching target skips style setters repeated between consecutive
+// features of the same kind. Features past the draw cap are summarized by a "+N" badge once
+// the capped ones are drawn. Returns the index to resume at when the frame budget ran out.
+pub const render_feature_batch: fn(&[&GeoFeature], &RenderContext, &dyn DrawTarget) -> GeoArrowResult<Option<usize>> =
+    |features, context, target| {
+        let target = StateCachingTarget::new(target);
+        let drawn = context.draw_cap.map_or(features.len(), |cap| cap.min(features.len()));
+        // With world copies on, every feature is drawn once per copy overlapping the viewport
+        let copies: Vec<RenderContext> = if context.world_copies {
+            context.world_offsets().into_iter().map(|offset| context.for_world_copy(offset)).collect()
+        } else {
+            Vec::new()
+        };
+        let draw = |i: usize, target: &dyn DrawTarget| {
+            if copies.is_empty() {
+                render_feature(features[i], context, target)
+            } else {
+                copies.iter().try_for_each(|copy| render_feature(features[i], copy, target))
+            }
+        };
+        let resume = render_budgeted(context, &target, drawn, &draw)?;
+        if resume.is_none() && drawn < features.len() {
+            renderer::draw_count_badge(&target, context.canvas_size, features.len() - drawn)?;
+        }
+        Ok(resume)
+    };
+
+// Clipped drawing: when the context has a clip mask, the mask path is installed as the clip
+// region around `draw_fn` and removed again afterwards via save/restore
+pub const render_clipped: fn(&RenderContext, &dyn DrawTarget, &dyn Fn(&dyn DrawTarget) -> GeoArrowResult<()>) -> GeoArrowResult<()> =
+    |context, target, draw_fn| {
+        let Some(mask) = context.clip_mask.as_ref().filter(|mask| mask.len() >= 3) else {
+            return draw_fn(target);
+        };
+
+        target.save();
+        renderer::draw_path(mask, target)?;
+        target.close_path();
+        target.clip();
+        let result = draw_fn(target);
+        target.restore();
+        result
+    };
+
+// Capped drawing: draws items `0..count` through `draw_fn` up to the context's draw cap, then
+// summarizes the remainder with a "+N" badge. Returns the number of items drawn.
+pub const render_capped: fn(&RenderContext, &dyn DrawTarget, usize, &dyn Fn(usize, &dyn DrawTarget) -> GeoArrowResult<()>) -> GeoArrowResult<usize> =
+    |context, target, count, draw_fn| {
+        let drawn = context.draw_cap.map_or(count, |cap| cap.min(count));
+        (0..drawn).try_for_each(|i| draw_fn(i, target))?;
+
+        if drawn < count {
+            renderer::draw_count_badge(target, context.canvas_size, count - drawn)?;
+        }
+        Ok(drawn)
+    };
+
+// Time-budgeted drawing: draws items from the context's resume index onwards, checking the clock
+// after each one. Once the frame budget is spent the next index is recorded and returned so the
+// following render picks up there; a completed pass resets the resume index and returns `None`.
+pub const render_budgeted: fn(&RenderContext, &dyn DrawTarget, usize, &dyn Fn(usize, &dyn DrawTarget) -> GeoArrowResult<()>) -> GeoArrowResult<Option<usize>> =
+    |context, target, count, draw_fn| {
+        let start = (context.clock)();
+        for i in context.resume_index.get().min(count)..count {
+            draw_fn(i, target)?;
+            let spent = context.frame_budget_ms.is_some_and(|budget| (context.clock)() - start > budget);
+            if spent && i + 1 < count {
+                context.resume_index.set(i + 1);
+                return Ok(Some(i + 1));
+            }
+        }
+        context.resume_index.set(0);
+        Ok(None)
     };
 
 impl RenderContext {
@@ -160,21 +352,464 @@ impl RenderContext {
             viewport_bounds,
             canvas_size,
             zoom_level,
+            rotation: 0.0,
+            layer_style: LayerStyle::from(&style),
             style,
+            densify_threshold: None,
+            clip_mask: None,
+            draw_cap: None,
+            world_copies: false,
+            coord_quantum: None,
+            scratch: geometry::ScratchBuffer::default(),
+            frame_budget_ms: None,
+            clock: now_ms,
+            resume_index: Cell::new(0),
+            wireframe: false,
+            wireframe_color: "#ff00ff".to_string(),
+            wireframe_width: 1.0,
         };
 
+    pub fn with_layer_style(mut self, layer_style: LayerStyle) -> Self {
+        self.layer_style = layer_style;
+        self
+    }
+
+    pub fn with_rotation(mut self, rotation: f64) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_densify_threshold(mut self, max_segment_deg: f64) -> Self {
+        self.densify_threshold = Some(max_segment_deg);
+        self
+    }
+
+    pub fn with_clip_mask(mut self, mask: Vec<(f64, f64)>) -> Self {
+        self.clip_mask = Some(mask);
+        self
+    }
+
+    pub fn with_draw_cap(mut self, cap: usize) -> Self {
+        self.draw_cap = Some(cap);
+        self
+    }
+
+    pub fn with_coord_quantum(mut self, quantum: f64) -> Self {
+        self.coord_quantum = Some(quantum);
+        self
+    }
+
+    pub fn with_frame_budget(mut self, budget_ms: f64) -> Self {
+        self.frame_budget_ms = Some(budget_ms);
+        self
+    }
+
+    pub fn with_clock(mut self, clock: FrameClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Draw outlines only, in one color and width
+    pub fn with_wireframe(mut self, color: &str, width: f64) -> Self {
+        self.wireframe = true;
+        self.wireframe_color = color.to_string();
+        self.wireframe_width = width;
+        self
+    }
+
+    pub fn with_world_copies(mut self, world_copies: bool) -> Self {
+        self.world_copies = world_copies;
+        self
+    }
+
+    /// Longitude offsets (multiples of 360°) of every world copy overlapping the viewport.
+    /// Only the primary world (`0.0`) is returned when world copies are off.
+    pub fn world_offsets(&self) -> Vec<f64> {
+        if !self.world_copies {
+            return vec![0.0];
+        }
+        let first = ((self.viewport_bounds.min_x + 180.0) / 360.0).floor() as i64;
+        let last = ((self.viewport_bounds.max_x - 180.0) / 360.0).ceil() as i64;
+        (first..=last.max(first)).map(|k| k as f64 * 360.0).collect()
+    }
+
+    /// Context that draws unshifted geometry as if it were moved by `offset` degrees of longitude
+    pub fn for_world_copy(&self, offset: f64) -> RenderContext {
+        let mut context = self.clone();
+        context.viewport_bounds.min_x -= offset;
+        context.viewport_bounds.max_x -= offset;
+        context
+    }
+
     // Pure transformation functions
     pub const world_to_screen: fn(&RenderContext, f64, f64) -> (f64, f64) =
-        |context, x, y| {
-            let bounds = &context.viewport_bounds;
-            let (canvas_width, canvas_height) = context.canvas_size;
+        |context, x, y| transforms::world_to_screen((x, y), context.canvas_size, context.rotation, &context.viewport_bounds);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::raster::RasterTarget;
+    use crate::engine::target::{DrawCommand, RecordingTarget};
+
+    fn square(min: f64, size: f64) -> Vec<(f64, f64)> {
+        vec![(min, min), (min + size, min), (min + size, min + size), (min, min + size)]
+    }
+
+    #[test]
+    fn test_clip_mask_hides_features_outside() {
+        let context = RenderContext::new(Bounds::new(0.0, 0.0, 20.0, 20.0), (20.0, 20.0), 1, MapStyle::default())
+            .with_clip_mask(square(0.0, 10.0));
+        let target = RasterTarget::new(20, 20);
+
+        render_clipped(&context, &target, &|target| {
+            renderer::render_polygon(&square(2.0, 6.0), &context, target)?;
+            renderer::render_polygon(&square(12.0, 6.0), &context, target)
+        })
+        .unwrap();
+
+        assert_ne!(target.pixel(5, 5)[3], 0);
+        for y in 10..20 {
+            for x in 10..20 {
+                assert_eq!(target.pixel(x, y), [0, 0, 0, 0], "pixel ({}, {}) outside the mask", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn test_clip_mask_is_reset_after_drawing() {
+        let context = RenderContext::new(Bounds::new(0.0, 0.0, 20.0, 20.0), (20.0, 20.0), 1, MapStyle::default())
+            .with_clip_mask(square(0.0, 10.0));
+        let target = RecordingTarget::new();
+
+        render_clipped(&context, &target, &|target| {
+            target.fill();
+            Ok(())
+        })
+        .unwrap();
+
+        let commands = target.commands();
+        let position = |command: &DrawCommand| commands.iter().position(|c| c == command).unwrap();
+        assert_eq!(commands.first(), Some(&DrawCommand::Save));
+        assert!(position(&DrawCommand::Clip) < position(&DrawCommand::Fill));
+        assert_eq!(commands.last(), Some(&DrawCommand::Restore));
+    }
+
+    #[test]
+    fn test_tiny_canvas_produces_finite_coordinates() {
+        let point = FeatureGeometry::Point(GeoPoint::new(5.0, 5.0));
+        let polygon = FeatureGeometry::Polygon(vec![vec![
+            GeoPoint::new(0.0, 0.0),
+            GeoPoint::new(0.0, 1e-12),
+            GeoPoint::new(1e-12, 1e-12),
+            GeoPoint::new(0.0, 0.0),
+        ]]);
+        // Degenerate viewport: the extent of a single point
+        let context = RenderContext::new(Bounds::new(5.0, 5.0, 5.0, 5.0), (2.0, 2.0), 20, MapStyle::default());
+        let target = RecordingTarget::new();
+
+        render_feature_geometry(&point, &context, &target).unwrap();
+        render_feature_geometry(&polygon, &context, &target).unwrap();
+
+        let coordinates: Vec<f64> = target
+            .commands()
+            .into_iter()
+            .flat_map(|command| match command {
+                DrawCommand::MoveTo(x, y) | DrawCommand::LineTo(x, y) | DrawCommand::Arc(x, y, ..) => vec![x, y],
+                _ => vec![],
+            })
+            .collect();
+        assert!(!coordinates.is_empty());
+        assert!(coordinates.iter().all(|c| c.is_finite()));
+        assert!(target.commands().iter().any(|c| matches!(c, DrawCommand::Arc(1.0, 1.0, 3.0, ..))));
+    }
+
+    #[test]
+    fn test_empty_geometry_issues_no_draw_calls() {
+        let context = RenderContext::new(Bounds::new(0.0, 0.0, 10.0, 10.0), (100.0, 100.0), 1, MapStyle::default());
+        let target = RecordingTarget::new();
+
+        render_feature_geometry(&FeatureGeometry::LineString(Vec::new()), &context, &target).unwrap();
+        render_feature_geometry(&FeatureGeometry::MultiPolygon(vec![vec![]]), &context, &target).unwrap();
+
+        assert!(target.commands().is_empty());
+    }
+
+    #[test]
+    fn test_world_copies_draw_wrapped_feature() {
+        // Viewport straddling the antimeridian on the western side
+        let context = RenderContext::new(Bounds::new(-200.0, -90.0, -100.0, 90.0), (100.0, 180.0), 1, MapStyle::default());
+        let near_edge = Geometry::new(GeoValue::Point(vec![170.0, 0.0]));
+        let on_screen = |context: &RenderContext| {
+            context.world_offsets()
+                .into_iter()
+                .filter_map(|offset| geometry::create_coordinate_transformer(&context.for_world_copy(offset), &near_edge))
+                .flatten()
+                .filter(|(x, y)| (0.0..=100.0).contains(x) && (0.0..=180.0).contains(y))
+                .collect::<Vec<_>>()
+        };
 
-            let x_ratio = (x - bounds.min_x) / (bounds.max_x - bounds.min_x);
-            let y_ratio = (y - bounds.min_y) / (bounds.max_y - bounds.min_y);
+        assert!(on_screen(&context).is_empty());
+        let wrapped = context.with_world_copies(true);
+        assert_eq!(wrapped.world_offsets(), vec![-360.0, 0.0]);
+        // 170° drawn one world to the west lands at -190°
+        assert_eq!(on_screen(&wrapped), vec![(10.0, 90.0)]);
+    }
 
-            let screen_x = x_ratio * canvas_width;
-            let screen_y = canvas_height - (y_ratio * canvas_height); // Flip Y axis
+    #[test]
+    fn test_geojson_pipeline_draws_onto_target() {
+        let context = RenderContext::new(Bounds::new(0.0, 0.0, 10.0, 10.0), (100.0, 100.0), 1, MapStyle::default());
+        let features: Vec<Feature> = [
+            GeoValue::Point(vec![5.0, 5.0]),
+            GeoValue::Polygon(vec![vec![vec![1.0, 1.0], vec![4.0, 1.0], vec![4.0, 4.0], vec![1.0, 1.0]]]),
+        ]
+        .into_iter()
+        .map(|value| Feature::from(Geometry::new(value)))
+        .collect();
+        let target = RecordingTarget::new();
 
-            (screen_x, screen_y)
+        create_render_pipeline(&context, &target)(&features).unwrap();
+
+        // Mercator spacing moves latitudes by a fraction of a pixel from an even split
+        let near = |x: f64, y: f64, ex: f64, ey: f64| (x - ex).abs() < 0.5 && (y - ey).abs() < 0.5;
+        let commands = target.commands();
+        assert!(commands.iter().any(|c| matches!(c, DrawCommand::Arc(x, y, ..) if near(*x, *y, 50.0, 50.0))));
+        assert!(commands.iter().any(|c| matches!(c, DrawCommand::MoveTo(x, y) if near(*x, *y, 10.0, 90.0))));
+        assert!(commands.contains(&DrawCommand::Fill));
+
+        // The draw cap applies to the GeoJSON pipeline too
+        let capped = context.with_draw_cap(1);
+        let target = RecordingTarget::new();
+        render_features(&features, &capped, &target).unwrap();
+        assert!(!target.commands().contains(&DrawCommand::MoveTo(10.0, 90.0)));
+        assert!(target.commands().iter().any(|c| matches!(c, DrawCommand::FillText(text, ..) if text == "+1")));
+    }
+
+    #[test]
+    fn test_draw_cap_emits_count_badge() {
+        let context = RenderContext::new(Bounds::new(0.0, 0.0, 100.0, 100.0), (100.0, 100.0), 1, MapStyle::default())
+            .with_draw_cap(10);
+        let target = RecordingTarget::new();
+        let points: Vec<(f64, f64)> = (0..50).map(|i| (i as f64, i as f64)).collect();
+
+        let drawn = render_capped(&context, &target, points.len(), &|i, target| {
+            renderer::render_points(&points[i..=i], &context, target)
+        })
+        .unwrap();
+
+        let commands = target.commands();
+        assert_eq!(drawn, 10);
+        assert_eq!(commands.iter().filter(|c| matches!(c, DrawCommand::Arc(..))).count(), 10);
+        assert!(commands.iter().any(|c| matches!(c, DrawCommand::FillText(text, ..) if text == "+40")));
+    }
+
+    #[test]
+    fn test_frame_budget_defers_remaining_features() {
+        thread_local! {
+            static NOW: Cell<f64> = const { Cell::new(0.0) };
+        }
+        // Every reading advances the mock clock by 1 ms
+        let clock: FrameClock = || NOW.with(|now| {
+            now.set(now.get() + 1.0);
+            now.get()
+        });
+        let context = RenderContext::new(Bounds::new(0.0, 0.0, 100.0, 100.0), (100.0, 100.0), 1, MapStyle::default())
+            .with_frame_budget(2.5)
+            .with_clock(clock);
+        let points: Vec<(f64, f64)> = (0..10).map(|i| (i as f64, i as f64)).collect();
+        let draw = |i: usize, target: &dyn DrawTarget| renderer::render_points(&points[i..=i], &context, target);
+        let arcs = |target: &RecordingTarget| {
+            target.commands().into_iter()
+                .filter_map(|c| match c {
+                    DrawCommand::Arc(x, ..) => Some(x as usize),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let target = RecordingTarget::new();
+        assert_eq!(render_budgeted(&context, &target, points.len(), &draw).unwrap(), Some(3));
+        assert_eq!(arcs(&target), [0, 1, 2]);
+        assert_eq!(context.resume_index.get(), 3);
+
+        let target = RecordingTarget::new();
+        assert_eq!(render_budgeted(&context, &target, points.len(), &draw).unwrap(), Some(6));
+        assert_eq!(arcs(&target), [3, 4, 5]);
+
+        // Without a budget the rest is drawn and the next render starts over
+        let unbounded = RenderContext { frame_budget_ms: None, ..context.clone() };
+        let target = RecordingTarget::new();
+        assert_eq!(render_budgeted(&unbounded, &target, points.len(), &draw).unwrap(), None);
+        assert_eq!(arcs(&target), [6, 7, 8, 9]);
+        assert_eq!(unbounded.resume_index.get(), 0);
+    }
+
+    #[test]
+    fn test_batch_sets_shared_fill_style_once() {
+        let context = RenderContext::new(Bounds::new(0.0, 0.0, 100.0, 100.0), (100.0, 100.0), 1, MapStyle::default());
+        let square = |i: usize, min: f64| {
+            let ring = vec![
+                GeoPoint::new(min, min),
+                GeoPoint::new(min, min + 1.0),
+                GeoPoint::new(min + 1.0, min + 1.0),
+                GeoPoint::new(min, min),
+            ];
+            GeoFeature::new(format!("p{i}").into(), FeatureGeometry::Polygon(vec![ring]), Default::default())
+        };
+        let features: Vec<GeoFeature> = (0..100).map(|i| square(i, i as f64 % 90.0)).collect();
+        let target = RecordingTarget::new();
+
+        render_feature_batch(&features.iter().collect::<Vec<_>>(), &context, &target).unwrap();
+
+        let commands = target.commands();
+        let count = |f: fn(&DrawCommand) -> bool| commands.iter().filter(|c| f(c)).count();
+        assert_eq!(count(|c| matches!(c, DrawCommand::SetFillStyle(_))), 1);
+        assert_eq!(count(|c| matches!(c, DrawCommand::SetStrokeStyle(_))), 1);
+        assert_eq!(count(|c| matches!(c, DrawCommand::Fill)), 100);
+    }
+
+    #[test]
+    fn test_batch_draws_the_simplified_geometry() {
+        let context = RenderContext::new(Bounds::new(0.0, -1.0, 20.0, 1.0), (200.0, 200.0), 1, MapStyle::default());
+        let zigzag = (0..=20).map(|i| GeoPoint::new(if i % 2 == 0 { 0.0 } else { 0.001 }, i as f64)).collect();
+        let mut feature = GeoFeature::new("river".into(), FeatureGeometry::LineString(zigzag), Default::default());
+        feature.simplify_for_rendering(0.01).unwrap();
+        let target = RecordingTarget::new();
+
+        render_feature_batch(&[&feature], &context, &target).unwrap();
+
+        let vertices = target.commands().iter()
+            .filter(|c| matches!(c, DrawCommand::MoveTo(..) | DrawCommand::LineTo(..)))
+            .count();
+        assert_eq!(vertices, 2);
+        assert_eq!(feature.geometry().unwrap().vertex_count(), 21);
+    }
+
+    #[test]
+    fn test_batch_densifies_model_geometries() {
+        let context = RenderContext::new(Bounds::new(-60.0, -10.0, 60.0, 80.0), (400.0, 300.0), 1, MapStyle::default());
+        let ring = vec![
+            GeoPoint::new(10.0, -50.0),
+            GeoPoint::new(70.0, -50.0),
+            GeoPoint::new(70.0, 50.0),
+            GeoPoint::new(10.0, 50.0),
+            GeoPoint::new(10.0, -50.0),
+        ];
+        let polygon = GeoFeature::new("large".into(), FeatureGeometry::Polygon(vec![ring.clone()]), Default::default());
+        let line = GeoFeature::new("edge".into(), FeatureGeometry::LineString(ring), Default::default());
+        let line_to_count = |context: &RenderContext, feature: &GeoFeature| {
+            let target = RecordingTarget::new();
+            render_feature_batch(&[feature], context, &target).unwrap();
+            target.commands().iter().filter(|c| matches!(c, DrawCommand::LineTo(..))).count()
         };
+        assert_eq!(line_to_count(&context, &polygon), 4);
+        assert_eq!(line_to_count(&context, &line), 4);
+
+        let densified = context.with_densify_threshold(5.0);
+        assert!(line_to_count(&densified, &polygon) > 20);
+        assert!(line_to_count(&densified, &line) > 20);
+    }
+
+    #[test]
+    fn test_width_property_draws_tapered_lines() {
+        let mut layer_style = LayerStyle::default();
+        layer_style.line_style.width_property = Some("flow".to_string());
+        let context = RenderContext::new(Bounds::new(0.0, 0.0, 10.0, 10.0), (100.0, 100.0), 1, MapStyle::default())
+            .with_layer_style(layer_style);
+        let line = || FeatureGeometry::LineString(vec![GeoPoint::new(5.0, 1.0), GeoPoint::new(5.0, 5.0), GeoPoint::new(5.0, 9.0)]);
+        let properties = dashmap::DashMap::new();
+        properties.insert("flow".to_string(), serde_json::json!([2.0, 4.0, 6.0]));
+        let tapered = GeoFeature::new("river".into(), line(), properties);
+        let target = RecordingTarget::new();
+
+        render_feature_batch(&[&tapered], &context, &target).unwrap();
+        let commands = target.commands();
+        assert!(commands.contains(&DrawCommand::Fill));
+        assert!(!commands.contains(&DrawCommand::Stroke));
+
+        // Lines without the property keep their constant-width stroke
+        let plain = GeoFeature::new("road".into(), line(), Default::default());
+        let target = RecordingTarget::new();
+        render_feature_batch(&[&plain], &context, &target).unwrap();
+        assert!(target.commands().contains(&DrawCommand::Stroke));
+        assert!(!target.commands().contains(&DrawCommand::Fill));
+    }
+
+    #[test]
+    fn test_batch_keeps_input_order_across_geometry_kinds() {
+        let context = RenderContext::new(Bounds::new(0.0, 0.0, 10.0, 10.0), (100.0, 100.0), 1, MapStyle::default());
+        let ring = vec![GeoPoint::new(1.0, 1.0), GeoPoint::new(1.0, 4.0), GeoPoint::new(4.0, 4.0), GeoPoint::new(1.0, 1.0)];
+        // A point followed by a polygon drawn over it, e.g. because the polygon is selected
+        let point = GeoFeature::new("point".into(), FeatureGeometry::Point(GeoPoint::new(2.0, 2.0)), Default::default());
+        let polygon = GeoFeature::new("polygon".into(), FeatureGeometry::Polygon(vec![ring]), Default::default());
+        let target = RecordingTarget::new();
+
+        render_feature_batch(&[&point, &polygon], &context, &target).unwrap();
+
+        let commands = target.commands();
+        let arc = commands.iter().position(|c| matches!(c, DrawCommand::Arc(..))).unwrap();
+        let polygon_fill = commands.iter().rposition(|c| *c == DrawCommand::Fill).unwrap();
+        assert!(arc < polygon_fill);
+    }
+
+    #[test]
+    fn test_polygon_rings_share_the_scratch_buffer() {
+        let context = RenderContext::new(Bounds::new(0.0, 0.0, 10.0, 10.0), (100.0, 100.0), 1, MapStyle::default());
+        let square = |min: f64, max: f64| vec![
+            GeoPoint::new(min, min),
+            GeoPoint::new(min, max),
+            GeoPoint::new(max, max),
+            GeoPoint::new(max, min),
+            GeoPoint::new(min, min),
+        ];
+        let donut = FeatureGeometry::Polygon(vec![square(1.0, 9.0), square(4.0, 6.0)]);
+        let target = RecordingTarget::new();
+
+        render_feature_geometry(&donut, &context, &target).unwrap();
+
+        // Both rings were packed into the scratch buffer, one after the other
+        assert!(context.scratch.capacity() >= 10);
+        let commands = target.commands();
+        let starts: Vec<usize> = commands.iter().enumerate()
+            .filter_map(|(i, c)| matches!(c, DrawCommand::MoveTo(..)).then_some(i))
+            .collect();
+        assert_eq!(starts.len(), 2);
+        assert_eq!(commands[starts[0]..starts[1]].iter().filter(|c| matches!(c, DrawCommand::LineTo(..))).count(), 4);
+        assert!(matches!(commands[starts[1]], DrawCommand::MoveTo(x, _) if (x - 40.0).abs() < 1e-9));
+        assert!(commands.contains(&DrawCommand::FillEvenOdd));
+
+        // The GeoJSON pipeline packs rings the same way
+        let geometry = geojson::Geometry::new(geojson::Value::Polygon(vec![
+            vec![vec![1.0, 1.0], vec![9.0, 1.0], vec![9.0, 9.0], vec![1.0, 9.0], vec![1.0, 1.0]],
+            vec![vec![4.0, 4.0], vec![6.0, 4.0], vec![6.0, 6.0], vec![4.0, 6.0], vec![4.0, 4.0]],
+        ]));
+        let target = RecordingTarget::new();
+        render_geometry(&geometry, &context, &target).unwrap();
+        let commands = target.commands();
+        assert_eq!(commands.iter().filter(|c| matches!(c, DrawCommand::MoveTo(..))).count(), 2);
+        assert!(commands.contains(&DrawCommand::FillEvenOdd));
+    }
+
+    #[test]
+    fn test_state_cache_follows_save_restore() {
+        let recording = RecordingTarget::new();
+        let target = StateCachingTarget::new(&recording);
+
+        target.set_fill_style("#111111");
+        target.save();
+        target.set_fill_style("#222222");
+        target.restore();
+        // The canvas is back on #111111, so only a change away from it is sent
+        target.set_fill_style("#111111");
+        target.set_fill_style("#222222");
+
+        let fills: Vec<DrawCommand> = recording.commands().into_iter()
+            .filter(|c| matches!(c, DrawCommand::SetFillStyle(_)))
+            .collect();
+        assert_eq!(fills, vec![
+            DrawCommand::SetFillStyle("#111111".to_string()),
+            DrawCommand::SetFillStyle("#222222".to_string()),
+            DrawCommand::SetFillStyle("#222222".to_string()),
+        ]);
+    }
 }
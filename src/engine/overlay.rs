@@ -0,0 +1,86 @@
+use dashmap::DashMap;
+use serde_json::Value;
+use crate::model::{FeatureGeometry, FeatureId, GeoFeature, GeoPoint};
+
+// Operations combining the geometries of several features
+
+// Dissolve polygons by a property: features sharing the value of `by` are merged into one feature
+// whose geometry collects all their polygons (a Polygon for a single part, otherwise a
+// MultiPolygon). Parts are gathered as-is, shared edges are not removed. Groups keep the order in
+// which their value first appears; features without the property form a `null` group and
+// non-polygonal or undecodable features are skipped. Each result carries `by` and a `count`.
+pub const dissolve: fn(&[GeoFeature], &str) -> Vec<GeoFeature> =
+    |features, by| {
+        let mut groups: Vec<(Value, Vec<Vec<Vec<GeoPoint>>>, usize)> = Vec::new();
+        for feature in features {
+            let parts = match feature.geometry() {
+                Ok(FeatureGeometry::Polygon(rings)) => vec![rings.clone()],
+                Ok(FeatureGeometry::MultiPolygon(polygons)) => polygons.clone(),
+                _ => continue,
+            };
+            let key = feature.properties.get(by).map_or(Value::Null, |value| value.clone());
+            match groups.iter_mut().find(|(value, _, _)| *value == key) {
+                Some((_, polygons, count)) => {
+                    polygons.extend(parts);
+                    *count += 1;
+                }
+                None => groups.push((key, parts, 1)),
+            }
+        }
+
+        groups
+            .into_iter()
+            .map(|(key, mut polygons, count)| {
+                let id = match &key {
+                    Value::String(value) => FeatureId::String(value.clone()),
+                    Value::Number(number) => FeatureId::Number(number.clone()),
+                    other => FeatureId::String(other.to_string()),
+                };
+                let geometry = if polygons.len() == 1 {
+                    FeatureGeometry::Polygon(polygons.remove(0))
+                } else {
+                    FeatureGeometry::MultiPolygon(polygons)
+                };
+                let properties = DashMap::new();
+                properties.insert(by.to_string(), key);
+                properties.insert("count".to_string(), Value::from(count));
+                GeoFeature::new(id, geometry, properties)
+            })
+            .collect()
+    };
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn region_square(id: &str, region: &str, min: f64) -> GeoFeature {
+        let ring = vec![
+            GeoPoint::new(0.0, min),
+            GeoPoint::new(0.0, min + 1.0),
+            GeoPoint::new(1.0, min + 1.0),
+            GeoPoint::new(1.0, min),
+            GeoPoint::new(0.0, min),
+        ];
+        let properties = DashMap::new();
+        properties.insert("region".to_string(), Value::from(region));
+        GeoFeature::new(id.into(), FeatureGeometry::Polygon(vec![ring]), properties)
+    }
+
+    #[test]
+    fn test_dissolve_by_region() {
+        let features = vec![
+            region_square("a", "north", 0.0),
+            region_square("b", "south", 1.0),
+            region_square("c", "north", 2.0),
+        ];
+
+        let dissolved = dissolve(&features, "region");
+
+        assert_eq!(dissolved.len(), 2);
+        assert_eq!(dissolved[0].id, FeatureId::from("north"));
+        assert!(matches!(dissolved[0].geometry().unwrap(), FeatureGeometry::MultiPolygon(parts) if parts.len() == 2));
+        assert_eq!(dissolved[0].bounds.max_x, 3.0);
+        assert_eq!(dissolved[0].properties.get("count").unwrap().as_u64(), Some(2));
+        assert!(matches!(dissolved[1].geometry().unwrap(), FeatureGeometry::Polygon(_)));
+    }
+}
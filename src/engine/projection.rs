@@ -0,0 +1,98 @@
+use std::fmt::Debug;
+use crate::model::GeoBounds;
+
+// Map projections and the tile grids they define
+
+// Latitude where Web Mercator's square world ends
+pub const WEB_MERCATOR_MAX_LAT: f64 = 85.051_128_779_806_59;
+
+pub trait Projection: Debug + Send + Sync {
+    /// Position of `lng`/`lat` on the projection's tile grid, normalized so the whole world spans
+    /// `0.0..=1.0` on both axes with y growing southwards
+    fn to_tile_space(&self, lng: f64, lat: f64) -> (f64, f64);
+}
+
+// Spherical Web Mercator, the grid of standard XYZ slippy-map tiles
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct WebMercator;
+
+impl Projection for WebMercator {
+    fn to_tile_space(&self, lng: f64, lat: f64) -> (f64, f64) {
+        let lat = lat.clamp(-WEB_MERCATOR_MAX_LAT, WEB_MERCATOR_MAX_LAT).to_radians();
+        let y = (1.0 - lat.tan().asinh() / std::f64::consts::PI) / 2.0;
+        ((lng + 180.0) / 360.0, y)
+    }
+}
+
+// Plate carrée: longitude and latitude map linearly onto the grid
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Equirectangular;
+
+impl Projection for Equirectangular {
+    fn to_tile_space(&self, lng: f64, lat: f64) -> (f64, f64) {
+        ((lng + 180.0) / 360.0, (90.0 - lat) / 180.0)
+    }
+}
+
+// Tile `(x, y)` at zoom `z` containing `lng`/`lat`, clamped to the grid
+pub fn tile_at(projection: &dyn Projection, lng: f64, lat: f64, z: u8) -> (u32, u32) {
+    let tile_count = (1u64 << z) as f64;
+    let (x, y) = projection.to_tile_space(lng, lat);
+    let index = |v: f64| (v * tile_count).floor().clamp(0.0, tile_count - 1.0) as u32;
+    (index(x), index(y))
+}
+
+// Every `(x, y, z)` tile overlapping `bounds` on the projection's grid. Columns wrap around
+// the tile count, so bounds crossing the antimeridian (`min_x > max_x`) or reaching past ±180°
+// (as a viewport over world copies does) get the tiles on both edges of the grid.
+pub fn tiles_covering(projection: &dyn Projection, bounds: &GeoBounds, z: u8) -> Vec<(u32, u32, u8)> {
+    let tile_count = 1i64 << z;
+    let max_lng = if bounds.crosses_antimeridian() { bounds.max_x + 360.0 } else { bounds.max_x };
+    let corners = [
+        (bounds.min_x, bounds.min_y),
+        (bounds.min_x, bounds.max_y),
+        (max_lng, bounds.min_y),
+        (max_lng, bounds.max_y),
+    ];
+    // Unclamped column, so positions past 180° land in columns beyond the last
+    let column = |(lng, lat): &(f64, f64)| (projection.to_tile_space(*lng, *lat).0 * tile_count as f64).floor() as i64;
+    let row = |(lng, lat): &(f64, f64)| tile_at(projection, *lng, *lat, z).1;
+    let (min_x, max_x) = (corners.iter().map(column).min().unwrap(), corners.iter().map(column).max().unwrap());
+    let (min_y, max_y) = (corners.iter().map(row).min().unwrap(), corners.iter().map(row).max().unwrap());
+    let columns: Vec<u32> = if max_x - min_x + 1 >= tile_count {
+        (0..tile_count as u32).collect()
+    } else {
+        (min_x..=max_x).map(|x| x.rem_euclid(tile_count) as u32).collect()
+    };
+
+    columns
+        .into_iter()
+        .flat_map(|x| (min_y..=max_y).map(move |y| (x, y, z)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_web_mercator_tile_matches_slippy_map_scheme() {
+        // Berlin at zoom 10 is OSM tile 10/550/335
+        assert_eq!(tile_at(&WebMercator, 13.4050, 52.5200, 10), (550, 335));
+        assert_eq!(tile_at(&Equirectangular, 13.4050, 52.5200, 10), (550, 213));
+    }
+
+    #[test]
+    fn test_tiles_wrap_across_antimeridian() {
+        // Fiji spans 177°E to 178°W; at zoom 4 that is the last column and the first
+        let fiji = GeoBounds::new(177.0, -19.2, -178.0, -16.0);
+        let tiles = tiles_covering(&WebMercator, &fiji, 4);
+        assert_eq!(tiles, vec![(15, 8, 4), (0, 8, 4)]);
+
+        // The same area written continuously past 180° wraps to the same columns
+        assert_eq!(tiles_covering(&WebMercator, &GeoBounds::new(177.0, -19.2, 182.0, -16.0), 4), tiles);
+        // A box wider than the world covers every column once
+        let wide = tiles_covering(&Equirectangular, &GeoBounds::new(-200.0, 10.0, 200.0, 20.0), 2);
+        assert_eq!(wide.iter().map(|tile| tile.0).collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+    }
+}
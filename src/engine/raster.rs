@@ -0,0 +1,508 @@
+use std::cell::RefCell;
+use crate::engine::target::DrawTarget;
+use crate::model::GeoArrowResult;
+
+// Software rasterizer implementing DrawTarget over an RGBA buffer, for headless rendering and tests
+
+const CURVE_STEPS: usize = 16;
+const ARC_STEPS_PER_TURN: f64 = 64.0;
+
+type Rgba = [u8; 4];
+
+#[derive(Clone, Debug)]
+struct SubPath {
+    points: Vec<(f64, f64)>,
+    closed: bool,
+}
+
+#[derive(Clone)]
+struct DrawState {
+    fill: Rgba,
+    stroke: Rgba,
+    line_width: f64,
+    line_dash: Vec<f64>,
+    global_alpha: f64,
+    clip: Option<Vec<bool>>,
+}
+
+struct RasterState {
+    pixels: Vec<u8>,
+    path: Vec<SubPath>,
+    current: DrawState,
+    saved: Vec<DrawState>,
+}
+
+pub struct RasterTarget {
+    width: u32,
+    height: u32,
+    state: RefCell<RasterState>,
+}
+
+impl RasterTarget {
+    pub fn new(width: u32, height: u32) -> Self {
+        RasterTarget {
+            width,
+            height,
+            state: RefCell::new(RasterState {
+                pixels: vec![0; (width * height * 4) as usize],
+                path: Vec::new(),
+                current: DrawState {
+                    fill: [0, 0, 0, 255],
+                    stroke: [0, 0, 0, 255],
+                    line_width: 1.0,
+                    line_dash: Vec::new(),
+                    global_alpha: 1.0,
+                    clip: None,
+                },
+                saved: Vec::new(),
+            }),
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// RGBA bytes, row-major from the top-left corner
+    pub fn pixels(&self) -> Vec<u8> {
+        self.state.borrow().pixels.clone()
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let state = self.state.borrow();
+        let i = ((y * self.width + x) * 4) as usize;
+        [state.pixels[i], state.pixels[i + 1], state.pixels[i + 2], state.pixels[i + 3]]
+    }
+
+    fn current_point(&self) -> Option<(f64, f64)> {
+        self.state.borrow().path.last().and_then(|sub| sub.points.last().copied())
+    }
+
+    fn push_point(&self, x: f64, y: f64) {
+        let mut state = self.state.borrow_mut();
+        match state.path.last_mut() {
+            Some(sub) if !sub.closed => sub.points.push((x, y)),
+            _ => state.path.push(SubPath { points: vec![(x, y)], closed: false }),
+        }
+    }
+
+    // Scanline coverage of a set of closed polygons, by nonzero winding or by even-odd parity
+    fn coverage(&self, polygons: &[Vec<(f64, f64)>], even_odd: bool) -> Coverage {
+        let mut coverage = Coverage::covering(polygons, self.width, self.height);
+        coverage.fill(polygons, even_odd);
+        coverage
+    }
+
+    fn paint(&self, coverage: &Coverage, color: Rgba) {
+        let mut state = self.state.borrow_mut();
+        let clip = state.current.clip.clone();
+        let alpha = color[3] as f64 / 255.0 * state.current.global_alpha;
+        for i in coverage.pixels(self.width as usize) {
+            if clip.as_ref().is_some_and(|clip| !clip[i]) {
+                continue;
+            }
+            let pixel = &mut state.pixels[i * 4..i * 4 + 4];
+            for channel in 0..3 {
+                pixel[channel] = (color[channel] as f64 * alpha + pixel[channel] as f64 * (1.0 - alpha)).round() as u8;
+            }
+            pixel[3] = (255.0 * (alpha + pixel[3] as f64 / 255.0 * (1.0 - alpha))).round() as u8;
+        }
+    }
+
+    fn rect_polygon(x: f64, y: f64, width: f64, height: f64) -> Vec<(f64, f64)> {
+        vec![(x, y), (x + width, y), (x + width, y + height), (x, y + height)]
+    }
+}
+
+impl DrawTarget for RasterTarget {
+    fn begin_path(&self) {
+        self.state.borrow_mut().path.clear();
+    }
+
+    fn move_to(&self, x: f64, y: f64) {
+        self.state.borrow_mut().path.push(SubPath { points: vec![(x, y)], closed: false });
+    }
+
+    fn line_to(&self, x: f64, y: f64) {
+        self.push_point(x, y);
+    }
+
+    fn quadratic_curve_to(&self, cpx: f64, cpy: f64, x: f64, y: f64) {
+        let (x0, y0) = self.current_point().unwrap_or((cpx, cpy));
+        for step in 1..=CURVE_STEPS {
+            let t = step as f64 / CURVE_STEPS as f64;
+            let mt = 1.0 - t;
+            self.push_point(
+                mt * mt * x0 + 2.0 * mt * t * cpx + t * t * x,
+                mt * mt * y0 + 2.0 * mt * t * cpy + t * t * y,
+            );
+        }
+    }
+
+    fn bezier_curve_to(&self, cp1x: f64, cp1y: f64, cp2x: f64, cp2y: f64, x: f64, y: f64) {
+        let (x0, y0) = self.current_point().unwrap_or((cp1x, cp1y));
+        for step in 1..=CURVE_STEPS {
+            let t = step as f64 / CURVE_STEPS as f64;
+            let mt = 1.0 - t;
+            self.push_point(
+                mt * mt * mt * x0 + 3.0 * mt * mt * t * cp1x + 3.0 * mt * t * t * cp2x + t * t * t * x,
+                mt * mt * mt * y0 + 3.0 * mt * mt * t * cp1y + 3.0 * mt * t * t * cp2y + t * t * t * y,
+            );
+        }
+    }
+
+    fn arc(&self, x: f64, y: f64, radius: f64, start_angle: f64, end_angle: f64) -> GeoArrowResult<()> {
+        let sweep = end_angle - start_angle;
+        let steps = ((sweep.abs() / std::f64::consts::TAU) * ARC_STEPS_PER_TURN).ceil().max(1.0) as usize;
+        for step in 0..=steps {
+            let angle = start_angle + sweep * step as f64 / steps as f64;
+            self.push_point(x + radius * angle.cos(), y + radius * angle.sin());
+        }
+        Ok(())
+    }
+
+    fn close_path(&self) {
+        let mut state = self.state.borrow_mut();
+        if let Some(sub) = state.path.last_mut() {
+            sub.closed = true;
+        }
+    }
+
+    fn fill(&self) {
+        let polygons: Vec<_> = self.state.borrow().path.iter().map(|sub| sub.points.clone()).collect();
+        let coverage = self.coverage(&polygons, false);
+        let color = self.state.borrow().current.fill;
+        self.paint(&coverage, color);
+    }
+
+    fn fill_even_odd(&self) {
+        let polygons: Vec<_> = self.state.borrow().path.iter().map(|sub| sub.points.clone()).collect();
+        let coverage = self.coverage(&polygons, true);
+        let color = self.state.borrow().current.fill;
+        self.paint(&coverage, color);
+    }
+
+    fn stroke(&self) {
+        let (path, half_width, color, dash) = {
+            let state = self.state.borrow();
+            let current = &state.current;
+            (state.path.clone(), current.line_width.max(1.0) / 2.0, current.stroke, current.line_dash.clone())
+        };
+
+        // Each segment becomes a quad of the line width; quads are unioned before painting
+        let mut quads = Vec::new();
+        for sub in &path {
+            let mut segments: Vec<_> = sub.points.windows(2).map(|w| (w[0], w[1])).collect();
+            if sub.closed && sub.points.len() > 2 {
+                segments.push((sub.points[sub.points.len() - 1], sub.points[0]));
+            }
+            if !dash.is_empty() {
+                segments = dash_segments(&segments, &dash);
+            }
+            for ((x0, y0), (x1, y1)) in segments {
+                let length = ((x1 - x0).powi(2) + (y1 - y0).powi(2)).sqrt();
+                if length == 0.0 {
+                    continue;
+                }
+                let (nx, ny) = (-(y1 - y0) / length * half_width, (x1 - x0) / length * half_width);
+                quads.push(vec![(x0 + nx, y0 + ny), (x1 + nx, y1 + ny), (x1 - nx, y1 - ny), (x0 - nx, y0 - ny)]);
+            }
+        }
+        let mut coverage = Coverage::covering(&quads, self.width, self.height);
+        for quad in &quads {
+            coverage.fill(std::slice::from_ref(quad), false);
+        }
+        self.paint(&coverage, color);
+    }
+
+    fn set_fill_style(&self, style: &str) {
+        self.state.borrow_mut().current.fill = parse_color(style);
+    }
+
+    fn set_stroke_style(&self, style: &str) {
+        self.state.borrow_mut().current.stroke = parse_color(style);
+    }
+
+    fn set_line_width(&self, width: f64) {
+        self.state.borrow_mut().current.line_width = width;
+    }
+
+    // Follows the canvas rules: an odd-length list is repeated to even length, and a list with a
+    // negative, non-finite or all-zero length is ignored
+    fn set_line_dash(&self, segments: &[f64]) -> GeoArrowResult<()> {
+        if segments.iter().any(|length| !length.is_finite() || *length < 0.0) {
+            return Ok(());
+        }
+        if !segments.is_empty() && segments.iter().all(|length| *length == 0.0) {
+            return Ok(());
+        }
+        let mut dash = segments.to_vec();
+        if dash.len() % 2 == 1 {
+            dash.extend_from_slice(segments);
+        }
+        self.state.borrow_mut().current.line_dash = dash;
+        Ok(())
+    }
+
+    fn set_global_alpha(&self, alpha: f64) {
+        // The canvas ignores values outside [0, 1]
+        if (0.0..=1.0).contains(&alpha) {
+            self.state.borrow_mut().current.global_alpha = alpha;
+        }
+    }
+
+    // Coverage is already binary, so output is always aliased
+    fn set_image_smoothing_enabled(&self, _enabled: bool) {}
+
+    // Shadows are not rasterized; the pick buffer and exports only need the shapes themselves
+    fn set_shadow_color(&self, _color: &str) {}
+
+    fn set_shadow_blur(&self, _blur: f64) {}
+
+    fn set_shadow_offset_x(&self, _offset: f64) {}
+
+    fn set_shadow_offset_y(&self, _offset: f64) {}
+
+    fn clear_rect(&self, x: f64, y: f64, width: f64, height: f64) {
+        let coverage = self.coverage(&[Self::rect_polygon(x, y, width, height)], false);
+        let mut state = self.state.borrow_mut();
+        for i in coverage.pixels(self.width as usize) {
+            state.pixels[i * 4..i * 4 + 4].fill(0);
+        }
+    }
+
+    fn fill_rect(&self, x: f64, y: f64, width: f64, height: f64) {
+        let coverage = self.coverage(&[Self::rect_polygon(x, y, width, height)], false);
+        let color = self.state.borrow().current.fill;
+        self.paint(&coverage, color);
+    }
+
+    // No font rasterization; text is skipped
+    fn fill_text(&self, _text: &str, _x: f64, _y: f64) -> GeoArrowResult<()> {
+        Ok(())
+    }
+
+    fn save(&self) {
+        let mut state = self.state.borrow_mut();
+        let current = state.current.clone();
+        state.saved.push(current);
+    }
+
+    fn restore(&self) {
+        let mut state = self.state.borrow_mut();
+        if let Some(saved) = state.saved.pop() {
+            state.current = saved;
+        }
+    }
+
+    fn clip(&self) {
+        let polygons: Vec<_> = self.state.borrow().path.iter().map(|sub| sub.points.clone()).collect();
+        let mut mask = vec![false; (self.width * self.height) as usize];
+        for i in self.coverage(&polygons, false).pixels(self.width as usize) {
+            mask[i] = true;
+        }
+        let mut state = self.state.borrow_mut();
+        state.current.clip = Some(match state.current.clip.take() {
+            Some(existing) => existing.iter().zip(mask).map(|(a, b)| *a && b).collect(),
+            None => mask,
+        });
+    }
+}
+
+// Pixels covered by a path, kept only for the window of the image its bounding box touches
+// so filling and painting cost scales with the path rather than the image
+struct Coverage {
+    left: usize,
+    top: usize,
+    columns: usize,
+    rows: usize,
+    mask: Vec<bool>,
+}
+
+impl Coverage {
+    // Nothing covered yet, over the pixels whose centers `polygons` can reach on a
+    // `width` x `height` image
+    fn covering(polygons: &[Vec<(f64, f64)>], width: u32, height: u32) -> Self {
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (f64::INFINITY, f64::INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY);
+        for &(x, y) in polygons.iter().flatten().filter(|(x, y)| x.is_finite() && y.is_finite()) {
+            (min_x, min_y, max_x, max_y) = (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y));
+        }
+        if min_x > max_x {
+            return Coverage { left: 0, top: 0, columns: 0, rows: 0, mask: Vec::new() };
+        }
+        // A pixel is covered when its center is, so the window runs between the pixel centers
+        // inside the bounding box
+        let first = |min: f64, limit: u32| (min - 0.5).ceil().clamp(0.0, limit as f64) as usize;
+        let (left, top) = (first(min_x, width), first(min_y, height));
+        let (right, bottom) = (first(max_x, width), first(max_y, height));
+        let (columns, rows) = (right - left, bottom - top);
+        Coverage { left, top, columns, rows, mask: vec![false; columns * rows] }
+    }
+
+    // Add the pixels inside `polygons`, which must lie within the window
+    fn fill(&mut self, polygons: &[Vec<(f64, f64)>], even_odd: bool) {
+        let (left, right) = (self.left as f64, (self.left + self.columns) as f64);
+        for row in 0..self.rows {
+            let scan_y = (self.top + row) as f64 + 0.5;
+            let mut crossings: Vec<(f64, i32)> = Vec::new();
+            for polygon in polygons {
+                let n = polygon.len();
+                for i in 0..n {
+                    let (x0, y0) = polygon[i];
+                    let (x1, y1) = polygon[(i + 1) % n];
+                    if (y0 <= scan_y) != (y1 <= scan_y) {
+                        let t = (scan_y - y0) / (y1 - y0);
+                        crossings.push((x0 + t * (x1 - x0), if y1 > y0 { 1 } else { -1 }));
+                    }
+                }
+            }
+            crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+            let mut winding = 0;
+            for pair in crossings.windows(2) {
+                winding += pair[0].1;
+                let inside = if even_odd { winding % 2 != 0 } else { winding != 0 };
+                if !inside {
+                    continue;
+                }
+                let start = (pair[0].0 - 0.5).ceil().clamp(left, right) as usize;
+                let end = (pair[1].0 - 0.5).ceil().clamp(left, right) as usize;
+                for column in start..end {
+                    self.mask[row * self.columns + column - self.left] = true;
+                }
+            }
+        }
+    }
+
+    // Row-major indices into an `image_width`-wide image of the covered pixels
+    fn pixels(&self, image_width: usize) -> impl Iterator<Item = usize> + '_ {
+        self.mask.iter().enumerate().filter(|(_, covered)| **covered).map(move |(i, _)| {
+            (self.top + i / self.columns) * image_width + self.left + i % self.columns
+        })
+    }
+}
+
+// Cut a sub-path's segments into the dashed pieces of `pattern` (alternating dash and gap
+// lengths). The pattern runs continuously across the segment joints.
+fn dash_segments(segments: &[((f64, f64), (f64, f64))], pattern: &[f64]) -> Vec<((f64, f64), (f64, f64))> {
+    let mut dashes = Vec::new();
+    let (mut index, mut remaining) = (0, pattern[0]);
+    for &((x0, y0), (x1, y1)) in segments {
+        let length = (x1 - x0).hypot(y1 - y0);
+        let point_at = |distance: f64| (x0 + (x1 - x0) * distance / length, y0 + (y1 - y0) * distance / length);
+        let mut travelled = 0.0;
+        while travelled < length {
+            let step = remaining.min(length - travelled);
+            if index % 2 == 0 && step > 0.0 {
+                dashes.push((point_at(travelled), point_at(travelled + step)));
+            }
+            travelled += step;
+            remaining -= step;
+            if remaining <= 0.0 {
+                index = (index + 1) % pattern.len();
+                remaining = pattern[index];
+            }
+        }
+    }
+    dashes
+}
+
+// CSS color parsing for the subset of syntaxes the styles use
+pub fn parse_color(style: &str) -> [u8; 4] {
+    let style = style.trim();
+    if let Some(hex) = style.strip_prefix('#') {
+        let digits: Vec<u8> = hex
+            .chars()
+            .filter_map(|c| c.to_digit(16).map(|d| d as u8))
+            .collect();
+        return match digits.len() {
+            3 => [digits[0] * 17, digits[1] * 17, digits[2] * 17, 255],
+            6 => [digits[0] * 16 + digits[1], digits[2] * 16 + digits[3], digits[4] * 16 + digits[5], 255],
+            8 => [
+                digits[0] * 16 + digits[1],
+                digits[2] * 16 + digits[3],
+                digits[4] * 16 + digits[5],
+                digits[6] * 16 + digits[7],
+            ],
+            _ => [0, 0, 0, 255],
+        };
+    }
+
+    if let Some(args) = style
+        .strip_prefix("rgba(")
+        .or_else(|| style.strip_prefix("rgb("))
+        .and_then(|rest| rest.strip_suffix(')'))
+    {
+        let parts: Vec<f64> = args.split(',').filter_map(|p| p.trim().parse().ok()).collect();
+        if parts.len() >= 3 {
+            let alpha = parts.get(3).copied().unwrap_or(1.0).clamp(0.0, 1.0);
+            return [
+                parts[0].clamp(0.0, 255.0) as u8,
+                parts[1].clamp(0.0, 255.0) as u8,
+                parts[2].clamp(0.0, 255.0) as u8,
+                (alpha * 255.0).round() as u8,
+            ];
+        }
+    }
+
+    match style {
+        "transparent" => [0, 0, 0, 0],
+        "white" => [255, 255, 255, 255],
+        "red" => [255, 0, 0, 255],
+        "green" => [0, 128, 0, 255],
+        "blue" => [0, 0, 255, 255],
+        _ => [0, 0, 0, 255],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fill_rect_paints_only_inside() {
+        let target = RasterTarget::new(10, 10);
+        target.set_fill_style("#FF0000");
+        target.fill_rect(2.0, 2.0, 4.0, 4.0);
+
+        assert_eq!(target.pixel(3, 3), [255, 0, 0, 255]);
+        assert_eq!(target.pixel(7, 7), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_coverage_only_spans_the_path_window() {
+        let coverage = Coverage::covering(&[RasterTarget::rect_polygon(10.0, 20.0, 5.0, 3.0)], 4000, 4000);
+        assert_eq!((coverage.left, coverage.top, coverage.columns, coverage.rows), (10, 20, 5, 3));
+        // Clamped to the image
+        let coverage = Coverage::covering(&[RasterTarget::rect_polygon(-5.0, 8.0, 10.0, 10.0)], 10, 10);
+        assert_eq!((coverage.left, coverage.top, coverage.columns, coverage.rows), (0, 8, 5, 2));
+
+        // A stroke running off the image is cut at the edge
+        let target = RasterTarget::new(10, 10);
+        target.set_line_width(2.0);
+        target.begin_path();
+        target.move_to(-5.0, 5.0);
+        target.line_to(5.0, 5.0);
+        target.line_to(5.0, 15.0);
+        target.stroke();
+        let painted: Vec<(u32, u32)> = (0..10)
+            .flat_map(|y| (0..10).map(move |x| (x, y)))
+            .filter(|&(x, y)| target.pixel(x, y)[3] != 0)
+            .collect();
+        // Two 2px bands of 10 pixels each, sharing one pixel at the corner
+        assert_eq!(painted.len(), 19);
+        assert!(painted.contains(&(0, 4)) && painted.contains(&(5, 9)));
+        assert!(!painted.contains(&(7, 5)));
+    }
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("#00FF00"), [0, 255, 0, 255]);
+        assert_eq!(parse_color("#fff"), [255, 255, 255, 255]);
+        assert_eq!(parse_color("rgba(0, 255, 0, 0.3)"), [0, 255, 0, 77]);
+    }
+}
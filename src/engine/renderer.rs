@@ -1,86 +1,302 @@
-use web_sys::CanvasRenderingContext2d;
+use std::borrow::Cow;
 use crate::engine::RenderContext;
+use crate::engine::target::DrawTarget;
+use crate::engine::text::{ApproximateTextMeasure, TextMeasure};
 use crate::model::GeoArrowResult;
 use crate::error::GeoArrowError;
 
 // Higher-order rendering functions
 
 // Canvas setup function
-pub const setup_canvas_context: fn(&CanvasRenderingContext2d, &RenderContext) -> GeoArrowResult<()> =
+pub const setup_canvas_context: fn(&dyn DrawTarget, &RenderContext) -> GeoArrowResult<()> =
     |context, render_context| {
         let style = &render_context.style;
 
-        context.set_fill_style_str(&style.polygon_fill);
-        context.set_stroke_style_str(&style.polygon_stroke);
+        context.set_image_smoothing_enabled(style.antialias);
+        context.set_fill_style(&style.polygon_fill);
+        context.set_stroke_style(&style.polygon_stroke);
         context.set_line_width(style.line_width);
 
         Ok(())
     };
 
+// Integer pixel snapping for aliased output; coordinates pass through untouched when antialiasing
+pub const snap_to_pixels: for<'a> fn(&'a [(f64, f64)], &RenderContext) -> Cow<'a, [(f64, f64)]> =
+    |points, render_context| {
+        if render_context.style.antialias {
+            Cow::Borrowed(points)
+        } else {
+            Cow::Owned(points.iter().map(|(x, y)| (x.round(), y.round())).collect())
+        }
+    };
+
+// Drop shadow: installs the layer's shadow around `draw_fn`, then clears it again so it doesn't
+// bleed into later draws. Without a configured shadow `draw_fn` runs unchanged.
+pub const render_with_shadow: fn(&RenderContext, &dyn DrawTarget, &dyn Fn(&dyn DrawTarget) -> GeoArrowResult<()>) -> GeoArrowResult<()> =
+    |render_context, canvas_context, draw_fn| {
+        let Some(shadow) = &render_context.layer_style.shadow else {
+            return draw_fn(canvas_context);
+        };
+
+        canvas_context.set_shadow_color(&shadow.color);
+        canvas_context.set_shadow_blur(shadow.blur);
+        canvas_context.set_shadow_offset_x(shadow.offset_x);
+        canvas_context.set_shadow_offset_y(shadow.offset_y);
+        let result = draw_fn(canvas_context);
+        canvas_context.set_shadow_color("rgba(0, 0, 0, 0)");
+        canvas_context.set_shadow_blur(0.0);
+        canvas_context.set_shadow_offset_x(0.0);
+        canvas_context.set_shadow_offset_y(0.0);
+        result
+    };
+
+// Wireframe mode: installs the single outline stroke and reports whether fills must be skipped
+pub const apply_wireframe_stroke: fn(&RenderContext, &dyn DrawTarget) -> bool =
+    |render_context, canvas_context| {
+        if render_context.wireframe {
+            canvas_context.set_stroke_style(&render_context.wireframe_color);
+            canvas_context.set_line_width(render_context.wireframe_width);
+        }
+        render_context.wireframe
+    };
+
+// Layer opacity through the target's global alpha. Wireframe outlines stay opaque; callers
+// reset the alpha to 1.0 once the feature is drawn.
+pub const apply_opacity: fn(&RenderContext, &dyn DrawTarget, f32) =
+    |render_context, canvas_context, opacity| {
+        if !render_context.wireframe {
+            canvas_context.set_global_alpha(opacity as f64);
+        }
+    };
+
 // Point rendering functions
-pub const render_points: fn(&[(f64, f64)], &RenderContext, &CanvasRenderingContext2d) -> GeoArrowResult<()> =
+pub const render_points: fn(&[(f64, f64)], &RenderContext, &dyn DrawTarget) -> GeoArrowResult<()> =
     |points, render_context, canvas_context| {
-        let style = &render_context.style;
+        let style = &render_context.layer_style.point_style;
 
-        canvas_context.set_fill_style_str(&style.point_color);
+        if apply_wireframe_stroke(render_context, canvas_context) {
+            return snap_to_pixels(points, render_context).iter().try_for_each(|(x, y)| {
+                canvas_context.begin_path();
+                canvas_context.arc(*x, *y, style.radius, 0.0, 2.0 * std::f64::consts::PI)?;
+                canvas_context.stroke();
+                Ok(())
+            });
+        }
+        canvas_context.set_fill_style(&style.color);
+        apply_opacity(render_context, canvas_context, style.opacity);
 
-        points.iter()
-            .map(|(x, y)| render_single_point(*x, *y, style.point_radius, canvas_context))
-            .collect::<Result<Vec<_>, _>>()
-            .map(|_| ())
+        let result = render_with_shadow(render_context, canvas_context, &|canvas_context| {
+            snap_to_pixels(points, render_context).iter()
+                .try_for_each(|(x, y)| render_single_point(*x, *y, style.radius, canvas_context))
+        });
+        canvas_context.set_global_alpha(1.0);
+        result
     };
 
-pub const render_single_point: fn(f64, f64, f64, &CanvasRenderingContext2d) -> GeoArrowResult<()> =
+pub const render_single_point: fn(f64, f64, f64, &dyn DrawTarget) -> GeoArrowResult<()> =
     |x, y, radius, context| {
         context.begin_path();
-        context.arc(x, y, radius, 0.0, 2.0 * std::f64::consts::PI)
-            .map_err(|_| GeoArrowError::Wasm("Failed to draw arc".to_string()))?;
+        context.arc(x, y, radius, 0.0, 2.0 * std::f64::consts::PI)?;
         context.fill();
         Ok(())
     };
 
 // Line rendering functions
-pub const render_linestring: fn(&[(f64, f64)], &RenderContext, &CanvasRenderingContext2d) -> GeoArrowResult<()> =
+pub const render_linestring: fn(&[(f64, f64)], &RenderContext, &dyn DrawTarget) -> GeoArrowResult<()> =
     |points, render_context, canvas_context| {
         if points.is_empty() {
             return Ok(());
         }
 
-        let style = &render_context.style;
-        canvas_context.set_stroke_style_str(&style.line_color);
-        canvas_context.set_line_width(style.line_width);
+        let line_style = &render_context.layer_style.line_style;
+        if !apply_wireframe_stroke(render_context, canvas_context) {
+            canvas_context.set_stroke_style(&line_style.color);
+            canvas_context.set_line_width(line_style.width);
+        }
+
+        let points = snap_to_pixels(points, render_context);
+        if line_style.smooth {
+            draw_smooth_path(&points, canvas_context)?;
+        } else {
+            draw_path(&points, canvas_context)?;
+        }
+        apply_opacity(render_context, canvas_context, line_style.opacity);
+        let stroked = match &line_style.dash_pattern {
+            Some(pattern) => canvas_context.set_line_dash(pattern).map(|()| canvas_context.stroke()),
+            None => {
+                canvas_context.stroke();
+                Ok(())
+            }
+        };
+        // Back to solid and opaque so the next feature isn't dashed or faded, even when the
+        // dash pattern was rejected
+        let reset = match line_style.dash_pattern {
+            Some(_) => canvas_context.set_line_dash(&[]),
+            None => Ok(()),
+        };
+        canvas_context.set_global_alpha(1.0);
+        stroked.and(reset)
+    };
+
+// Tapered line rendering: instead of a constant-width stroke, fills the ribbon polygon built
+// from one width per vertex. Missing widths repeat the last one, or the style's line width.
+pub const render_tapered_linestring: fn(&[(f64, f64)], &[f64], &RenderContext, &dyn DrawTarget) -> GeoArrowResult<()> =
+    |points, widths, render_context, canvas_context| {
+        let line_style = &render_context.layer_style.line_style;
+        let ribbon = tapered_ribbon(points, widths, line_style.width);
+        if ribbon.is_empty() {
+            return Ok(());
+        }
 
-        draw_path(points, canvas_context)?;
-        canvas_context.stroke();
+        let wireframe = apply_wireframe_stroke(render_context, canvas_context);
+        if !wireframe {
+            canvas_context.set_fill_style(&line_style.color);
+        }
+        draw_path(&snap_to_pixels(&ribbon, render_context), canvas_context)?;
+        canvas_context.close_path();
+        if wireframe {
+            canvas_context.stroke();
+        } else {
+            apply_opacity(render_context, canvas_context, line_style.opacity);
+            canvas_context.fill();
+            canvas_context.set_global_alpha(1.0);
+        }
         Ok(())
     };
 
+// Longest miter at a joint, in half-widths. Sharper turns are clamped so the ribbon doesn't spike.
+const MITER_LIMIT: f64 = 2.0;
+
+// Ribbon polygon around a polyline: the left offsets in order followed by the right offsets in
+// reverse, each `width / 2` from the line. Interior vertices offset along the miter (bisector of
+// the adjacent segment normals), clamped to `MITER_LIMIT` on sharp turns. Repeated vertices are
+// skipped; fewer than two distinct vertices give an empty ribbon.
+pub const tapered_ribbon: fn(&[(f64, f64)], &[f64], f64) -> Vec<(f64, f64)> =
+    |points, widths, default_width| {
+        let mut vertices: Vec<((f64, f64), f64)> = Vec::with_capacity(points.len());
+        for (i, point) in points.iter().enumerate() {
+            let width = widths.get(i).or(widths.last()).copied().unwrap_or(default_width);
+            match vertices.last_mut() {
+                Some((last, last_width)) if last == point => *last_width = last_width.max(width),
+                _ => vertices.push((*point, width)),
+            }
+        }
+        if vertices.len() < 2 {
+            return Vec::new();
+        }
+
+        let normal = |a: (f64, f64), b: (f64, f64)| {
+            let length = (b.0 - a.0).hypot(b.1 - a.1);
+            (-(b.1 - a.1) / length, (b.0 - a.0) / length)
+        };
+        let last = vertices.len() - 1;
+        let offsets: Vec<(f64, f64)> = (0..=last)
+            .map(|i| {
+                let half_width = vertices[i].1.max(0.0) / 2.0;
+                let incoming = (i > 0).then(|| normal(vertices[i - 1].0, vertices[i].0));
+                let outgoing = (i < last).then(|| normal(vertices[i].0, vertices[i + 1].0));
+                let (n1, n2) = match (incoming, outgoing) {
+                    (Some(n1), Some(n2)) => (n1, n2),
+                    (Some(n), None) | (None, Some(n)) => (n, n),
+                    (None, None) => unreachable!("at least two vertices"),
+                };
+                let bisector = (n1.0 + n2.0, n1.1 + n2.1);
+                let bisector_length = bisector.0.hypot(bisector.1);
+                // A full reversal has no bisector; fall back to the incoming normal
+                if bisector_length < 1e-9 {
+                    return (n1.0 * half_width, n1.1 * half_width);
+                }
+                let miter = (bisector.0 / bisector_length, bisector.1 / bisector_length);
+                let cos_half_angle = miter.0 * n1.0 + miter.1 * n1.1;
+                let length = (half_width / cos_half_angle).min(half_width * MITER_LIMIT);
+                (miter.0 * length, miter.1 * length)
+            })
+            .collect();
+
+        let left = vertices.iter().zip(&offsets).map(|(((x, y), _), (dx, dy))| (x + dx, y + dy));
+        let right = vertices.iter().zip(&offsets).rev().map(|(((x, y), _), (dx, dy))| (x - dx, y - dy));
+        left.chain(right).collect()
+    };
+
 // Polygon rendering functions
-pub const render_polygon: fn(&[(f64, f64)], &RenderContext, &CanvasRenderingContext2d) -> GeoArrowResult<()> =
-    |points, render_context, canvas_context| {
-        if points.is_empty() {
+pub const render_polygon: fn(&[(f64, f64)], &RenderContext, &dyn DrawTarget) -> GeoArrowResult<()> =
+    |points, render_context, canvas_context| render_rings(&[points], render_context, canvas_context);
+
+// Outer ring followed by its holes. Every ring becomes a sub-path of one path, filled with the
+// even-odd rule so the holes stay empty whatever their winding order.
+pub const render_polygon_with_holes: fn(&[Vec<(f64, f64)>], &RenderContext, &dyn DrawTarget) -> GeoArrowResult<()> =
+    |rings, render_context, canvas_context| {
+        let rings: Vec<&[(f64, f64)]> = rings.iter().map(Vec::as_slice).collect();
+        render_rings(&rings, render_context, canvas_context)
+    };
+
+// Rings packed back to back in one buffer, as the engine's scratch buffer holds them: ring `i`
+// ends at `ring_ends[i]` and starts where the previous one ended
+pub const render_polygon_packed: fn(&[(f64, f64)], &[usize], &RenderContext, &dyn DrawTarget) -> GeoArrowResult<()> =
+    |points, ring_ends, render_context, canvas_context| {
+        let starts = std::iter::once(0).chain(ring_ends.iter().copied());
+        let rings: Vec<&[(f64, f64)]> = starts.zip(ring_ends).map(|(start, &end)| &points[start..end]).collect();
+        render_rings(&rings, render_context, canvas_context)
+    };
+
+const render_rings: fn(&[&[(f64, f64)]], &RenderContext, &dyn DrawTarget) -> GeoArrowResult<()> =
+    |rings, render_context, canvas_context| {
+        let rings: Vec<_> = rings.iter().filter(|ring| !ring.is_empty()).collect();
+        if rings.is_empty() {
             return Ok(());
         }
 
-        let style = &render_context.style;
-        canvas_context.set_fill_style_str(&style.polygon_fill);
-        canvas_context.set_stroke_style_str(&style.polygon_stroke);
-        canvas_context.set_line_width(style.line_width);
+        let style = &render_context.layer_style.polygon_style;
+        let wireframe = apply_wireframe_stroke(render_context, canvas_context);
+        if !wireframe {
+            canvas_context.set_fill_style(&style.fill_color);
+            canvas_context.set_stroke_style(&style.stroke_color);
+            canvas_context.set_line_width(style.stroke_width);
+        }
 
-        draw_path(points, canvas_context)?;
-        canvas_context.close_path();
-        canvas_context.fill();
-        canvas_context.stroke();
-        Ok(())
+        let rings: Vec<_> = rings.iter().map(|ring| snap_to_pixels(ring, render_context)).collect();
+        let corner_radius = style.corner_radius;
+        let result = render_with_shadow(render_context, canvas_context, &|canvas_context| {
+            canvas_context.begin_path();
+            for ring in &rings {
+                if corner_radius > 0.0 {
+                    trace_rounded_ring(ring, corner_radius, canvas_context)?;
+                } else {
+                    trace_path(ring, canvas_context)?;
+                }
+                canvas_context.close_path();
+            }
+            if !wireframe {
+                apply_opacity(render_context, canvas_context, style.fill_opacity);
+                if rings.len() > 1 {
+                    canvas_context.fill_even_odd();
+                } else {
+                    canvas_context.fill();
+                }
+            }
+            apply_opacity(render_context, canvas_context, style.stroke_opacity);
+            canvas_context.stroke();
+            Ok(())
+        });
+        canvas_context.set_global_alpha(1.0);
+        result
     };
 
 
 
 // Utility path drawing function
-pub const draw_path: fn(&[(f64, f64)], &CanvasRenderingContext2d) -> GeoArrowResult<()> =
+pub const draw_path: fn(&[(f64, f64)], &dyn DrawTarget) -> GeoArrowResult<()> =
     |points, context| {
-        if let Some((first_x, first_y)) = points.first() {
+        if !points.is_empty() {
             context.begin_path();
+        }
+        trace_path(points, context)
+    };
+
+// Add the points to the current path as a new sub-path, without starting a fresh path
+pub const trace_path: fn(&[(f64, f64)], &dyn DrawTarget) -> GeoArrowResult<()> =
+    |points, context| {
+        if let Some((first_x, first_y)) = points.first() {
             context.move_to(*first_x, *first_y);
 
             points.iter().skip(1)
@@ -92,35 +308,142 @@ pub const draw_path: fn(&[(f64, f64)], &CanvasRenderingContext2d) -> GeoArrowRes
         Ok(())
     };
 
+// Smoothed path drawing: Catmull-Rom spline through the points, emitted as cubic Bezier segments.
+// The curve passes through every input point, so the endpoints are preserved exactly.
+pub const draw_smooth_path: fn(&[(f64, f64)], &dyn DrawTarget) -> GeoArrowResult<()> =
+    |points, context| {
+        if points.len() < 3 {
+            return draw_path(points, context);
+        }
+
+        let (first_x, first_y) = points[0];
+        context.begin_path();
+        context.move_to(first_x, first_y);
+
+        let last = points.len() - 1;
+        for i in 0..last {
+            let p0 = points[i.saturating_sub(1)];
+            let p1 = points[i];
+            let p2 = points[i + 1];
+            let p3 = points[(i + 2).min(last)];
+
+            let (cp1x, cp1y) = catmull_rom_control_point(p0, p1, p2);
+            let (cp2x, cp2y) = catmull_rom_control_point(p3, p2, p1);
+            context.bezier_curve_to(cp1x, cp1y, cp2x, cp2y, p2.0, p2.1);
+        }
+        Ok(())
+    };
+
+// Closed ring with each corner replaced by a quadratic curve through the vertex, starting and
+// ending `radius` pixels along the adjacent edges. Corners next to an edge shorter than twice the
+// radius stay sharp, since the curves would overlap.
+pub const draw_rounded_ring: fn(&[(f64, f64)], f64, &dyn DrawTarget) -> GeoArrowResult<()> =
+    |points, radius, context| {
+        if !points.is_empty() {
+            context.begin_path();
+        }
+        trace_rounded_ring(points, radius, context)
+    };
+
+// `draw_rounded_ring` as a sub-path of the current path
+pub const trace_rounded_ring: fn(&[(f64, f64)], f64, &dyn DrawTarget) -> GeoArrowResult<()> =
+    |points, radius, context| {
+        // Screen rings repeat the first vertex at the end
+        let ring = match points {
+            [first, .., last] if first == last => &points[..points.len() - 1],
+            _ => points,
+        };
+        if ring.len() < 3 {
+            return trace_path(points, context);
+        }
+
+        let toward = |from: (f64, f64), to: (f64, f64)| {
+            let length = (to.0 - from.0).hypot(to.1 - from.1);
+            (length, ((to.0 - from.0) / length, (to.1 - from.1) / length))
+        };
+        let corners: Vec<_> = (0..ring.len())
+            .map(|i| {
+                let vertex = ring[i];
+                let (prev_length, to_prev) = toward(vertex, ring[(i + ring.len() - 1) % ring.len()]);
+                let (next_length, to_next) = toward(vertex, ring[(i + 1) % ring.len()]);
+                if prev_length.min(next_length) < 2.0 * radius {
+                    return (vertex, None);
+                }
+                let entry = (vertex.0 + to_prev.0 * radius, vertex.1 + to_prev.1 * radius);
+                let exit = (vertex.0 + to_next.0 * radius, vertex.1 + to_next.1 * radius);
+                (vertex, Some((entry, exit)))
+            })
+            .collect();
+
+        for (i, (vertex, rounded)) in corners.iter().enumerate() {
+            let start = rounded.map_or(*vertex, |(entry, _)| entry);
+            if i == 0 {
+                context.move_to(start.0, start.1);
+            } else {
+                context.line_to(start.0, start.1);
+            }
+            if let Some((_, exit)) = rounded {
+                context.quadratic_curve_to(vertex.0, vertex.1, exit.0, exit.1);
+            }
+        }
+        Ok(())
+    };
+
+// Bezier control point leaving `current` towards `next`, tangent parallel to (next - previous)
+const catmull_rom_control_point: fn((f64, f64), (f64, f64), (f64, f64)) -> (f64, f64) =
+    |previous, current, next| {
+        (
+            current.0 + (next.0 - previous.0) / 6.0,
+            current.1 + (next.1 - previous.1) / 6.0,
+        )
+    };
+
 // Rendering function combinators
-pub const compose_renderers: fn(
-    fn(&[(f64, f64)], &RenderContext, &CanvasRenderingContext2d) -> GeoArrowResult<()>,
-    fn(&[(f64, f64)], &RenderContext, &CanvasRenderingContext2d) -> GeoArrowResult<()>
-) -> fn(&[(f64, f64)], &RenderContext, &CanvasRenderingContext2d) -> GeoArrowResult<()> =
-    |renderer1, renderer2| |points, render_context, canvas_context| {
+pub type PathRenderer = fn(&[(f64, f64)], &RenderContext, &dyn DrawTarget) -> GeoArrowResult<()>;
+
+pub fn compose_renderers(
+    renderer1: PathRenderer,
+    renderer2: PathRenderer,
+) -> impl Fn(&[(f64, f64)], &RenderContext, &dyn DrawTarget) -> GeoArrowResult<()> {
+    move |points, render_context, canvas_context| {
         renderer1(points, render_context, canvas_context)?;
         renderer2(points, render_context, canvas_context)
-    };
+    }
+}
 
 // Clear canvas function
-pub const clear_canvas: fn(&CanvasRenderingContext2d, (f64, f64)) -> GeoArrowResult<()> =
+pub const clear_canvas: fn(&dyn DrawTarget, (f64, f64)) -> GeoArrowResult<()> =
     |context, (width, height)| {
         context.clear_rect(0.0, 0.0, width, height);
         Ok(())
     };
 
 // Background drawing function
-pub const draw_background: fn(&CanvasRenderingContext2d, (f64, f64), &str) -> GeoArrowResult<()> =
+pub const draw_background: fn(&dyn DrawTarget, (f64, f64), &str) -> GeoArrowResult<()> =
     |context, (width, height), color| {
-        context.set_fill_style_str(color);
+        context.set_fill_style(color);
         context.fill_rect(0.0, 0.0, width, height);
         Ok(())
     };
 
+// "+N" badge in the bottom-right corner for features left undrawn by a draw cap
+pub const draw_count_badge: fn(&dyn DrawTarget, (f64, f64), usize) -> GeoArrowResult<()> =
+    |context, (width, height), hidden| {
+        let label = format!("+{}", hidden);
+        let (text_width, _) = ApproximateTextMeasure.measure_text(&label, "12px sans-serif")?;
+        let (badge_width, badge_height) = (text_width + 8.0, 18.0);
+        let (x, y) = (width - badge_width - 4.0, height - badge_height - 4.0);
+
+        context.set_fill_style("rgba(0, 0, 0, 0.6)");
+        context.fill_rect(x, y, badge_width, badge_height);
+        context.set_fill_style("#ffffff");
+        context.fill_text(&label, x + 4.0, y + 13.0)
+    };
+
 // Grid drawing function (for debugging/reference)
-pub const draw_grid: fn(&CanvasRenderingContext2d, (f64, f64), f64) -> GeoArrowResult<()> =
+pub const draw_grid: fn(&dyn DrawTarget, (f64, f64), f64) -> GeoArrowResult<()> =
     |context, (width, height), spacing| {
-        context.set_stroke_style_str("#cccccc");
+        context.set_stroke_style("#cccccc");
         context.set_line_width(0.5);
 
         // Vertical lines
@@ -145,3 +468,241 @@ pub const draw_grid: fn(&CanvasRenderingContext2d, (f64, f64), f64) -> GeoArrowR
 
         Ok(())
     };
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::raster::RasterTarget;
+    use crate::engine::target::{DrawCommand, RecordingTarget};
+    use crate::model::{Bounds, LayerStyle, ShadowStyle};
+    use crate::view::view::MapStyle;
+
+    fn smooth_context(smooth: bool) -> RenderContext {
+        let mut layer_style = LayerStyle::default();
+        layer_style.line_style.smooth = smooth;
+        RenderContext::new(Bounds::new(0.0, 0.0, 100.0, 100.0), (100.0, 100.0), 1, MapStyle::default())
+            .with_layer_style(layer_style)
+    }
+
+    #[test]
+    fn test_smooth_linestring_emits_curves() {
+        let points = [(0.0, 0.0), (10.0, 20.0), (30.0, 5.0), (50.0, 40.0)];
+        let target = RecordingTarget::new();
+
+        render_linestring(&points, &smooth_context(true), &target).unwrap();
+
+        let commands = target.commands();
+        assert!(!commands.iter().any(|c| matches!(c, DrawCommand::LineTo(..))));
+        let curves: Vec<_> = commands
+            .iter()
+            .filter_map(|c| match c {
+                DrawCommand::BezierCurveTo(_, _, _, _, x, y) => Some((*x, *y)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(curves.len(), points.len() - 1);
+        assert!(commands.contains(&DrawCommand::MoveTo(0.0, 0.0)));
+        assert_eq!(curves.last(), Some(&(50.0, 40.0)));
+    }
+
+    #[test]
+    fn test_tapered_ribbon_widens_along_line() {
+        let points = [(0.0, 0.0), (10.0, 0.0), (20.0, 0.0)];
+        let ribbon = tapered_ribbon(&points, &[2.0, 4.0, 6.0], 1.0);
+
+        // Left side forward, then right side back
+        assert_eq!(
+            ribbon,
+            vec![(0.0, 1.0), (10.0, 2.0), (20.0, 3.0), (20.0, -3.0), (10.0, -2.0), (0.0, -1.0)]
+        );
+
+        let target = RecordingTarget::new();
+        render_tapered_linestring(&points, &[2.0, 4.0, 6.0], &smooth_context(false), &target).unwrap();
+        let commands = target.commands();
+        assert!(commands.contains(&DrawCommand::Fill));
+        assert!(!commands.contains(&DrawCommand::Stroke));
+        assert_eq!(commands.iter().filter(|c| matches!(c, DrawCommand::LineTo(..))).count(), 5);
+    }
+
+    #[test]
+    fn test_tapered_ribbon_clamps_sharp_turns() {
+        // A hairpin would otherwise push the miter far beyond the line
+        let ribbon = tapered_ribbon(&[(0.0, 0.0), (10.0, 0.0), (0.0, 1.0)], &[4.0], 1.0);
+
+        let (x, y) = ribbon[1];
+        assert!((x - 10.0).hypot(y) <= 2.0 * MITER_LIMIT + 1e-9);
+        assert!(tapered_ribbon(&[(1.0, 1.0), (1.0, 1.0)], &[2.0], 1.0).is_empty());
+    }
+
+    #[test]
+    fn test_shadow_set_before_drawing_and_reset_after() {
+        let shadow = ShadowStyle { color: "rgba(0, 0, 0, 0.5)".to_string(), blur: 4.0, offset_x: 2.0, offset_y: 3.0 };
+        let layer_style = LayerStyle { shadow: Some(shadow), ..LayerStyle::default() };
+        let context = RenderContext::new(Bounds::new(0.0, 0.0, 100.0, 100.0), (100.0, 100.0), 1, MapStyle::default())
+            .with_layer_style(layer_style);
+        let target = RecordingTarget::new();
+
+        render_polygon(&[(10.0, 10.0), (50.0, 10.0), (50.0, 50.0), (10.0, 10.0)], &context, &target).unwrap();
+
+        let commands = target.commands();
+        let position = |command: &DrawCommand| commands.iter().position(|c| c == command).unwrap();
+        let fill = position(&DrawCommand::Fill);
+        assert!(position(&DrawCommand::SetShadowColor("rgba(0, 0, 0, 0.5)".to_string())) < fill);
+        assert!(position(&DrawCommand::SetShadowBlur(4.0)) < fill);
+        assert!(position(&DrawCommand::SetShadowOffsetX(2.0)) < fill);
+        assert!(position(&DrawCommand::SetShadowOffsetY(3.0)) < fill);
+        assert!(position(&DrawCommand::SetShadowColor("rgba(0, 0, 0, 0)".to_string())) > position(&DrawCommand::Stroke));
+        assert_eq!(commands[commands.len() - 2..], [DrawCommand::SetShadowOffsetY(0.0), DrawCommand::SetGlobalAlpha(1.0)]);
+
+        // No shadow configured: no shadow state is touched
+        let target = RecordingTarget::new();
+        render_points(&[(5.0, 5.0)], &smooth_context(false), &target).unwrap();
+        assert!(!target.commands().iter().any(|c| matches!(c, DrawCommand::SetShadowBlur(_))));
+    }
+
+    #[test]
+    fn test_polygon_uses_layer_style_colors_and_opacity() {
+        let mut layer_style = LayerStyle::default();
+        layer_style.polygon_style.fill_color = "#123456".to_string();
+        layer_style.polygon_style.fill_opacity = 0.5;
+        layer_style.polygon_style.stroke_opacity = 0.75;
+        let context = smooth_context(false).with_layer_style(layer_style);
+        let target = RecordingTarget::new();
+
+        render_polygon(&[(10.0, 10.0), (50.0, 10.0), (50.0, 50.0), (10.0, 10.0)], &context, &target).unwrap();
+
+        let commands = target.commands();
+        let position = |command: &DrawCommand| commands.iter().position(|c| c == command).unwrap();
+        assert!(commands.contains(&DrawCommand::SetFillStyle("#123456".to_string())));
+        assert_eq!(position(&DrawCommand::SetGlobalAlpha(0.5)) + 1, position(&DrawCommand::Fill));
+        assert_eq!(position(&DrawCommand::SetGlobalAlpha(0.75)) + 1, position(&DrawCommand::Stroke));
+        assert_eq!(commands.last(), Some(&DrawCommand::SetGlobalAlpha(1.0)));
+    }
+
+    #[test]
+    fn test_wireframe_polygon_strokes_without_filling() {
+        let context = smooth_context(false).with_wireframe("#00ff00", 2.0);
+        let target = RecordingTarget::new();
+
+        render_polygon(&[(10.0, 10.0), (50.0, 10.0), (50.0, 50.0), (10.0, 10.0)], &context, &target).unwrap();
+        render_points(&[(5.0, 5.0)], &context, &target).unwrap();
+
+        let commands = target.commands();
+        assert!(!commands.contains(&DrawCommand::Fill));
+        assert!(!commands.iter().any(|c| matches!(c, DrawCommand::SetFillStyle(_))));
+        assert_eq!(commands.iter().filter(|c| **c == DrawCommand::Stroke).count(), 2);
+        assert!(commands.contains(&DrawCommand::SetStrokeStyle("#00ff00".to_string())));
+        assert!(!commands.contains(&DrawCommand::SetStrokeStyle(MapStyle::default().polygon_stroke)));
+        assert!(commands.contains(&DrawCommand::SetLineWidth(2.0)));
+    }
+
+    #[test]
+    fn test_polygon_hole_is_a_sub_path_filled_even_odd() {
+        let outer = vec![(10.0, 10.0), (90.0, 10.0), (90.0, 90.0), (10.0, 90.0), (10.0, 10.0)];
+        let hole = vec![(40.0, 40.0), (60.0, 40.0), (60.0, 60.0), (40.0, 60.0), (40.0, 40.0)];
+        let context = smooth_context(false);
+        let target = RecordingTarget::new();
+
+        render_polygon_with_holes(&[outer.clone(), hole.clone()], &context, &target).unwrap();
+
+        let commands = target.commands();
+        assert_eq!(commands.iter().filter(|c| **c == DrawCommand::BeginPath).count(), 1);
+        assert!(commands.contains(&DrawCommand::MoveTo(10.0, 10.0)));
+        assert!(commands.contains(&DrawCommand::MoveTo(40.0, 40.0)));
+        assert_eq!(commands.iter().filter(|c| **c == DrawCommand::ClosePath).count(), 2);
+        assert!(commands.contains(&DrawCommand::FillEvenOdd));
+        assert!(!commands.contains(&DrawCommand::Fill));
+
+        let raster = RasterTarget::new(100, 100);
+        render_polygon_with_holes(&[outer, hole], &context, &raster).unwrap();
+        assert_ne!(raster.pixel(20, 20)[3], 0);
+        assert_eq!(raster.pixel(50, 50), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_unsmoothed_linestring_uses_line_to() {
+        let points = [(0.0, 0.0), (10.0, 20.0), (30.0, 5.0)];
+        let target = RecordingTarget::new();
+
+        render_linestring(&points, &smooth_context(false), &target).unwrap();
+
+        let commands = target.commands();
+        assert!(!commands.iter().any(|c| matches!(c, DrawCommand::BezierCurveTo(..))));
+        assert!(commands.contains(&DrawCommand::LineTo(30.0, 5.0)));
+    }
+
+    #[test]
+    fn test_dash_pattern_applied_to_stroke_and_reset() {
+        let mut layer_style = LayerStyle::default();
+        layer_style.line_style.dash_pattern = Some(vec![4.0, 4.0]);
+        let context = smooth_context(false).with_layer_style(layer_style);
+        let points = [(0.0, 10.5), (40.0, 10.5)];
+        let target = RecordingTarget::new();
+
+        render_linestring(&points, &context, &target).unwrap();
+
+        let commands = target.commands();
+        let dash = commands.iter().position(|c| *c == DrawCommand::SetLineDash(vec![4.0, 4.0])).unwrap();
+        let stroke = commands.iter().position(|c| *c == DrawCommand::Stroke).unwrap();
+        assert!(dash < stroke);
+        assert_eq!(commands[commands.len() - 2..], [DrawCommand::SetLineDash(Vec::new()), DrawCommand::SetGlobalAlpha(1.0)]);
+
+        let raster = RasterTarget::new(40, 20);
+        render_linestring(&points, &context, &raster).unwrap();
+        assert_ne!(raster.pixel(2, 10)[3], 0);
+        assert_eq!(raster.pixel(6, 10)[3], 0);
+        assert_ne!(raster.pixel(10, 10)[3], 0);
+
+        // A rejected pattern still leaves the target solid and opaque for the next feature
+        let mut faded = context.layer_style.clone();
+        faded.line_style.opacity = 0.5;
+        let target = RecordingTarget::new();
+        target.reject_line_dash.set(true);
+        assert!(render_linestring(&points, &context.clone().with_layer_style(faded), &target).is_err());
+        let commands = target.commands();
+        assert!(!commands.contains(&DrawCommand::Stroke));
+        assert_eq!(commands[commands.len() - 2..], [DrawCommand::SetLineDash(Vec::new()), DrawCommand::SetGlobalAlpha(1.0)]);
+    }
+
+    #[test]
+    fn test_polygon_corner_radius_emits_curves() {
+        let square = [(10.0, 10.0), (60.0, 10.0), (60.0, 60.0), (10.0, 60.0), (10.0, 10.0)];
+        let mut layer_style = LayerStyle::default();
+        let sharp = RenderContext::new(Bounds::new(0.0, 0.0, 100.0, 100.0), (100.0, 100.0), 1, MapStyle::default())
+            .with_layer_style(layer_style.clone());
+        layer_style.polygon_style.corner_radius = 5.0;
+        let rounded = sharp.clone().with_layer_style(layer_style);
+
+        let sharp_target = RecordingTarget::new();
+        render_polygon(&square, &sharp, &sharp_target).unwrap();
+        assert!(!sharp_target.commands().iter().any(|c| matches!(c, DrawCommand::QuadraticCurveTo(..))));
+
+        let target = RecordingTarget::new();
+        render_polygon(&square, &rounded, &target).unwrap();
+        let commands = target.commands();
+        let curves: Vec<_> = commands
+            .iter()
+            .filter_map(|c| match c {
+                DrawCommand::QuadraticCurveTo(cx, cy, ..) => Some((*cx, *cy)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(curves, vec![(10.0, 10.0), (60.0, 10.0), (60.0, 60.0), (10.0, 60.0)]);
+        assert_eq!(commands[commands.iter().position(|c| *c == DrawCommand::BeginPath).unwrap() + 1], DrawCommand::MoveTo(10.0, 15.0));
+    }
+
+    #[test]
+    fn test_disabling_antialias_turns_off_smoothing_and_snaps() {
+        let mut context = smooth_context(false);
+        context.style.antialias = false;
+        let target = RecordingTarget::new();
+
+        setup_canvas_context(&target, &context).unwrap();
+        render_linestring(&[(0.4, 0.6), (10.2, 19.7)], &context, &target).unwrap();
+
+        let commands = target.commands();
+        assert_eq!(commands.first(), Some(&DrawCommand::SetImageSmoothingEnabled(false)));
+        assert!(commands.contains(&DrawCommand::MoveTo(0.0, 1.0)));
+        assert!(commands.contains(&DrawCommand::LineTo(10.0, 20.0)));
+    }
+}
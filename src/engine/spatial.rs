@@ -0,0 +1,439 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use rstar::primitives::{GeomWithData, Rectangle};
+use rstar::{Envelope, RTree, RTreeObject, AABB};
+use crate::engine::geometry::ring_contains_point;
+use crate::model::{FeatureGeometry, FeatureId, GeoBounds, GeoFeature, GeoPoint, Tile, MEAN_EARTH_RADIUS};
+
+// Spatial queries over feature collections
+
+type IndexedBounds = GeomWithData<Rectangle<[f64; 2]>, usize>;
+
+// R-tree over the bounds of a feature slice. Features without finite bounds (empty geometries)
+// are left out of the index.
+pub struct SpatialIndex<'a> {
+    tree: RTree<IndexedBounds>,
+    features: &'a [GeoFeature],
+}
+
+impl<'a> SpatialIndex<'a> {
+    pub fn new(features: &'a [GeoFeature]) -> Self {
+        let entries = features
+            .iter()
+            .enumerate()
+            .filter(|(_, feature)| has_finite_bounds(&feature.bounds))
+            .map(|(i, feature)| {
+                GeomWithData::new(rectangle(&feature.bounds), i)
+            })
+            .collect();
+        Self { tree: RTree::bulk_load(entries), features }
+    }
+
+    /// Features whose bounds intersect `bounds`, in input order
+    pub fn query(&self, bounds: &GeoBounds) -> Vec<&'a GeoFeature> {
+        let mut hits: Vec<usize> = self.query_positions(bounds).collect();
+        hits.sort_unstable();
+        hits.dedup();
+        hits.into_iter().map(|i| &self.features[i]).collect()
+    }
+
+    // Positions in the feature slice of the features intersecting `bounds`, unordered. Bounds
+    // crossing the antimeridian are queried as their two halves, so a position may repeat.
+    fn query_positions(&self, bounds: &GeoBounds) -> impl Iterator<Item = usize> + use<'_, 'a> {
+        bounds
+            .split_antimeridian()
+            .into_iter()
+            .flat_map(|part| self.tree.locate_in_envelope_intersecting(&envelope(&part)).map(|entry| entry.data))
+    }
+
+    pub fn len(&self) -> usize {
+        self.tree.size()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tree.size() == 0
+    }
+}
+
+// Ids of the features intersecting a moving viewport. Moving the viewport only queries the
+// strips it uncovered or left behind, rather than rescanning every feature.
+#[derive(Default)]
+pub struct ViewportIndex {
+    tree: RTree<GeomWithData<Rectangle<[f64; 2]>, FeatureId>>,
+    // Envelopes of the current view: one, or the two halves of a view crossing the antimeridian
+    view: Vec<AABB<[f64; 2]>>,
+    in_view: HashSet<FeatureId>,
+}
+
+impl ViewportIndex {
+    pub fn new(features: &[GeoFeature], view: Option<&GeoBounds>) -> Self {
+        let entries = features
+            .iter()
+            .filter(|feature| has_finite_bounds(&feature.bounds))
+            .map(|feature| GeomWithData::new(rectangle(&feature.bounds), feature.id.clone()))
+            .collect();
+        let mut index = Self { tree: RTree::bulk_load(entries), view: Vec::new(), in_view: HashSet::new() };
+        if let Some(view) = view {
+            index.set_view(view);
+        }
+        index
+    }
+
+    /// Index one more feature. Replacing a feature with the same id needs a rebuild instead.
+    pub fn insert(&mut self, feature: &GeoFeature) {
+        if !has_finite_bounds(&feature.bounds) {
+            return;
+        }
+        let entry = GeomWithData::new(rectangle(&feature.bounds), feature.id.clone());
+        if self.view.iter().any(|part| entry.envelope().intersects(part)) {
+            self.in_view.insert(feature.id.clone());
+        }
+        self.tree.insert(entry);
+    }
+
+    /// Move the viewport, updating the in-view set from the regions that changed. Views crossing
+    /// the antimeridian are split in two; moving to or from one rescans the view's envelopes.
+    pub fn set_view(&mut self, view: &GeoBounds) {
+        let parts: Vec<AABB<[f64; 2]>> = view.split_antimeridian().iter().map(envelope).collect();
+        let old = std::mem::replace(&mut self.view, parts);
+        match (old.as_slice(), self.view.as_slice()) {
+            ([old], [new]) if old.intersects(new) => {
+                for strip in envelope_difference(new, old) {
+                    self.in_view.extend(self.tree.locate_in_envelope_intersecting(&strip).map(|e| e.data.clone()));
+                }
+                for strip in envelope_difference(old, new) {
+                    for entry in self.tree.locate_in_envelope_intersecting(&strip) {
+                        if !entry.envelope().intersects(new) {
+                            self.in_view.remove(&entry.data);
+                        }
+                    }
+                }
+            }
+            (_, parts) => {
+                self.in_view = parts
+                    .iter()
+                    .flat_map(|part| self.tree.locate_in_envelope_intersecting(part).map(|e| e.data.clone()))
+                    .collect();
+            }
+        }
+    }
+
+    pub fn in_view(&self) -> &HashSet<FeatureId> {
+        &self.in_view
+    }
+}
+
+fn rectangle(bounds: &GeoBounds) -> Rectangle<[f64; 2]> {
+    Rectangle::from_corners([bounds.min_x, bounds.min_y], [bounds.max_x, bounds.max_y])
+}
+
+fn envelope(bounds: &GeoBounds) -> AABB<[f64; 2]> {
+    AABB::from_corners([bounds.min_x, bounds.min_y], [bounds.max_x, bounds.max_y])
+}
+
+// The part of `a` outside `b` as up to four strips: full-height strips left and right of `b`,
+// then the pieces below and above it
+fn envelope_difference(a: &AABB<[f64; 2]>, b: &AABB<[f64; 2]>) -> Vec<AABB<[f64; 2]>> {
+    let ([a_min_x, a_min_y], [a_max_x, a_max_y]) = (a.lower(), a.upper());
+    let ([b_min_x, b_min_y], [b_max_x, b_max_y]) = (b.lower(), b.upper());
+    let (mid_min_x, mid_max_x) = (a_min_x.max(b_min_x), a_max_x.min(b_max_x));
+    let mut strips = Vec::with_capacity(4);
+    if a_min_x < b_min_x {
+        strips.push(AABB::from_corners([a_min_x, a_min_y], [b_min_x, a_max_y]));
+    }
+    if a_max_x > b_max_x {
+        strips.push(AABB::from_corners([b_max_x, a_min_y], [a_max_x, a_max_y]));
+    }
+    if a_min_y < b_min_y {
+        strips.push(AABB::from_corners([mid_min_x, a_min_y], [mid_max_x, b_min_y]));
+    }
+    if a_max_y > b_max_y {
+        strips.push(AABB::from_corners([mid_min_x, b_max_y], [mid_max_x, a_max_y]));
+    }
+    strips
+}
+
+fn has_finite_bounds(bounds: &GeoBounds) -> bool {
+    [bounds.min_x, bounds.min_y, bounds.max_x, bounds.max_y].iter().all(|v| v.is_finite())
+        && bounds.min_x <= bounds.max_x
+        && bounds.min_y <= bounds.max_y
+}
+
+// Bin features into tiles: each tile takes a copy of every feature whose bounds intersect its
+// own, as `Tile::add_feature` checks. One index query per tile replaces scanning every feature
+// for every tile.
+pub fn assign_to_tiles(features: &[GeoFeature], tiles: &mut [Tile]) {
+    let index = SpatialIndex::new(features);
+    for tile in tiles {
+        let bounds = tile.bounds.to_geo_bounds();
+        // The index also returns features that only touch the tile's edge
+        for feature in index.query(&bounds).into_iter().filter(|feature| feature.bounds.intersects(&bounds)) {
+            tile.features.push(feature.clone());
+        }
+    }
+}
+
+const METERS_PER_DEGREE: f64 = MEAN_EARTH_RADIUS * std::f64::consts::PI / 180.0;
+
+// Corridor search: ids of features whose geometry comes within `distance_m` meters of any segment
+// of `line`. Candidates come from an R-tree query around each segment; distances are then measured
+// between vertices and segments on a local equirectangular plane, which matches haversine closely
+// at corridor scales. A polygon also matches when one of the line's vertices lies inside it, so
+// one wholly containing the corridor is returned without an edge nearby.
+pub const features_near_line: fn(&[GeoPoint], f64, &[GeoFeature]) -> Vec<FeatureId> =
+    |line, distance_m, features| {
+        if line.is_empty() || distance_m < 0.0 {
+            return Vec::new();
+        }
+        let index = SpatialIndex::new(features);
+        let segments: Vec<(&GeoPoint, &GeoPoint)> = match line {
+            [only] => vec![(only, only)],
+            _ => line.windows(2).map(|pair| (&pair[0], &pair[1])).collect(),
+        };
+
+        // Ordered set, so results come back in input order
+        let candidates: BTreeSet<usize> = segments
+            .iter()
+            .flat_map(|(a, b)| index.query_positions(&segment_search_bounds(a, b, distance_m)))
+            .collect();
+
+        candidates
+            .into_iter()
+            .map(|i| &features[i])
+            .filter(|feature| {
+                let Ok(geometry) = feature.geometry() else {
+                    return false;
+                };
+                polygons_contain_any(geometry, line)
+                    || geometry_paths(geometry)
+                        .iter()
+                        .any(|path| path_distance_m(path, line) <= distance_m)
+            })
+            .map(|feature| feature.id.clone())
+            .collect()
+    };
+
+// Grid aggregation: counts features per square cell of `cell_deg` degrees, keyed by the cell's
+// (column, row) index counted from 0°/0°, i.e. `floor(lng / cell_deg)` and `floor(lat / cell_deg)`.
+// Each feature counts once, in the cell holding its centroid. Empty or undecodable geometries and
+// non-positive cell sizes contribute nothing.
+pub const density_grid: fn(&[GeoFeature], f64) -> HashMap<(i64, i64), usize> =
+    |features, cell_deg| {
+        let mut cells = HashMap::new();
+        if cell_deg.is_nan() || cell_deg <= 0.0 {
+            return cells;
+        }
+        for geometry in features.iter().filter_map(|feature| feature.geometry().ok()) {
+            if geometry.is_empty() {
+                continue;
+            }
+            let centroid = geometry.centroid();
+            let cell = ((centroid.lng / cell_deg).floor() as i64, (centroid.lat / cell_deg).floor() as i64);
+            *cells.entry(cell).or_insert(0) += 1;
+        }
+        cells
+    };
+
+// Segment bounding box grown by `distance_m`, widened in longitude for the segment's latitude
+fn segment_search_bounds(a: &GeoPoint, b: &GeoPoint, distance_m: f64) -> GeoBounds {
+    let lat_margin = distance_m / METERS_PER_DEGREE;
+    let max_lat = a.lat.abs().max(b.lat.abs()) + lat_margin;
+    let cos_lat = max_lat.min(89.9).to_radians().cos();
+    let lng_margin = (lat_margin / cos_lat).min(360.0);
+    GeoBounds::new(
+        a.lng.min(b.lng) - lng_margin,
+        a.lat.min(b.lat) - lat_margin,
+        a.lng.max(b.lng) + lng_margin,
+        a.lat.max(b.lat) + lat_margin,
+    )
+}
+
+// Vertex sequences of a geometry: one per point, line or ring
+fn geometry_paths(geometry: &FeatureGeometry) -> Vec<&[GeoPoint]> {
+    match geometry {
+        FeatureGeometry::Point(point) => vec![std::slice::from_ref(point)],
+        FeatureGeometry::MultiPoint(points) => points.iter().map(std::slice::from_ref).collect(),
+        FeatureGeometry::LineString(points) => vec![points.as_slice()],
+        FeatureGeometry::Polygon(rings) | FeatureGeometry::MultiLineString(rings) => {
+            rings.iter().map(Vec::as_slice).collect()
+        }
+        FeatureGeometry::MultiPolygon(polygons) => polygons.iter().flatten().map(Vec::as_slice).collect(),
+    }
+}
+
+// Whether any of `points` lies inside one of the geometry's polygons, outside their holes
+fn polygons_contain_any(geometry: &FeatureGeometry, points: &[GeoPoint]) -> bool {
+    let polygons: Vec<&[Vec<GeoPoint>]> = match geometry {
+        FeatureGeometry::Polygon(rings) => vec![rings.as_slice()],
+        FeatureGeometry::MultiPolygon(polygons) => polygons.iter().map(Vec::as_slice).collect(),
+        _ => return false,
+    };
+    polygons.iter().any(|rings| {
+        let Some((outer, holes)) = rings.split_first() else {
+            return false;
+        };
+        points.iter().any(|p| ring_contains_point(outer, p) && !holes.iter().any(|hole| ring_contains_point(hole, p)))
+    })
+}
+
+// Shortest distance in meters between two paths (a single vertex counts as a zero-length segment)
+fn path_distance_m(a: &[GeoPoint], b: &[GeoPoint]) -> f64 {
+    let segments = |path: &[GeoPoint]| -> Vec<(GeoPoint, GeoPoint)> {
+        match path {
+            [] => Vec::new(),
+            [only] => vec![(only.clone(), only.clone())],
+            _ => path.windows(2).map(|pair| (pair[0].clone(), pair[1].clone())).collect(),
+        }
+    };
+    let (a, b) = (segments(a), segments(b));
+    a.iter()
+        .flat_map(|s| b.iter().map(move |t| segment_distance_m(s, t)))
+        .fold(f64::INFINITY, f64::min)
+}
+
+fn segment_distance_m((p1, p2): &(GeoPoint, GeoPoint), (q1, q2): &(GeoPoint, GeoPoint)) -> f64 {
+    // Local plane centered on the first segment, in meters
+    let cos_lat = ((p1.lat + p2.lat) / 2.0).to_radians().cos();
+    let to_plane = |p: &GeoPoint| {
+        ((p.lng - p1.lng) * cos_lat * METERS_PER_DEGREE, (p.lat - p1.lat) * METERS_PER_DEGREE)
+    };
+    let (a, b, c, d) = (to_plane(p1), to_plane(p2), to_plane(q1), to_plane(q2));
+
+    if segments_cross(a, b, c, d) {
+        return 0.0;
+    }
+    [
+        point_segment_distance(a, c, d),
+        point_segment_distance(b, c, d),
+        point_segment_distance(c, a, b),
+        point_segment_distance(d, a, b),
+    ]
+    .into_iter()
+    .fold(f64::INFINITY, f64::min)
+}
+
+fn point_segment_distance(p: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length_sq = dx * dx + dy * dy;
+    let t = if length_sq == 0.0 {
+        0.0
+    } else {
+        (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / length_sq).clamp(0.0, 1.0)
+    };
+    (p.0 - (a.0 + t * dx)).hypot(p.1 - (a.1 + t * dy))
+}
+
+fn segments_cross(a: (f64, f64), b: (f64, f64), c: (f64, f64), d: (f64, f64)) -> bool {
+    let orient = |p: (f64, f64), q: (f64, f64), r: (f64, f64)| (q.0 - p.0) * (r.1 - p.1) - (q.1 - p.1) * (r.0 - p.0);
+    let (d1, d2) = (orient(c, d, a), orient(c, d, b));
+    let (d3, d4) = (orient(a, b, c), orient(a, b, d));
+    d1 * d2 < 0.0 && d3 * d4 < 0.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dashmap::DashMap;
+
+    fn point(id: &str, lat: f64, lng: f64) -> GeoFeature {
+        GeoFeature::new(id.into(), FeatureGeometry::Point(GeoPoint::new(lat, lng)), DashMap::new())
+    }
+
+    #[test]
+    fn test_features_near_line() {
+        // Route east along the equator from 0° to 1°; 0.001° of latitude is about 111 m
+        let route = [GeoPoint::new(0.0, 0.0), GeoPoint::new(0.0, 1.0)];
+        let features = vec![
+            point("on-route", 0.0, 0.5),
+            point("close", 0.001, 0.25),
+            point("far", 0.01, 0.5),
+            point("past-end", 0.0, 1.01),
+            GeoFeature::new(
+                "crossing".into(),
+                FeatureGeometry::LineString(vec![GeoPoint::new(-1.0, 0.7), GeoPoint::new(1.0, 0.7)]),
+                DashMap::new(),
+            ),
+            // Surrounds the whole route, with every edge far from it
+            GeoFeature::new(
+                "surrounding".into(),
+                FeatureGeometry::Polygon(vec![vec![
+                    GeoPoint::new(-5.0, -5.0),
+                    GeoPoint::new(-5.0, 5.0),
+                    GeoPoint::new(5.0, 5.0),
+                    GeoPoint::new(5.0, -5.0),
+                    GeoPoint::new(-5.0, -5.0),
+                ]]),
+                DashMap::new(),
+            ),
+        ];
+
+        let near = features_near_line(&route, 200.0, &features);
+
+        let ids: Vec<String> = near.iter().map(ToString::to_string).collect();
+        assert_eq!(ids, vec!["on-route", "close", "crossing", "surrounding"]);
+        let hits = SpatialIndex::new(&features).query(&GeoBounds::new(0.4, -0.005, 0.6, 0.005));
+        let hits: Vec<String> = hits.iter().map(|feature| feature.id.to_string()).collect();
+        assert_eq!(hits, vec!["on-route", "surrounding"]);
+    }
+
+    #[test]
+    fn test_viewport_index_splits_antimeridian_views() {
+        let features = vec![point("east", 0.0, 179.5), point("west", 0.0, -179.5), point("middle", 0.0, 0.0)];
+        let ids = |index: &ViewportIndex| {
+            let mut ids: Vec<String> = index.in_view().iter().map(ToString::to_string).collect();
+            ids.sort();
+            ids
+        };
+
+        // From 179°E across the antimeridian to 179°W
+        let across = GeoBounds::new(179.0, -1.0, -179.0, 1.0);
+        let mut index = ViewportIndex::new(&features, Some(&across));
+        assert_eq!(ids(&index), vec!["east", "west"]);
+
+        index.set_view(&GeoBounds::new(170.0, -1.0, 179.8, 1.0));
+        assert_eq!(ids(&index), vec!["east"]);
+
+        index.set_view(&across);
+        index.insert(&point("late", 0.5, -179.9));
+        assert_eq!(ids(&index), vec!["east", "late", "west"]);
+    }
+
+    #[test]
+    fn test_assign_to_tiles_matches_linear_scan() {
+        let features: Vec<GeoFeature> = (0..400)
+            .map(|i| point(&format!("p{i}"), (i / 20) as f64 * 8.0 - 80.0, (i % 20) as f64 * 18.0 - 171.0))
+            .chain([GeoFeature::new(
+                "span".into(),
+                FeatureGeometry::LineString(vec![GeoPoint::new(10.0, -100.0), GeoPoint::new(20.0, 100.0)]),
+                DashMap::new(),
+            )])
+            .collect();
+        let mut tiles: Vec<Tile> = (0..4).flat_map(|x| (0..4).map(move |y| Tile::new(x, y, 2))).collect();
+        assign_to_tiles(&features, &mut tiles);
+
+        for tile in &tiles {
+            let mut expected = Tile::new(tile.x, tile.y, tile.z);
+            features.iter().for_each(|feature| {
+                let _ = expected.add_feature(feature.clone());
+            });
+            let ids = |tile: &Tile| tile.features.iter().map(|feature| feature.id.clone()).collect::<Vec<_>>();
+            assert_eq!(ids(tile), ids(&expected), "tile {}/{}/{}", tile.z, tile.x, tile.y);
+        }
+        // The long line lands in each tile its bounds reach
+        let span_tiles = tiles.iter().filter(|tile| tile.features.iter().any(|feature| feature.id == "span".into())).count();
+        assert_eq!(span_tiles, 4);
+    }
+
+    #[test]
+    fn test_density_grid_counts_per_cell() {
+        let mut features: Vec<GeoFeature> =
+            (0..20).map(|i| point(&format!("city-{i}"), 52.5 + i as f64 * 0.01, 13.4 + i as f64 * 0.01)).collect();
+        features.push(point("outlier", -33.9, 151.2));
+
+        let grid = density_grid(&features, 1.0);
+
+        assert_eq!(grid.len(), 2);
+        assert_eq!(grid[&(13, 52)], 20);
+        assert_eq!(grid[&(151, -34)], 1);
+        assert!(density_grid(&features, 0.0).is_empty());
+    }
+}
@@ -0,0 +1,468 @@
+use std::cell::RefCell;
+use wasm_bindgen::JsValue;
+use web_sys::{CanvasRenderingContext2d, CanvasWindingRule};
+use crate::model::GeoArrowResult;
+use crate::error::GeoArrowError;
+
+// Drawing surface abstraction so the renderers can target a browser canvas or a headless recorder
+
+pub trait DrawTarget {
+    fn begin_path(&self);
+    fn move_to(&self, x: f64, y: f64);
+    fn line_to(&self, x: f64, y: f64);
+    fn quadratic_curve_to(&self, cpx: f64, cpy: f64, x: f64, y: f64);
+    fn bezier_curve_to(&self, cp1x: f64, cp1y: f64, cp2x: f64, cp2y: f64, x: f64, y: f64);
+    fn arc(&self, x: f64, y: f64, radius: f64, start_angle: f64, end_angle: f64) -> GeoArrowResult<()>;
+    fn close_path(&self);
+    fn fill(&self);
+    // Fill using the even-odd rule, so nested sub-paths such as polygon holes stay empty
+    fn fill_even_odd(&self);
+    fn stroke(&self);
+    fn set_fill_style(&self, style: &str);
+    fn set_stroke_style(&self, style: &str);
+    fn set_line_width(&self, width: f64);
+    // Alternating dash and gap lengths in pixels for later strokes; empty for solid lines
+    fn set_line_dash(&self, segments: &[f64]) -> GeoArrowResult<()>;
+    fn set_global_alpha(&self, alpha: f64);
+    fn set_image_smoothing_enabled(&self, enabled: bool);
+    fn set_shadow_color(&self, color: &str);
+    fn set_shadow_blur(&self, blur: f64);
+    fn set_shadow_offset_x(&self, offset: f64);
+    fn set_shadow_offset_y(&self, offset: f64);
+    fn clear_rect(&self, x: f64, y: f64, width: f64, height: f64);
+    fn fill_rect(&self, x: f64, y: f64, width: f64, height: f64);
+    fn fill_text(&self, text: &str, x: f64, y: f64) -> GeoArrowResult<()>;
+    fn save(&self);
+    fn restore(&self);
+    fn clip(&self);
+}
+
+impl DrawTarget for CanvasRenderingContext2d {
+    fn begin_path(&self) {
+        CanvasRenderingContext2d::begin_path(self);
+    }
+
+    fn move_to(&self, x: f64, y: f64) {
+        CanvasRenderingContext2d::move_to(self, x, y);
+    }
+
+    fn line_to(&self, x: f64, y: f64) {
+        CanvasRenderingContext2d::line_to(self, x, y);
+    }
+
+    fn quadratic_curve_to(&self, cpx: f64, cpy: f64, x: f64, y: f64) {
+        CanvasRenderingContext2d::quadratic_curve_to(self, cpx, cpy, x, y);
+    }
+
+    fn bezier_curve_to(&self, cp1x: f64, cp1y: f64, cp2x: f64, cp2y: f64, x: f64, y: f64) {
+        CanvasRenderingContext2d::bezier_curve_to(self, cp1x, cp1y, cp2x, cp2y, x, y);
+    }
+
+    fn arc(&self, x: f64, y: f64, radius: f64, start_angle: f64, end_angle: f64) -> GeoArrowResult<()> {
+        CanvasRenderingContext2d::arc(self, x, y, radius, start_angle, end_angle)
+            .map_err(|_| GeoArrowError::Wasm("Failed to draw arc".to_string()))
+    }
+
+    fn close_path(&self) {
+        CanvasRenderingContext2d::close_path(self);
+    }
+
+    fn fill(&self) {
+        CanvasRenderingContext2d::fill(self);
+    }
+
+    fn fill_even_odd(&self) {
+        self.fill_with_canvas_winding_rule(CanvasWindingRule::Evenodd);
+    }
+
+    fn stroke(&self) {
+        CanvasRenderingContext2d::stroke(self);
+    }
+
+    fn set_fill_style(&self, style: &str) {
+        self.set_fill_style_str(style);
+    }
+
+    fn set_stroke_style(&self, style: &str) {
+        self.set_stroke_style_str(style);
+    }
+
+    fn set_line_width(&self, width: f64) {
+        CanvasRenderingContext2d::set_line_width(self, width);
+    }
+
+    fn set_line_dash(&self, segments: &[f64]) -> GeoArrowResult<()> {
+        let segments: js_sys::Array = segments.iter().map(|&length| JsValue::from_f64(length)).collect();
+        CanvasRenderingContext2d::set_line_dash(self, &segments)
+            .map_err(|_| GeoArrowError::Wasm("Failed to set line dash".to_string()))
+    }
+
+    fn set_global_alpha(&self, alpha: f64) {
+        CanvasRenderingContext2d::set_global_alpha(self, alpha);
+    }
+
+    fn set_image_smoothing_enabled(&self, enabled: bool) {
+        CanvasRenderingContext2d::set_image_smoothing_enabled(self, enabled);
+    }
+
+    fn set_shadow_color(&self, color: &str) {
+        CanvasRenderingContext2d::set_shadow_color(self, color);
+    }
+
+    fn set_shadow_blur(&self, blur: f64) {
+        CanvasRenderingContext2d::set_shadow_blur(self, blur);
+    }
+
+    fn set_shadow_offset_x(&self, offset: f64) {
+        CanvasRenderingContext2d::set_shadow_offset_x(self, offset);
+    }
+
+    fn set_shadow_offset_y(&self, offset: f64) {
+        CanvasRenderingContext2d::set_shadow_offset_y(self, offset);
+    }
+
+    fn clear_rect(&self, x: f64, y: f64, width: f64, height: f64) {
+        CanvasRenderingContext2d::clear_rect(self, x, y, width, height);
+    }
+
+    fn fill_rect(&self, x: f64, y: f64, width: f64, height: f64) {
+        CanvasRenderingContext2d::fill_rect(self, x, y, width, height);
+    }
+
+    fn fill_text(&self, text: &str, x: f64, y: f64) -> GeoArrowResult<()> {
+        CanvasRenderingContext2d::fill_text(self, text, x, y)
+            .map_err(|_| GeoArrowError::Wasm("Failed to draw text".to_string()))
+    }
+
+    fn save(&self) {
+        CanvasRenderingContext2d::save(self);
+    }
+
+    fn restore(&self) {
+        CanvasRenderingContext2d::restore(self);
+    }
+
+    fn clip(&self) {
+        CanvasRenderingContext2d::clip(self);
+    }
+}
+
+// Style setters are comparatively expensive on WASM, so batched drawing goes through this
+// wrapper: it remembers the fill, stroke, line width and global alpha last sent to the inner
+// target and drops setters that wouldn't change them. save/restore keep the remembered state
+// in step with the canvas state stack.
+pub struct StateCachingTarget<'a> {
+    inner: &'a dyn DrawTarget,
+    state: RefCell<CanvasState>,
+    saved: RefCell<Vec<CanvasState>>,
+}
+
+// Last value sent for each cached setter; `None` until first set
+#[derive(Clone, Default)]
+struct CanvasState {
+    fill_style: Option<String>,
+    stroke_style: Option<String>,
+    line_width: Option<f64>,
+    global_alpha: Option<f64>,
+}
+
+impl<'a> StateCachingTarget<'a> {
+    pub fn new(inner: &'a dyn DrawTarget) -> Self {
+        Self { inner, state: RefCell::new(CanvasState::default()), saved: RefCell::new(Vec::new()) }
+    }
+
+    // Store `value` in the slot and report whether it changed
+    fn update<T: PartialEq + Clone>(slot: &mut Option<T>, value: &T) -> bool {
+        if slot.as_ref() == Some(value) {
+            return false;
+        }
+        *slot = Some(value.clone());
+        true
+    }
+}
+
+impl DrawTarget for StateCachingTarget<'_> {
+    fn begin_path(&self) {
+        self.inner.begin_path();
+    }
+
+    fn move_to(&self, x: f64, y: f64) {
+        self.inner.move_to(x, y);
+    }
+
+    fn line_to(&self, x: f64, y: f64) {
+        self.inner.line_to(x, y);
+    }
+
+    fn quadratic_curve_to(&self, cpx: f64, cpy: f64, x: f64, y: f64) {
+        self.inner.quadratic_curve_to(cpx, cpy, x, y);
+    }
+
+    fn bezier_curve_to(&self, cp1x: f64, cp1y: f64, cp2x: f64, cp2y: f64, x: f64, y: f64) {
+        self.inner.bezier_curve_to(cp1x, cp1y, cp2x, cp2y, x, y);
+    }
+
+    fn arc(&self, x: f64, y: f64, radius: f64, start_angle: f64, end_angle: f64) -> GeoArrowResult<()> {
+        self.inner.arc(x, y, radius, start_angle, end_angle)
+    }
+
+    fn close_path(&self) {
+        self.inner.close_path();
+    }
+
+    fn fill(&self) {
+        self.inner.fill();
+    }
+
+    fn fill_even_odd(&self) {
+        self.inner.fill_even_odd();
+    }
+
+    fn stroke(&self) {
+        self.inner.stroke();
+    }
+
+    fn set_fill_style(&self, style: &str) {
+        if Self::update(&mut self.state.borrow_mut().fill_style, &style.to_string()) {
+            self.inner.set_fill_style(style);
+        }
+    }
+
+    fn set_stroke_style(&self, style: &str) {
+        if Self::update(&mut self.state.borrow_mut().stroke_style, &style.to_string()) {
+            self.inner.set_stroke_style(style);
+        }
+    }
+
+    fn set_line_width(&self, width: f64) {
+        if Self::update(&mut self.state.borrow_mut().line_width, &width) {
+            self.inner.set_line_width(width);
+        }
+    }
+
+    fn set_line_dash(&self, segments: &[f64]) -> GeoArrowResult<()> {
+        self.inner.set_line_dash(segments)
+    }
+
+    fn set_global_alpha(&self, alpha: f64) {
+        if Self::update(&mut self.state.borrow_mut().global_alpha, &alpha) {
+            self.inner.set_global_alpha(alpha);
+        }
+    }
+
+    fn set_image_smoothing_enabled(&self, enabled: bool) {
+        self.inner.set_image_smoothing_enabled(enabled);
+    }
+
+    fn set_shadow_color(&self, color: &str) {
+        self.inner.set_shadow_color(color);
+    }
+
+    fn set_shadow_blur(&self, blur: f64) {
+        self.inner.set_shadow_blur(blur);
+    }
+
+    fn set_shadow_offset_x(&self, offset: f64) {
+        self.inner.set_shadow_offset_x(offset);
+    }
+
+    fn set_shadow_offset_y(&self, offset: f64) {
+        self.inner.set_shadow_offset_y(offset);
+    }
+
+    fn clear_rect(&self, x: f64, y: f64, width: f64, height: f64) {
+        self.inner.clear_rect(x, y, width, height);
+    }
+
+    fn fill_rect(&self, x: f64, y: f64, width: f64, height: f64) {
+        self.inner.fill_rect(x, y, width, height);
+    }
+
+    fn fill_text(&self, text: &str, x: f64, y: f64) -> GeoArrowResult<()> {
+        self.inner.fill_text(text, x, y)
+    }
+
+    fn save(&self) {
+        self.saved.borrow_mut().push(self.state.borrow().clone());
+        self.inner.save();
+    }
+
+    fn restore(&self) {
+        if let Some(state) = self.saved.borrow_mut().pop() {
+            *self.state.borrow_mut() = state;
+        }
+        self.inner.restore();
+    }
+
+    fn clip(&self) {
+        self.inner.clip();
+    }
+}
+
+// Recording target used by the unit tests to assert on emitted draw commands
+#[cfg(test)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum DrawCommand {
+    BeginPath,
+    MoveTo(f64, f64),
+    LineTo(f64, f64),
+    QuadraticCurveTo(f64, f64, f64, f64),
+    BezierCurveTo(f64, f64, f64, f64, f64, f64),
+    Arc(f64, f64, f64, f64, f64),
+    ClosePath,
+    Fill,
+    FillEvenOdd,
+    Stroke,
+    SetFillStyle(String),
+    SetStrokeStyle(String),
+    SetLineWidth(f64),
+    SetLineDash(Vec<f64>),
+    SetGlobalAlpha(f64),
+    SetImageSmoothingEnabled(bool),
+    SetShadowColor(String),
+    SetShadowBlur(f64),
+    SetShadowOffsetX(f64),
+    SetShadowOffsetY(f64),
+    ClearRect(f64, f64, f64, f64),
+    FillRect(f64, f64, f64, f64),
+    FillText(String, f64, f64),
+    Save,
+    Restore,
+    Clip,
+}
+
+#[cfg(test)]
+#[derive(Default)]
+pub struct RecordingTarget {
+    pub commands: std::cell::RefCell<Vec<DrawCommand>>,
+    // Makes non-empty dash patterns fail, like a canvas rejecting them
+    pub reject_line_dash: std::cell::Cell<bool>,
+}
+
+#[cfg(test)]
+impl RecordingTarget {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn commands(&self) -> Vec<DrawCommand> {
+        self.commands.borrow().clone()
+    }
+
+    fn record(&self, command: DrawCommand) {
+        self.commands.borrow_mut().push(command);
+    }
+}
+
+#[cfg(test)]
+impl DrawTarget for RecordingTarget {
+    fn begin_path(&self) {
+        self.record(DrawCommand::BeginPath);
+    }
+
+    fn move_to(&self, x: f64, y: f64) {
+        self.record(DrawCommand::MoveTo(x, y));
+    }
+
+    fn line_to(&self, x: f64, y: f64) {
+        self.record(DrawCommand::LineTo(x, y));
+    }
+
+    fn quadratic_curve_to(&self, cpx: f64, cpy: f64, x: f64, y: f64) {
+        self.record(DrawCommand::QuadraticCurveTo(cpx, cpy, x, y));
+    }
+
+    fn bezier_curve_to(&self, cp1x: f64, cp1y: f64, cp2x: f64, cp2y: f64, x: f64, y: f64) {
+        self.record(DrawCommand::BezierCurveTo(cp1x, cp1y, cp2x, cp2y, x, y));
+    }
+
+    fn arc(&self, x: f64, y: f64, radius: f64, start_angle: f64, end_angle: f64) -> GeoArrowResult<()> {
+        self.record(DrawCommand::Arc(x, y, radius, start_angle, end_angle));
+        Ok(())
+    }
+
+    fn close_path(&self) {
+        self.record(DrawCommand::ClosePath);
+    }
+
+    fn fill(&self) {
+        self.record(DrawCommand::Fill);
+    }
+
+    fn fill_even_odd(&self) {
+        self.record(DrawCommand::FillEvenOdd);
+    }
+
+    fn stroke(&self) {
+        self.record(DrawCommand::Stroke);
+    }
+
+    fn set_fill_style(&self, style: &str) {
+        self.record(DrawCommand::SetFillStyle(style.to_string()));
+    }
+
+    fn set_stroke_style(&self, style: &str) {
+        self.record(DrawCommand::SetStrokeStyle(style.to_string()));
+    }
+
+    fn set_line_width(&self, width: f64) {
+        self.record(DrawCommand::SetLineWidth(width));
+    }
+
+    fn set_line_dash(&self, segments: &[f64]) -> GeoArrowResult<()> {
+        if self.reject_line_dash.get() && !segments.is_empty() {
+            return Err(GeoArrowError::Wasm("Failed to set line dash".to_string()));
+        }
+        self.record(DrawCommand::SetLineDash(segments.to_vec()));
+        Ok(())
+    }
+
+    fn set_global_alpha(&self, alpha: f64) {
+        self.record(DrawCommand::SetGlobalAlpha(alpha));
+    }
+
+    fn set_image_smoothing_enabled(&self, enabled: bool) {
+        self.record(DrawCommand::SetImageSmoothingEnabled(enabled));
+    }
+
+    fn set_shadow_color(&self, color: &str) {
+        self.record(DrawCommand::SetShadowColor(color.to_string()));
+    }
+
+    fn set_shadow_blur(&self, blur: f64) {
+        self.record(DrawCommand::SetShadowBlur(blur));
+    }
+
+    fn set_shadow_offset_x(&self, offset: f64) {
+        self.record(DrawCommand::SetShadowOffsetX(offset));
+    }
+
+    fn set_shadow_offset_y(&self, offset: f64) {
+        self.record(DrawCommand::SetShadowOffsetY(offset));
+    }
+
+    fn clear_rect(&self, x: f64, y: f64, width: f64, height: f64) {
+        self.record(DrawCommand::ClearRect(x, y, width, height));
+    }
+
+    fn fill_rect(&self, x: f64, y: f64, width: f64, height: f64) {
+        self.record(DrawCommand::FillRect(x, y, width, height));
+    }
+
+    fn fill_text(&self, text: &str, x: f64, y: f64) -> GeoArrowResult<()> {
+        self.record(DrawCommand::FillText(text.to_string(), x, y));
+        Ok(())
+    }
+
+    fn save(&self) {
+        self.record(DrawCommand::Save);
+    }
+
+    fn restore(&self) {
+        self.record(DrawCommand::Restore);
+    }
+
+    fn clip(&self) {
+        self.record(DrawCommand::Clip);
+    }
+}
@@ -0,0 +1,90 @@
+use web_sys::CanvasRenderingContext2d;
+use crate::error::GeoArrowError;
+use crate::model::GeoArrowResult;
+
+// Text measurement for label layout, with a browser implementation and a native approximation
+
+// Font size assumed when a CSS font string has no recognizable `px`/`pt` size
+pub const DEFAULT_FONT_PX: f64 = 12.0;
+
+pub trait TextMeasure {
+    /// Width and height in pixels of `text` drawn in the CSS `font` (e.g. "12px sans-serif")
+    fn measure_text(&self, text: &str, font: &str) -> GeoArrowResult<(f64, f64)>;
+}
+
+impl TextMeasure for CanvasRenderingContext2d {
+    fn measure_text(&self, text: &str, font: &str) -> GeoArrowResult<(f64, f64)> {
+        let previous_font = self.font();
+        self.set_font(font);
+        let metrics = CanvasRenderingContext2d::measure_text(self, text);
+        self.set_font(&previous_font);
+
+        let metrics = metrics.map_err(|_| GeoArrowError::Wasm("Failed to measure text".to_string()))?;
+        let height = metrics.actual_bounding_box_ascent() + metrics.actual_bounding_box_descent();
+        Ok((metrics.width(), height))
+    }
+}
+
+// Off-browser fallback: per-character advance widths as fractions of the font size, roughly
+// matching a proportional sans-serif. Height is the font size.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ApproximateTextMeasure;
+
+impl ApproximateTextMeasure {
+    fn advance(c: char) -> f64 {
+        match c {
+            ' ' | 'i' | 'j' | 'l' | '!' | '.' | ',' | ':' | ';' | '\'' | '|' => 0.28,
+            'f' | 'r' | 't' | 'I' | '(' | ')' | '[' | ']' | '-' => 0.36,
+            'm' | 'w' | 'M' | 'W' | '@' => 0.86,
+            c if c.is_ascii_uppercase() => 0.66,
+            c if c.is_ascii_digit() => 0.56,
+            c if c.is_ascii() => 0.52,
+            // CJK and other wide scripts are roughly square
+            _ => 1.0,
+        }
+    }
+}
+
+impl TextMeasure for ApproximateTextMeasure {
+    fn measure_text(&self, text: &str, font: &str) -> GeoArrowResult<(f64, f64)> {
+        let size = font_size_px(font);
+        let width = text.chars().map(Self::advance).sum::<f64>() * size;
+        Ok((width, size))
+    }
+}
+
+// Pixel size from a CSS font shorthand; points are converted at 96 dpi
+pub const font_size_px: fn(&str) -> f64 =
+    |font| {
+        font.split_whitespace()
+            .find_map(|token| {
+                let token = token.split('/').next().unwrap_or(token);
+                if let Some(px) = token.strip_suffix("px") {
+                    px.parse::<f64>().ok()
+                } else {
+                    token.strip_suffix("pt").and_then(|pt| pt.parse::<f64>().ok()).map(|pt| pt * 96.0 / 72.0)
+                }
+            })
+            .filter(|size| *size > 0.0)
+            .unwrap_or(DEFAULT_FONT_PX)
+    };
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_approximate_measure_is_plausible() {
+        let measure = ApproximateTextMeasure;
+        let (width, height) = measure.measure_text("Salt Lake City", "bold 16px sans-serif").unwrap();
+
+        assert_eq!(height, 16.0);
+        // Real sans-serif renderings of this label are roughly 100-120px wide at 16px
+        assert!((80.0..140.0).contains(&width), "width {}", width);
+        let (narrow, _) = measure.measure_text("iiii", "16px sans-serif").unwrap();
+        let (wide, _) = measure.measure_text("MMMM", "16px sans-serif").unwrap();
+        assert!(narrow < wide);
+        assert_eq!(font_size_px("italic 12pt/1.5 serif"), 16.0);
+        assert_eq!(font_size_px("serif"), DEFAULT_FONT_PX);
+    }
+}
@@ -1,5 +1,10 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
 use dashmap::DashMap;
-use crate::model::{Tile, GeoArrowResult}; 
+use crate::engine::{render_clipped, render_feature_batch, RenderContext};
+use crate::engine::target::DrawTarget;
+use crate::model::{GeoArrowResult, GeoFeature, Tile, TileStatus};
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct TileInfo {
     pub id: u32,
     pub x: u32,
@@ -7,11 +12,22 @@ pub struct TileInfo {
     pub z: u8,
 }
 
+// Deepest zoom tiles are requested at; deeper views overzoom these tiles. Keeps every tile's
+// pyramid index within a u32 id.
+pub const MAX_TILE_ZOOM: u8 = 15;
+
 impl TileInfo {
     pub fn new(id: u32, x: u32, y: u32, z: u8) -> Self {
         Self {id, x, y, z}
     }
 
+    /// Tile `(x, y, z)` identified by its index in the tile pyramid: every tile of the zoom
+    /// levels above, then row by row. `z` must not exceed `MAX_TILE_ZOOM`.
+    pub fn at(x: u32, y: u32, z: u8) -> Self {
+        let above = ((1u32 << (2 * z)) - 1) / 3;
+        Self::new(above + y * (1 << z) + x, x, y, z)
+    }
+
 }
 
 pub struct TileCache {
@@ -26,54 +42,370 @@ impl TileCache {
     pub fn new(max_size: usize) -> Self {
         Self {
             tiles: DashMap::new(),
+            access_order: Vec::new(),
             max_size,
             current_size: 0,
         }
     }
-    pub fn get(&self, id: &u32) -> Option<Tile> {
-        self.tiles.get(id).map(|entry| entry.value().clone())
+    /// Cached tile, marking it as the most recently used
+    pub fn get(&mut self, id: &u32) -> Option<Tile> {
+        let tile = self.tiles.get(id).map(|entry| entry.value().clone())?;
+        self.touch(*id);
+        Some(tile)
     }
 
+    /// Cache a tile, evicting the least recently used one when the cache is full. Replacing a
+    /// cached tile doesn't evict anything.
     pub fn insert(&mut self, id: u32, tile: Tile) {
-        if self.current_size >= self.max_size {
-            self.evict_oldest()
+        if self.tiles.insert(id, tile).is_some() {
+            self.touch(id);
+            return;
+        }
+        self.access_order.push(id);
+        self.current_size += 1;
+        if self.current_size > self.max_size {
+            self.evict_oldest();
+        }
+    }
+
+    // Move `id` to the most recently used end of the access order
+    fn touch(&mut self, id: u32) {
+        if let Some(position) = self.access_order.iter().position(|&cached| cached == id) {
+            self.access_order.remove(position);
+            self.access_order.push(id);
         }
-        todo!()
     }
 
-    async fn evict_oldest(&mut self) -> GeoArrowResult<()> {
-        if let Some(oldest_id) = self.access_order.first() {
-            self.tiles.remove(oldest_id);
-            self.access_order.remove(0);
+    fn evict_oldest(&mut self) {
+        // The tile just inserted sits at the back, so it is only evicted by a zero-size cache
+        if !self.access_order.is_empty() {
+            let oldest_id = self.access_order.remove(0);
+            self.tiles.remove(&oldest_id);
             self.current_size -= 1;
+        }
+    }
 
+    /// Cached tile, leaving the access order alone
+    pub fn peek(&self, id: &u32) -> Option<Tile> {
+        self.tiles.get(id).map(|entry| entry.value().clone())
+    }
 
-        }
-        Ok(())
+    /// Record a failed fetch for a cached tile. Returns false when the tile isn't cached.
+    pub fn mark_failed(&self, id: &u32, reason: &str) -> bool {
+        self.tiles.get_mut(id).map(|mut tile| tile.mark_failed(reason)).is_some()
+    }
+
+    pub fn contains(&self, id: &u32) -> bool {
+        self.tiles.contains_key(id)
     }
 
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
 
-        
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
 
+    /// Drop every cached tile and reset the bookkeeping
+    pub fn clear(&mut self) {
+        self.tiles.clear();
+        self.access_order.clear();
+        self.current_size = 0;
+    }
+
+    #[allow(dead_code)]
     fn memory_usage(&self) -> usize {
         self.current_size
     }
 }
 
+// What to draw in place of a tile whose fetch failed, instead of leaving a gap
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum TilePlaceholder {
+    /// Fill with a solid CSS color
+    Color(String),
+    /// Light gray fill crossed with a diagonal hatch
+    #[default]
+    MissingPattern,
+    /// Draw the parent tile's features over this tile's extent, falling back to the pattern
+    /// when the parent isn't loaded
+    ParentUpscaled,
+}
+
+// Spacing in pixels of the missing-tile hatch lines
+const HATCH_SPACING: f64 = 16.0;
+
+// Draw one tile where its bounds fall in `context`'s view. Loaded tiles draw their features,
+// failed tiles the style's placeholder, and tiles still loading nothing.
+pub const render_tile: fn(&Tile, Option<&Tile>, &RenderContext, &dyn DrawTarget) -> GeoArrowResult<()> =
+    |tile, parent, context, target| {
+        let rect = tile_rect(tile, context);
+        match &tile.status {
+            TileStatus::Loaded => draw_tile_features(tile, rect, context, target),
+            TileStatus::Error(_) => match (&context.style.tile_placeholder, parent) {
+                (TilePlaceholder::Color(color), _) => {
+                    target.set_fill_style(color);
+                    target.fill_rect(rect.0, rect.1, rect.2, rect.3);
+                    Ok(())
+                }
+                (TilePlaceholder::ParentUpscaled, Some(parent)) if parent.status == TileStatus::Loaded => {
+                    draw_tile_features(parent, rect, context, target)
+                }
+                _ => draw_missing_pattern(target, rect),
+            },
+            TileStatus::NotLoaded | TileStatus::Loading => Ok(()),
+        }
+    };
+
+// Screen rectangle `(x, y, width, height)` the tile's bounds cover
+fn tile_rect(tile: &Tile, context: &RenderContext) -> (f64, f64, f64, f64) {
+    let b = &tile.bounds;
+    let (left, top) = RenderContext::world_to_screen(context, b.min_x, b.max_y);
+    let (right, bottom) = RenderContext::world_to_screen(context, b.max_x, b.min_y);
+    (left, top, right - left, bottom - top)
+}
+
+// Draw `source`'s features clipped to the screen rectangle of a tile
+const draw_tile_features: fn(&Tile, (f64, f64, f64, f64), &RenderContext, &dyn DrawTarget) -> GeoArrowResult<()> =
+    |source, (x, y, width, height), context, target| {
+        let context = context.clone()
+            .with_clip_mask(vec![(x, y), (x + width, y), (x + width, y + height), (x, y + height)]);
+        let features: Vec<&GeoFeature> = source.features.iter().collect();
+        render_clipped(&context, target, &|target| render_feature_batch(&features, &context, target).map(|_| ()))
+    };
+
+const draw_missing_pattern: fn(&dyn DrawTarget, (f64, f64, f64, f64)) -> GeoArrowResult<()> =
+    |target, (x, y, width, height)| {
+        target.set_fill_style("#e0e0e0");
+        target.fill_rect(x, y, width, height);
+        target.set_stroke_style("#c0c0c0");
+        target.set_line_width(1.0);
+        target.begin_path();
+        // Diagonals where the offsets from the top-left corner sum to `offset`
+        let mut offset = HATCH_SPACING;
+        while offset < width + height {
+            target.move_to(x + offset.min(width), y + (offset - width).max(0.0));
+            target.line_to(x + (offset - height).max(0.0), y + offset.min(height));
+            offset += HATCH_SPACING;
+        }
+        target.stroke();
+        Ok(())
+    };
+
+// Tile request queue ordered by distance from the viewport center, so the middle of the map
+// fills in first. `center` is in fractional tile coordinates at the tiles' zoom level.
+pub struct TileScheduler {
+    center: (f64, f64),
+    queue: BinaryHeap<ScheduledTile>,
+    sequence: u64,
+}
+
+struct ScheduledTile {
+    distance: f64,
+    // Insertion order, so equally distant tiles are fetched first come, first served
+    sequence: u64,
+    tile: TileInfo,
+}
+
+impl PartialEq for ScheduledTile {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for ScheduledTile {}
 
+impl PartialOrd for ScheduledTile {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
 
+impl Ord for ScheduledTile {
+    // Reversed so the max-heap pops the nearest, oldest tile first
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.distance.total_cmp(&self.distance).then(other.sequence.cmp(&self.sequence))
+    }
+}
 
+impl TileScheduler {
+    pub fn new(center: (f64, f64)) -> Self {
+        Self { center, queue: BinaryHeap::new(), sequence: 0 }
+    }
 
+    /// Queue a tile request
+    pub fn schedule(&mut self, tile: TileInfo) {
+        let distance = self.distance(&tile);
+        self.queue.push(ScheduledTile { distance, sequence: self.sequence, tile });
+        self.sequence += 1;
+    }
+
+    /// The queued tile closest to the center
+    pub fn next_tile(&mut self) -> Option<TileInfo> {
+        self.queue.pop().map(|scheduled| scheduled.tile)
+    }
+
+    /// Move the center, e.g. after a pan, and re-prioritize the pending requests
+    pub fn set_center(&mut self, center: (f64, f64)) {
+        self.center = center;
+        let pending = std::mem::take(&mut self.queue).into_vec();
+        self.queue = pending
+            .into_iter()
+            .map(|scheduled| ScheduledTile { distance: self.distance(&scheduled.tile), ..scheduled })
+            .collect();
+    }
+
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    // Distance from the center to the middle of the tile
+    fn distance(&self, tile: &TileInfo) -> f64 {
+        (tile.x as f64 + 0.5 - self.center.0).hypot(tile.y as f64 + 0.5 - self.center.1)
+    }
+}
+
+impl Iterator for TileScheduler {
+    type Item = TileInfo;
+
+    fn next(&mut self) -> Option<TileInfo> {
+        self.next_tile()
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    
+    use crate::engine::target::{DrawCommand, RecordingTarget};
+    use crate::model::GeoBounds;
+    use crate::view::view::MapStyle;
+
+    // Context drawing exactly `tile`'s bounds onto a 256px square
+    fn tile_context(tile: &Tile, style: MapStyle) -> RenderContext {
+        let b = &tile.bounds;
+        RenderContext::new(GeoBounds::new(b.min_x, b.min_y, b.max_x, b.max_y), (256.0, 256.0), tile.z, style)
+    }
+
     #[test]
     fn test_tile_cache() {
-        let cache = TileCache::new(10);
-        let tile_info1  = TileInfo::new(0, 0, 0, 1) ;
-        let tile_info2 = TileInfo::new(1, 1, 1, 1);
+        let mut single = TileCache::new(1);
+        single.insert(0, Tile::new(0, 0, 1));
+        single.insert(1, Tile::new(1, 0, 1));
+        assert_eq!(single.len(), 1);
+        assert!(single.get(&1).is_some());
+
+        // A zero-size cache holds nothing
+        let mut none = TileCache::new(0);
+        none.insert(0, Tile::new(0, 0, 1));
+        assert!(none.is_empty());
+        assert!(none.get(&0).is_none());
+
+        let mut cache = TileCache::new(2);
+        cache.insert(0, Tile::new(0, 0, 1));
+        cache.insert(1, Tile::new(1, 0, 1));
+        cache.insert(2, Tile::new(0, 1, 1));
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(&0).is_none());
+        assert_eq!(cache.get(&2).map(|tile| (tile.x, tile.y)), Some((0, 1)));
+        assert_eq!(cache.memory_usage(), 2);
+    }
+
+    #[test]
+    fn test_tile_cache_evicts_least_recently_used() {
+        let mut cache = TileCache::new(3);
+        for id in 0..3 {
+            cache.insert(id, Tile::new(id, 0, 2));
+        }
+        // Reading tile 0 makes tile 1 the least recently used
+        assert!(cache.get(&0).is_some());
+        // Replacing a cached tile refreshes it without evicting anything
+        cache.insert(2, Tile::new(2, 1, 2));
+        assert_eq!(cache.len(), 3);
+
+        cache.insert(3, Tile::new(3, 0, 2));
+        assert!(cache.get(&1).is_none());
+        assert!(cache.get(&0).is_some() && cache.get(&2).is_some() && cache.get(&3).is_some());
+
+        cache.insert(4, Tile::new(0, 1, 2));
+        assert!(cache.get(&0).is_none());
+        assert_eq!(cache.len(), 3);
+    }
+
+    #[test]
+    fn test_clear_empties_cache() {
+        let mut cache = TileCache::new(10);
+        cache.insert(0, Tile::new(0, 0, 1));
+
+        cache.clear();
+
+        assert!(cache.is_empty());
+        assert!(cache.get(&0).is_none());
+        assert_eq!(cache.memory_usage(), 0);
+    }
+
+    #[test]
+    fn test_failed_tile_renders_placeholder() {
+        let mut cache = TileCache::new(4);
+        cache.insert(7, Tile::new(1, 1, 2));
+        assert!(cache.mark_failed(&7, "404 Not Found"));
+        assert!(!cache.mark_failed(&8, "404 Not Found"));
+        let tile = cache.get(&7).unwrap();
+        assert_eq!(tile.status, TileStatus::Error("404 Not Found".to_string()));
+
+        let style = MapStyle { tile_placeholder: TilePlaceholder::Color("#ff00ff".to_string()), ..MapStyle::default() };
+        let target = RecordingTarget::new();
+        render_tile(&tile, None, &tile_context(&tile, style), &target).unwrap();
+        assert_eq!(
+            target.commands(),
+            vec![DrawCommand::SetFillStyle("#ff00ff".to_string()), DrawCommand::FillRect(0.0, 0.0, 256.0, 256.0)]
+        );
+
+        // Without a loaded parent, ParentUpscaled falls back to the hatch pattern
+        let style = MapStyle { tile_placeholder: TilePlaceholder::ParentUpscaled, ..MapStyle::default() };
+        let target = RecordingTarget::new();
+        render_tile(&tile, Some(&Tile::new(0, 0, 1)), &tile_context(&tile, style.clone()), &target).unwrap();
+        assert!(target.commands().contains(&DrawCommand::Stroke));
+
+        let target = RecordingTarget::new();
+        render_tile(&Tile::new(1, 1, 2), None, &tile_context(&tile, style), &target).unwrap();
+        assert!(target.commands().is_empty());
+    }
+
+    #[test]
+    fn test_scheduler_fetches_center_tile_first() {
+        // 3x3 block of tiles around the center of tile (5, 5)
+        let mut scheduler = TileScheduler::new((5.5, 5.5));
+        let mut id = 0;
+        for y in 4..=6 {
+            for x in 4..=6 {
+                scheduler.schedule(TileInfo::new(id, x, y, 4));
+                id += 1;
+            }
+        }
+
+        let order: Vec<(u32, u32)> = scheduler.map(|tile| (tile.x, tile.y)).collect();
+        assert_eq!(order[0], (5, 5));
+        // Edge neighbours come before any corner
+        let first_corner = order.iter().position(|&(x, y)| x != 5 && y != 5).unwrap();
+        assert_eq!(first_corner, 5);
+        assert_eq!(order.len(), 9);
+    }
+
+    #[test]
+    fn test_scheduler_reprioritizes_after_pan() {
+        let mut scheduler = TileScheduler::new((0.5, 0.5));
+        scheduler.schedule(TileInfo::new(0, 0, 0, 2));
+        scheduler.schedule(TileInfo::new(1, 3, 0, 2));
+
+        scheduler.set_center((3.5, 0.5));
 
+        assert_eq!(scheduler.next_tile().map(|tile| tile.id), Some(1));
+        assert_eq!(scheduler.len(), 1);
     }
 }
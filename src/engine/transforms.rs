@@ -1,7 +1,19 @@
+use crate::engine::projection::WEB_MERCATOR_MAX_LAT;
 use crate::model::Bounds;
 
 // Pure transformation functions for coordinate systems and projections
 
+// Smallest canvas dimension, in pixels, used when deriving geographic extents from a canvas size
+pub const MIN_CANVAS_PX: f64 = 4.0;
+
+// Division for normalized positions: degenerate (zero-width, non-finite) spans map to the middle
+// instead of producing NaN or infinity
+pub const safe_ratio: fn(f64, f64) -> f64 =
+    |numerator, denominator| {
+        let ratio = numerator / denominator;
+        if denominator.abs() < f64::EPSILON || !ratio.is_finite() { 0.5 } else { ratio }
+    };
+
 // Zoom transformation functions
 pub const apply_zoom_transform: fn(f64, f64, u8) -> (f64, f64) =
     |x, y, zoom_level| {
@@ -9,8 +21,9 @@ pub const apply_zoom_transform: fn(f64, f64, u8) -> (f64, f64) =
         (x * scale_factor, y * scale_factor)
     };
 
-pub const create_zoom_transformer: fn(u8) -> fn(f64, f64) -> (f64, f64) =
-    |zoom_level| |x, y| apply_zoom_transform(x, y, zoom_level);
+pub fn create_zoom_transformer(zoom_level: u8) -> impl Fn(f64, f64) -> (f64, f64) {
+    move |x, y| apply_zoom_transform(x, y, zoom_level)
+}
 
 // Viewport bounds calculations
 pub const calculate_viewport_bounds: fn((f64, f64), (f64, f64), u8) -> Bounds =
@@ -60,6 +73,9 @@ pub const bounds_size: fn(&Bounds) -> (f64, f64) =
 pub const preserve_aspect_ratio: fn(&Bounds, f64) -> Bounds =
     |bounds, target_aspect_ratio| {
         let (width, height) = bounds_size(bounds);
+        if !(height > 0.0 && target_aspect_ratio > 0.0 && target_aspect_ratio.is_finite()) {
+            return bounds.clone();
+        }
         let current_aspect = width / height;
 
         if current_aspect > target_aspect_ratio {
@@ -89,20 +105,69 @@ pub const preserve_aspect_ratio: fn(&Bounds, f64) -> Bounds =
 pub const apply_pan_transform: fn(f64, f64, f64, f64) -> (f64, f64) =
     |x, y, dx, dy| (x + dx, y + dy);
 
-pub const create_pan_transformer: fn(f64, f64) -> fn(f64, f64) -> (f64, f64) =
-    |dx, dy| |x, y| apply_pan_transform(x, y, dx, dy);
+pub fn create_pan_transformer(dx: f64, dy: f64) -> impl Fn(f64, f64) -> (f64, f64) {
+    move |x, y| apply_pan_transform(x, y, dx, dy)
+}
+
+// Spherical Web Mercator y of a latitude, in radians of the unit sphere, clamped to the
+// projection's limit
+fn mercator_y(lat: f64) -> f64 {
+    lat.clamp(-WEB_MERCATOR_MAX_LAT, WEB_MERCATOR_MAX_LAT).to_radians().tan().asinh()
+}
+
+fn mercator_lat(y: f64) -> f64 {
+    y.sinh().atan().to_degrees()
+}
+
+// Pixel size of the unrotated frame a view's bounds span: the axis-aligned extent of the canvas
+// turned `rotation` degrees, never smaller than MIN_CANVAS_PX a side
+pub const view_frame: fn((f64, f64), f64) -> (f64, f64) =
+    |canvas_size, rotation| {
+        let (width, height) = (canvas_size.0.max(MIN_CANVAS_PX), canvas_size.1.max(MIN_CANVAS_PX));
+        let (sin, cos) = rotation.to_radians().sin_cos();
+        (width * cos.abs() + height * sin.abs(), width * sin.abs() + height * cos.abs())
+    };
+
+// Turn a pixel's offset from the canvas center through `degrees`
+pub const rotate_about_center: fn((f64, f64), (f64, f64), f64) -> (f64, f64) =
+    |screen_pos, canvas_size, degrees| {
+        let (cx, cy) = (canvas_size.0 / 2.0, canvas_size.1 / 2.0);
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        let (dx, dy) = (screen_pos.0 - cx, screen_pos.1 - cy);
+        (cx + dx * cos - dy * sin, cy + dx * sin + dy * cos)
+    };
 
-// Screen to world coordinate transformation
-pub const screen_to_world: fn((f64, f64), (f64, f64), &Bounds) -> (f64, f64) =
-    |screen_pos, canvas_size, bounds| {
-        let (screen_x, screen_y) = screen_pos;
+// World to screen coordinate transformation: `bounds` span the view frame centered on the canvas,
+// longitude linearly and latitude spaced by Web Mercator, y flipped so north is up. The frame is
+// then turned `rotation` degrees about the canvas center. `Viewport` projects through this too.
+pub const world_to_screen: fn((f64, f64), (f64, f64), f64, &Bounds) -> (f64, f64) =
+    |world_pos, canvas_size, rotation, bounds| {
+        let (world_x, world_y) = world_pos;
         let (canvas_width, canvas_height) = canvas_size;
+        let (frame_width, frame_height) = view_frame(canvas_size, rotation);
+
+        let x_ratio = safe_ratio(world_x - bounds.min_x, bounds.max_x - bounds.min_x);
+        let bottom = mercator_y(bounds.min_y);
+        let y_ratio = safe_ratio(mercator_y(world_y) - bottom, mercator_y(bounds.max_y) - bottom);
 
-        let x_ratio = screen_x / canvas_width;
-        let y_ratio = 1.0 - (screen_y / canvas_height); // Flip Y axis
+        let x = (canvas_width - frame_width) / 2.0 + x_ratio * frame_width;
+        let y = (canvas_height - frame_height) / 2.0 + (1.0 - y_ratio) * frame_height; // Flip Y axis
+        rotate_about_center((x, y), canvas_size, -rotation)
+    };
+
+// Screen to world coordinate transformation, the inverse of `world_to_screen`
+pub const screen_to_world: fn((f64, f64), (f64, f64), f64, &Bounds) -> (f64, f64) =
+    |screen_pos, canvas_size, rotation, bounds| {
+        let (canvas_width, canvas_height) = canvas_size;
+        let (frame_width, frame_height) = view_frame(canvas_size, rotation);
+        let (screen_x, screen_y) = rotate_about_center(screen_pos, canvas_size, rotation);
+
+        let x_ratio = safe_ratio(screen_x - (canvas_width - frame_width) / 2.0, frame_width);
+        let y_ratio = 1.0 - safe_ratio(screen_y - (canvas_height - frame_height) / 2.0, frame_height); // Flip Y axis
 
         let world_x = bounds.min_x + x_ratio * (bounds.max_x - bounds.min_x);
-        let world_y = bounds.min_y + y_ratio * (bounds.max_y - bounds.min_y);
+        let bottom = mercator_y(bounds.min_y);
+        let world_y = mercator_lat(bottom + y_ratio * (mercator_y(bounds.max_y) - bottom));
 
         (world_x, world_y)
     };
@@ -129,6 +194,43 @@ pub const calculate_bounds_from_coordinates: fn(&[(f64, f64)]) -> Option<Bounds>
         Some(Bounds::new(min_x, min_y, max_x, max_y))
     };
 
+// How data bounds are mapped onto a canvas
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FitMode {
+    /// Show all of the data with equal x/y scaling, padding the short axis
+    #[default]
+    Contain,
+    /// Fill the canvas with equal x/y scaling, cropping the long axis
+    Cover,
+    /// Map the data corners onto the canvas corners, scaling x and y independently
+    Stretch,
+}
+
+// World extent to render so that `data_bounds` fits a canvas of `canvas_size` under `mode`
+pub const fit_bounds_with_mode: fn(&Bounds, (f64, f64), FitMode) -> Bounds =
+    |data_bounds, canvas_size, mode| {
+        let (canvas_width, canvas_height) = canvas_size;
+        let canvas_aspect = canvas_width / canvas_height;
+        let (width, height) = bounds_size(data_bounds);
+        if !(canvas_aspect.is_finite() && canvas_aspect > 0.0 && width > 0.0 && height > 0.0) {
+            return data_bounds.clone();
+        }
+
+        match mode {
+            FitMode::Stretch => data_bounds.clone(),
+            FitMode::Contain => preserve_aspect_ratio(data_bounds, canvas_aspect),
+            FitMode::Cover => {
+                let (center_x, center_y) = bounds_center(data_bounds);
+                let (half_width, half_height) = if width / height > canvas_aspect {
+                    (height * canvas_aspect / 2.0, height / 2.0)
+                } else {
+                    (width / 2.0, width / canvas_aspect / 2.0)
+                };
+                Bounds::new(center_x - half_width, center_y - half_height, center_x + half_width, center_y + half_height)
+            }
+        }
+    };
+
 // Fit bounds to viewport
 pub const fit_bounds_to_viewport: fn(&Bounds, (f64, f64)) -> (u8, (f64, f64)) =
     |data_bounds, viewport_size| {
@@ -140,9 +242,31 @@ pub const fit_bounds_to_viewport: fn(&Bounds, (f64, f64)) -> (u8, (f64, f64)) =
         let scale = scale_x.min(scale_y);
 
         // Calculate zoom level (rough approximation)
-        let zoom_level = (scale.log2().floor() as i32).max(1).min(20) as u8;
+        let zoom_level = (scale.log2().floor() as i32).clamp(1, 20) as u8;
 
         let center = bounds_center(data_bounds);
 
         (zoom_level, center)
-    };
\ No newline at end of file
+    };
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::RenderContext;
+    use crate::view::view::MapStyle;
+
+    #[test]
+    fn test_fit_modes() {
+        let data = Bounds::new(-10.0, 0.0, 30.0, 10.0);
+        let canvas = (200.0, 200.0);
+        let context_for = |mode| RenderContext::new(fit_bounds_with_mode(&data, canvas, mode), canvas, 1, MapStyle::default());
+
+        let stretch = context_for(FitMode::Stretch);
+        assert_eq!((RenderContext::world_to_screen)(&stretch, -10.0, 0.0), (0.0, 200.0));
+        assert_eq!((RenderContext::world_to_screen)(&stretch, 30.0, 10.0), (200.0, 0.0));
+
+        // Contain pads latitude to a square 40x40 extent, Cover crops longitude to 10x10
+        assert_eq!(fit_bounds_with_mode(&data, canvas, FitMode::Contain), Bounds::new(-10.0, -15.0, 30.0, 25.0));
+        assert_eq!(fit_bounds_with_mode(&data, canvas, FitMode::Cover), Bounds::new(5.0, 0.0, 15.0, 10.0));
+    }
+}
@@ -1,15 +1,25 @@
+// The engine is written as `const` function pointers over tuple coordinates, and the view
+// module is nested as `view::view`; both are intentional.
+#![allow(clippy::type_complexity, clippy::module_inception)]
+
 use wasm_bindgen::prelude::*;
 mod error;
-use error::{GeoArrowError};
+pub mod engine;
 pub mod model;
-use model::{Bounds, GeoArrowFile, GeoArrowResult  };
 pub mod view;
-use view::view::{MapView, MapStyle};
+
+// Categorical legend colors, under the name the styling API documents
+pub mod style {
+    pub use crate::model::stylesheet::{category_color, CATEGORY_PALETTE_SIZE};
+}
 
 
 #[wasm_bindgen(start)]
 fn start() {
-    tracing_subscriber::fmt::init();
+    // Report span durations on close so slow renders and loads show up in the logs
+    tracing_subscriber::fmt()
+        .with_span_events(tracing_subscriber::fmt::format::FmtSpan::CLOSE)
+        .init();
 
 
 
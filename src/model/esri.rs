@@ -0,0 +1,256 @@
+use dashmap::DashMap;
+use serde_json::Value;
+use crate::engine::geometry::{ring_contains_point, signed_ring_area};
+use crate::error::GeoArrowError;
+use crate::model::{FeatureGeometry, FeatureId, GeoArrowResult, GeoFeature, GeoPoint};
+
+// EsriJSON (ArcGIS REST) geometry support
+
+/// True when the document is EsriJSON: a geometry with `rings`/`paths` keys, or a feature set
+/// whose features carry such geometries (or `x`/`y` points alongside `attributes`).
+pub fn is_esri_json(value: &Value) -> bool {
+    let is_esri_geometry = |geometry: &Value| {
+        geometry.get("rings").is_some() || geometry.get("paths").is_some()
+    };
+
+    if is_esri_geometry(value) {
+        return true;
+    }
+    value
+        .get("features")
+        .and_then(Value::as_array)
+        .and_then(|features| features.first())
+        .is_some_and(|feature| {
+            feature.get("geometry").is_some_and(|geometry| {
+                is_esri_geometry(geometry)
+                    || (feature.get("attributes").is_some() && geometry.get("x").is_some())
+            })
+        })
+}
+
+/// Convert an EsriJSON feature set (or a bare geometry) into features. Attributes become
+/// properties, and `OBJECTID` is used as the feature id when present.
+pub fn features_from_esri_json(value: &Value) -> GeoArrowResult<Vec<GeoFeature>> {
+    let Some(features) = value.get("features").and_then(Value::as_array) else {
+        let geometry = FeatureGeometry::from_esri_json(value)?;
+        return Ok(vec![GeoFeature::new(new_id(), geometry, DashMap::new())]);
+    };
+
+    features
+        .iter()
+        .map(|feature| {
+            let geometry = feature
+                .get("geometry")
+                .ok_or_else(|| GeoArrowError::Serialization("Esri feature has no geometry".to_string()))
+                .and_then(FeatureGeometry::from_esri_json)?;
+            let properties: DashMap<String, Value> = feature
+                .get("attributes")
+                .and_then(Value::as_object)
+                .into_iter()
+                .flatten()
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect();
+            let id = properties
+                .get("OBJECTID")
+                .and_then(|id| id.as_u64())
+                .map(FeatureId::from)
+                .unwrap_or_else(new_id);
+            Ok(GeoFeature::new(id, geometry, properties))
+        })
+        .collect()
+}
+
+fn new_id() -> FeatureId {
+    FeatureId::String(uuid::Uuid::new_v4().to_string())
+}
+
+impl FeatureGeometry {
+    /// Convert an Esri geometry object. `x`/`y` becomes a Point, `points` a MultiPoint,
+    /// `paths` a LineString or MultiLineString, and `rings` a Polygon or MultiPolygon.
+    /// Esri marks exterior rings clockwise and holes counter-clockwise. Rings are regrouped by
+    /// that rule and rewound to the RFC 7946 order (exterior counter-clockwise).
+    /// A point's `z`, or the third value of each position when `hasZ` is set, becomes the
+    /// elevation; M values are dropped.
+    pub fn from_esri_json(geometry: &Value) -> GeoArrowResult<FeatureGeometry> {
+        if let (Some(x), Some(y)) = (geometry.get("x"), geometry.get("y")) {
+            let (Some(x), Some(y)) = (x.as_f64(), y.as_f64()) else {
+                return Err(GeoArrowError::Serialization("Invalid Esri point coordinates".to_string()));
+            };
+            let z = match geometry.get("z") {
+                Some(z) => Some(z.as_f64().ok_or_else(|| GeoArrowError::Serialization("Invalid Esri point coordinates".to_string()))?),
+                None => None,
+            };
+            return Ok(FeatureGeometry::Point(checked_point(x, y, z)?));
+        }
+
+        let has_z = geometry.get("hasZ").and_then(Value::as_bool).unwrap_or(false);
+        if let Some(points) = geometry.get("points") {
+            return Ok(FeatureGeometry::MultiPoint(parse_path(points, has_z)?));
+        }
+
+        if let Some(paths) = geometry.get("paths") {
+            let mut lines = parse_paths(paths, has_z)?;
+            return Ok(if lines.len() == 1 {
+                FeatureGeometry::LineString(lines.remove(0))
+            } else {
+                FeatureGeometry::MultiLineString(lines)
+            });
+        }
+
+        if let Some(rings) = geometry.get("rings") {
+            let mut polygons = group_rings(parse_paths(rings, has_z)?);
+            return Ok(if polygons.len() == 1 {
+                FeatureGeometry::Polygon(polygons.remove(0))
+            } else {
+                FeatureGeometry::MultiPolygon(polygons)
+            });
+        }
+
+        Err(GeoArrowError::Serialization(
+            "Unsupported Esri geometry: expected x/y, points, paths or rings".to_string(),
+        ))
+    }
+}
+
+// Clockwise rings start polygons; counter-clockwise rings are holes of the outer ring containing them
+fn group_rings(rings: Vec<Vec<GeoPoint>>) -> Vec<Vec<Vec<GeoPoint>>> {
+    let (outers, holes): (Vec<_>, Vec<_>) = rings
+        .into_iter()
+        .partition(|ring| signed_ring_area(ring) <= 0.0);
+
+    let mut polygons: Vec<Vec<Vec<GeoPoint>>> = outers
+        .into_iter()
+        .map(|mut ring| {
+            ring.reverse();
+            vec![ring]
+        })
+        .collect();
+
+    for mut hole in holes {
+        let owner = hole.first().and_then(|probe| {
+            polygons.iter().position(|polygon| ring_contains_point(&polygon[0], probe))
+        });
+        hole.reverse();
+        match owner {
+            Some(index) => polygons[index].push(hole),
+            // An unenclosed counter-clockwise ring is treated as its own exterior
+            None => {
+                hole.reverse();
+                polygons.push(vec![hole]);
+            }
+        }
+    }
+    polygons
+}
+
+fn parse_paths(value: &Value, has_z: bool) -> GeoArrowResult<Vec<Vec<GeoPoint>>> {
+    value
+        .as_array()
+        .ok_or_else(|| GeoArrowError::Serialization("Esri paths/rings must be an array".to_string()))?
+        .iter()
+        .map(|path| parse_path(path, has_z))
+        .collect()
+}
+
+// Positions are [x, y], then z when the geometry has Z, then m when it has M
+fn parse_path(value: &Value, has_z: bool) -> GeoArrowResult<Vec<GeoPoint>> {
+    value
+        .as_array()
+        .ok_or_else(|| GeoArrowError::Serialization("Esri path must be an array".to_string()))?
+        .iter()
+        .map(|position| {
+            let coords = position.as_array().map(|c| (c.first(), c.get(1), c.get(2).filter(|_| has_z)));
+            match coords {
+                Some((Some(x), Some(y), z)) => match (x.as_f64(), y.as_f64(), z.map(Value::as_f64)) {
+                    (Some(x), Some(y), None) => checked_point(x, y, None),
+                    (Some(x), Some(y), Some(Some(z))) => checked_point(x, y, Some(z)),
+                    _ => Err(GeoArrowError::Serialization("Invalid Esri coordinate".to_string())),
+                },
+                _ => Err(GeoArrowError::Serialization("Invalid Esri coordinate".to_string())),
+            }
+        })
+        .collect()
+}
+
+fn checked_point(x: f64, y: f64, z: Option<f64>) -> GeoArrowResult<GeoPoint> {
+    let point = match z {
+        Some(z) => GeoPoint::new_3d(y, x, z),
+        None => GeoPoint::new(y, x),
+    };
+    if point.is_valid() {
+        Ok(point)
+    } else {
+        Err(GeoArrowError::Serialization("Invalid Esri coordinates".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_esri_polygon_with_hole() {
+        // Exterior clockwise, hole counter-clockwise (Esri convention)
+        let esri: Value = serde_json::from_str(
+            r#"{
+                "rings": [
+                    [[0, 0], [0, 10], [10, 10], [10, 0], [0, 0]],
+                    [[2, 2], [4, 2], [4, 4], [2, 4], [2, 2]]
+                ],
+                "spatialReference": {"wkid": 4326}
+            }"#,
+        )
+        .unwrap();
+
+        assert!(is_esri_json(&esri));
+        let FeatureGeometry::Polygon(rings) = FeatureGeometry::from_esri_json(&esri).unwrap() else {
+            panic!("expected a polygon");
+        };
+        assert_eq!(rings.len(), 2);
+        // RFC 7946 winding: exterior counter-clockwise, hole clockwise
+        assert!(signed_ring_area(&rings[0]) > 0.0);
+        assert!(signed_ring_area(&rings[1]) < 0.0);
+        assert!(rings[1].iter().all(|p| (2.0..=4.0).contains(&p.lng)));
+    }
+
+    #[test]
+    fn test_esri_feature_set() {
+        let esri: Value = serde_json::from_str(
+            r#"{
+                "geometryType": "esriGeometryPolyline",
+                "features": [
+                    {"attributes": {"OBJECTID": 7, "name": "road"},
+                     "geometry": {"paths": [[[0, 0], [1, 1]], [[2, 2], [3, 3]]]}},
+                    {"attributes": {"OBJECTID": 8},
+                     "geometry": {"paths": [[[5, 5], [6, 6]]]}}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert!(is_esri_json(&esri));
+        let features = features_from_esri_json(&esri).unwrap();
+        assert_eq!(features[0].id, FeatureId::from(7u64));
+        assert!(matches!(features[0].geometry().unwrap(), FeatureGeometry::MultiLineString(lines) if lines.len() == 2));
+        assert!(matches!(features[1].geometry().unwrap(), FeatureGeometry::LineString(_)));
+        assert_eq!(features[0].properties.get("name").unwrap().as_str(), Some("road"));
+    }
+
+    #[test]
+    fn test_esri_z_values_become_elevation() {
+        let point: Value = serde_json::from_str(r#"{"x": 13.4, "y": 52.5, "z": 34.0}"#).unwrap();
+        assert_eq!(FeatureGeometry::from_esri_json(&point).unwrap(), FeatureGeometry::Point(GeoPoint::new_3d(52.5, 13.4, 34.0)));
+
+        let line: Value = serde_json::from_str(r#"{"hasZ": true, "hasM": true, "paths": [[[0, 0, 10, 1], [1, 1, 12, 2]]]}"#).unwrap();
+        assert_eq!(
+            FeatureGeometry::from_esri_json(&line).unwrap(),
+            FeatureGeometry::LineString(vec![GeoPoint::new_3d(0.0, 0.0, 10.0), GeoPoint::new_3d(1.0, 1.0, 12.0)])
+        );
+        // Without hasZ the third value is an M and is dropped
+        let measured: Value = serde_json::from_str(r#"{"hasM": true, "paths": [[[0, 0, 5], [1, 1, 6]]]}"#).unwrap();
+        assert_eq!(
+            FeatureGeometry::from_esri_json(&measured).unwrap(),
+            FeatureGeometry::LineString(vec![GeoPoint::new(0.0, 0.0), GeoPoint::new(1.0, 1.0)])
+        );
+    }
+}
@@ -1,11 +1,22 @@
+use crate::engine::geometry::{ring_contains_point, signed_ring_area};
+use crate::engine::projection::{tiles_covering, Projection, WebMercator, WEB_MERCATOR_MAX_LAT};
+use crate::engine::transforms::{self, MIN_CANVAS_PX};
 use crate::error::GeoArrowError;
 use arrow::datatypes::Schema;
 use dashmap::DashMap;
-use geojson::{Feature, FeatureCollection, Geometry, Position, Value as GeoValue};
+use geojson::{Feature, FeatureCollection, Geometry, Value as GeoValue};
 use std::fmt::Debug;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 pub type GeoArrowResult<T> = Result<T, GeoArrowError>;
 
+pub mod esri;
+pub mod stylesheet;
+mod wkb;
+mod wkt;
+
+pub use stylesheet::ZoomStop;
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct GeoBounds {
     pub min_x: f64,
@@ -43,20 +54,115 @@ impl GeoBounds {
         }
     }
 
+    /// Whether the box wraps across ±180° longitude, which is written as `min_x > max_x`
+    /// (as in a GeoJSON `bbox`): it spans `min_x..=180` and `-180..=max_x`
+    pub fn crosses_antimeridian(&self) -> bool {
+        self.min_x > self.max_x && self.min_y <= self.max_y
+    }
+
+    /// The box as one or two boxes that don't cross the antimeridian, split at ±180°
+    pub fn split_antimeridian(&self) -> Vec<GeoBounds> {
+        if !self.crosses_antimeridian() {
+            return vec![self.clone()];
+        }
+        vec![
+            GeoBounds::new(self.min_x, self.min_y, 180.0, self.max_y),
+            GeoBounds::new(-180.0, self.min_y, self.max_x, self.max_y),
+        ]
+    }
+
     pub fn contains(&self, x: f64, y: f64) -> bool {
-        x >= self.min_x && x <= self.max_x && y >= self.min_y && y <= self.max_y
+        let within_x = if self.crosses_antimeridian() {
+            x >= self.min_x || x <= self.max_x
+        } else {
+            x >= self.min_x && x <= self.max_x
+        };
+        within_x && y >= self.min_y && y <= self.max_y
     }
 
     pub fn is_empty(&self) -> bool {
-        self.min_x >= self.max_x || self.min_y >= self.max_y
+        (self.min_x >= self.max_x && !self.crosses_antimeridian()) || self.min_y >= self.max_y
     }
 
     pub fn intersects(&self, other: &GeoBounds) -> bool {
+        if self.crosses_antimeridian() || other.crosses_antimeridian() {
+            let others = other.split_antimeridian();
+            return self.split_antimeridian().iter().any(|part| others.iter().any(|other| part.intersects(other)));
+        }
         !(self.max_x <= other.min_x
             || self.min_x >= other.max_x
             || self.max_y <= other.min_y
             || self.min_y >= other.max_y)
     }
+
+    /// Smallest box containing both. Empty boxes contribute nothing, so the union of two empty
+    /// boxes is empty. When either box crosses the antimeridian the result may too: it takes
+    /// the shorter way around the globe.
+    pub fn union(&self, other: &GeoBounds) -> GeoBounds {
+        match (self.is_empty(), other.is_empty()) {
+            (_, true) => self.clone(),
+            (true, false) => other.clone(),
+            (false, false) if self.crosses_antimeridian() || other.crosses_antimeridian() => {
+                let parts: Vec<GeoBounds> = self.split_antimeridian().into_iter().chain(other.split_antimeridian()).collect();
+                covering_longitudes(&parts)
+            }
+            (false, false) => GeoBounds::new(
+                self.min_x.min(other.min_x),
+                self.min_y.min(other.min_y),
+                self.max_x.max(other.max_x),
+                self.max_y.max(other.max_y),
+            ),
+        }
+    }
+
+    pub fn center(&self) -> GeoPoint {
+        let lng = if self.crosses_antimeridian() {
+            let lng = (self.min_x + self.max_x + 360.0) / 2.0;
+            if lng > 180.0 { lng - 360.0 } else { lng }
+        } else {
+            (self.min_x + self.max_x) / 2.0
+        };
+        GeoPoint::new((self.min_y + self.max_y) / 2.0, lng)
+    }
+
+    /// Pad every side by a fixed number of degrees, clamped to the world extent.
+    /// See `transforms::expand_bounds` for padding proportional to the size instead.
+    /// A box crossing the antimeridian grows on both sides of it until it wraps the globe.
+    pub fn buffer(&self, degrees: f64) -> GeoBounds {
+        let (min_y, max_y) = ((self.min_y - degrees).max(-90.0), (self.max_y + degrees).min(90.0));
+        if self.crosses_antimeridian() {
+            let (min_x, max_x) = (self.min_x - degrees, self.max_x + degrees);
+            return if min_x <= max_x {
+                GeoBounds::new(-180.0, min_y, 180.0, max_y)
+            } else {
+                GeoBounds::new(min_x, min_y, max_x, max_y)
+            };
+        }
+        GeoBounds::new((self.min_x - degrees).max(-180.0), min_y, (self.max_x + degrees).min(180.0), max_y)
+    }
+}
+
+// Smallest box covering `parts`, none of which cross the antimeridian: the longitudes are
+// taken around the widest uncovered gap, which crosses the result over the antimeridian
+// unless that gap is the one at ±180°
+fn covering_longitudes(parts: &[GeoBounds]) -> GeoBounds {
+    let mut spans: Vec<(f64, f64)> = parts.iter().map(|part| (part.min_x, part.max_x)).collect();
+    spans.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let min_y = parts.iter().map(|part| part.min_y).fold(f64::INFINITY, f64::min);
+    let max_y = parts.iter().map(|part| part.max_y).fold(f64::NEG_INFINITY, f64::max);
+
+    let (west, mut east) = (spans[0].0, spans[0].1);
+    let mut widest_gap = (west + 360.0 - spans.iter().map(|span| span.1).fold(f64::NEG_INFINITY, f64::max), None);
+    for &(min_x, max_x) in &spans[1..] {
+        if min_x - east > widest_gap.0 {
+            widest_gap = (min_x - east, Some((min_x, east)));
+        }
+        east = east.max(max_x);
+    }
+    match widest_gap.1 {
+        Some((min_x, max_x)) => GeoBounds::new(min_x, min_y, max_x, max_y),
+        None => GeoBounds::new(west, min_y, east, max_y),
+    }
 }
 
 impl PixelBounds {
@@ -88,11 +194,89 @@ impl TileBounds {
         }
     }
 
+    /// Geographic extent of XYZ tile `z/x/y` on the Web Mercator grid: x in degrees of
+    /// longitude, y in degrees of latitude, with row 0 at the northern edge (85.0511° N)
     pub fn from_tile_coords(x: u32, y: u32, z: u8) -> Self {
-        let tile_size = 1.0 / (1u32 << z) as f64;
-        let min_x = x as f64 * tile_size;
-        let min_y = y as f64 * tile_size;
-        TileBounds::new(min_x, min_y, min_x + tile_size, min_y + tile_size)
+        let tiles = (1u64 << z) as f64;
+        let lng = |column: f64| column / tiles * 360.0 - 180.0;
+        let lat = |row: f64| (std::f64::consts::PI * (1.0 - 2.0 * row / tiles)).sinh().atan().to_degrees();
+        let (x, y) = (x as f64, y as f64);
+        TileBounds::new(lng(x), lat(y + 1.0), lng(x + 1.0), lat(y))
+    }
+
+    pub fn to_geo_bounds(&self) -> GeoBounds {
+        GeoBounds::new(self.min_x, self.min_y, self.max_x, self.max_y)
+    }
+}
+
+// Source encodings the loader can recognize
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SourceFormat {
+    /// GeoJSON or EsriJSON
+    Json,
+    /// Newline-delimited GeoJSON: one Feature per line
+    NdJson,
+    /// Comma-separated rows with latitude and longitude columns
+    Csv,
+    Parquet,
+    Protobuf,
+    Unknown,
+}
+
+impl SourceFormat {
+    /// Pick the format from the path extension, then the HTTP `Content-Type`, then by sniffing
+    /// the content: a `{...}` object per line is newline-delimited, otherwise the first
+    /// non-whitespace character decides.
+    pub fn detect(path: &str, content_type: Option<&str>, content: &str) -> Self {
+        // Ignore query strings and fragments on URLs
+        let path = path.split(['?', '#']).next().unwrap_or(path);
+        let extension = path
+            .rsplit('/')
+            .next()
+            .and_then(|name| name.rsplit_once('.'))
+            .map(|(_, extension)| extension.to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("geojson" | "json") => return SourceFormat::Json,
+            Some("ndjson" | "geojsonl" | "geojsons") => return SourceFormat::NdJson,
+            Some("csv") => return SourceFormat::Csv,
+            Some("parquet") => return SourceFormat::Parquet,
+            Some("pbf" | "mvt") => return SourceFormat::Protobuf,
+            _ => {}
+        }
+
+        let media_type = content_type
+            .and_then(|value| value.split(';').next())
+            .map(|value| value.trim().to_ascii_lowercase());
+        match media_type.as_deref() {
+            Some("application/geo+json" | "application/vnd.geo+json" | "application/json" | "text/json") => {
+                return SourceFormat::Json;
+            }
+            Some("application/geo+json-seq" | "application/x-ndjson") => return SourceFormat::NdJson,
+            Some("text/csv") => return SourceFormat::Csv,
+            Some("application/vnd.apache.parquet" | "application/x-parquet") => return SourceFormat::Parquet,
+            Some("application/x-protobuf" | "application/vnd.mapbox-vector-tile") => {
+                return SourceFormat::Protobuf;
+            }
+            _ => {}
+        }
+
+        // Only the first two records are looked at, so sniffing a huge stream stays cheap
+        let mut records = content
+            .lines()
+            .map(|line| line.trim().trim_start_matches('\u{1e}'))
+            .filter(|line| !line.is_empty());
+        if let (Some(first), Some(second)) = (records.next(), records.next())
+            && first.starts_with('{')
+            && first.ends_with('}')
+            && second.starts_with('{')
+        {
+            return SourceFormat::NdJson;
+        }
+
+        match content.trim_start().chars().next() {
+            Some('{' | '[') => SourceFormat::Json,
+            _ => SourceFormat::Unknown,
+        }
     }
 }
 
@@ -102,6 +286,14 @@ pub struct GeoArrowFile {
     pub created_at: String,
     pub schema: Option<Schema>,
     pub feature_count: Option<usize>,
+    /// Memory-map local files instead of reading them onto the heap (native only)
+    pub mmap: bool,
+    /// Columns kept by `get_features`, or `None` for all of them
+    pub columns: Option<Vec<String>>,
+    /// Latitude and longitude column names for CSV sources; `None` detects common names
+    pub csv_columns: Option<(String, String)>,
+    // Collection parsed by `open`, handed out by `get_features` until `reload`
+    collection: Option<FeatureCollection>,
 }
 
 impl Debug for GeoArrowFile {
@@ -114,6 +306,34 @@ impl Debug for GeoArrowFile {
     }
 }
 
+// Bytes read per chunk when loading local files, so progress is reported at least this often
+const LOAD_CHUNK_SIZE: usize = 64 * 1024;
+
+/// How far a load has got: bytes read so far and the total size when it is known up front
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LoadProgress {
+    pub bytes_read: u64,
+    pub total_bytes: Option<u64>,
+}
+
+// Reader that reports the running byte count after every read
+struct ProgressReader<'a, R> {
+    inner: R,
+    progress: LoadProgress,
+    on_progress: &'a mut dyn FnMut(LoadProgress),
+}
+
+impl<R: std::io::Read> std::io::Read for ProgressReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        if read > 0 {
+            self.progress.bytes_read += read as u64;
+            (self.on_progress)(self.progress);
+        }
+        Ok(read)
+    }
+}
+
 impl GeoArrowFile {
     pub fn new(path: String, size: i64, created_at: String) -> Self {
         GeoArrowFile {
@@ -122,59 +342,182 @@ impl GeoArrowFile {
             created_at,
             schema: None,
             feature_count: None,
+            mmap: false,
+            columns: None,
+            csv_columns: None,
+            collection: None,
         }
     }
+
+    /// Read CSV point coordinates from these latitude and longitude columns instead of
+    /// detecting them by name
+    pub fn with_csv_columns(mut self, lat_col: &str, lng_col: &str) -> Self {
+        self.csv_columns = Some((lat_col.to_string(), lng_col.to_string()));
+        self
+    }
+
+    /// Keep only these property columns. The geometry is always kept; listing `"geometry"` is
+    /// allowed and changes nothing. Every other property is dropped when the file is opened, so
+    /// only the selection is kept in memory; newline-delimited files drop them as each line is
+    /// read. Applies to GeoJSON, newline-delimited GeoJSON and CSV sources; Parquet files still
+    /// fail to load. Naming a column the data doesn't have is an error.
+    pub fn with_columns(mut self, columns: &[&str]) -> Self {
+        self.columns = Some(columns.iter().map(|column| column.to_string()).collect());
+        self
+    }
+
+    /// Parse local files from a read-only memory map rather than a heap copy, cutting peak
+    /// memory for multi-GB GeoJSON. Ignored for URLs and on wasm32.
+    pub fn with_mmap(mut self, mmap: bool) -> Self {
+        self.mmap = mmap;
+        self
+    }
+
     pub async fn open(&mut self) -> GeoArrowResult<()> {
-        self.load_from_url().await?;
+        self.open_with_progress(|_| {}).await
+    }
+
+    /// `open`, reporting the bytes read as the body arrives (HTTP) or after every chunk read
+    /// (local files). Local newline-delimited GeoJSON is parsed line by line as it is read.
+    pub async fn open_with_progress(&mut self, mut on_progress: impl FnMut(LoadProgress)) -> GeoArrowResult<()> {
+        self.load_from_url(&mut on_progress).await?;
+        // Only the selected columns of a parsed collection are kept around
+        if let (Some(columns), Some(collection)) = (&self.columns, &mut self.collection) {
+            project_columns(collection, columns)?;
+        }
         Ok(())
     }
 
-    async fn load_from_url(&mut self) -> GeoArrowResult<()> {
+    #[tracing::instrument(name = "load", skip(self, on_progress), fields(url = %self.path, bytes = tracing::field::Empty))]
+    async fn load_from_url(&mut self, on_progress: &mut dyn FnMut(LoadProgress)) -> GeoArrowResult<()> {
         tracing::info!("Loading geoarrow file from URL: {}", self.path);
-        let content = if self.path.starts_with("http") || self.path.starts_with("https") {
-            let resp = reqwest::get(&self.path)
+        let (content, content_type) = if self.path.starts_with("http") || self.path.starts_with("https") {
+            let mut resp = reqwest::get(&self.path)
                 .await
                 .map_err(|e| GeoArrowError::Io(format!("Failed to fetch URL: {}", e)))?;
-            resp.text()
+            let content_type = resp
+                .headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string);
+            let mut progress = LoadProgress { bytes_read: 0, total_bytes: resp.content_length() };
+            let mut body = Vec::new();
+            while let Some(chunk) = resp
+                .chunk()
                 .await
                 .map_err(|e| GeoArrowError::Io(format!("Failed to read response: {}", e)))?
+            {
+                body.extend_from_slice(&chunk);
+                progress.bytes_read = body.len() as u64;
+                on_progress(progress);
+            }
+            let text = String::from_utf8(body)
+                .map_err(|e| GeoArrowError::Io(format!("Failed to read response: {}", e)))?;
+            (text, content_type)
         } else {
-            std::fs::read_to_string(&self.path).map_err(|e| {
-                GeoArrowError::Io(format!("Failed to read file {}: {}", self.path, e))
-            })?
+            #[cfg(not(target_arch = "wasm32"))]
+            if self.mmap {
+                return self.load_mapped(on_progress);
+            }
+            let read_error = |e: std::io::Error| GeoArrowError::Io(format!("Failed to read file {}: {}", self.path, e));
+            let file = std::fs::File::open(&self.path).map_err(read_error)?;
+            let total_bytes = file.metadata().map(|metadata| metadata.len()).ok();
+            let mut reader = ProgressReader {
+                inner: file,
+                progress: LoadProgress { bytes_read: 0, total_bytes },
+                on_progress,
+            };
+            if SourceFormat::detect(&self.path, None, "") == SourceFormat::NdJson {
+                let bytes = total_bytes.unwrap_or_default();
+                self.parse_ndjson(std::io::BufReader::with_capacity(LOAD_CHUNK_SIZE, reader))?;
+                tracing::Span::current().record("bytes", bytes);
+                return Ok(());
+            }
+
+            let mut content = Vec::with_capacity(total_bytes.unwrap_or_default() as usize);
+            let mut chunk = vec![0; LOAD_CHUNK_SIZE];
+            loop {
+                let read = std::io::Read::read(&mut reader, &mut chunk).map_err(read_error)?;
+                if read == 0 {
+                    break;
+                }
+                content.extend_from_slice(&chunk[..read]);
+            }
+            let text = String::from_utf8(content)
+                .map_err(|e| GeoArrowError::Io(format!("Failed to read file {}: {}", self.path, e)))?;
+            (text, None)
         };
+        tracing::Span::current().record("bytes", content.len());
 
-        self.parse_content(&content)?;
+        self.parse_content(&content, content_type.as_deref())?;
         Ok(())
     }
 
-    fn parse_content(&mut self, content: &str) -> GeoArrowResult<()> {
-        // Determine file format based on extension or content
-        if self.path.ends_with(".geojson") || self.path.ends_with(".json") {
-            self.parse_geojson(content)?;
-        } else if self.path.ends_with(".parquet") {
-            return Err(GeoArrowError::Serialization(
-                "Parquet format not yet implemented".to_string(),
-            ));
-        } else {
-            // Try to auto-detect format
-            if content.trim_start().starts_with('{') || content.trim_start().starts_with('[') {
-                self.parse_geojson(content)?;
-            } else {
-                return Err(GeoArrowError::Serialization(
-                    "Unknown file format".to_string(),
-                ));
-            }
+    // Parse a local file straight out of a read-only memory map. The whole file is mapped at
+    // once, so progress is reported a single time. The format is sniffed from the mapped
+    // content like any other source, so extension-less newline-delimited files stream too.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load_mapped(&mut self, on_progress: &mut dyn FnMut(LoadProgress)) -> GeoArrowResult<()> {
+        let read_error = |e: std::io::Error| GeoArrowError::Io(format!("Failed to read file {}: {}", self.path, e));
+        let map = map_local_file(std::path::Path::new(&self.path)).map_err(read_error)?;
+        let bytes = map.len() as u64;
+        on_progress(LoadProgress { bytes_read: bytes, total_bytes: Some(bytes) });
+        tracing::Span::current().record("bytes", bytes);
+
+        let content = std::str::from_utf8(&map)
+            .map_err(|e| GeoArrowError::Io(format!("Failed to read file {}: {}", self.path, e)))?;
+        self.parse_content(content, None)
+    }
+
+    // Count the features of newline-delimited GeoJSON line by line; fed from a file, the whole
+    // document is never held in memory.
+    fn parse_ndjson<R: std::io::BufRead>(&mut self, reader: R) -> GeoArrowResult<()> {
+        let mut count = 0;
+        for feature in ndjson_features(reader, &self.path) {
+            feature?;
+            count += 1;
         }
+        self.feature_count = Some(count);
+        tracing::info!("Loaded {} features from newline-delimited GeoJSON", count);
+        self.schema = None;
         Ok(())
     }
 
+    fn parse_content(&mut self, content: &str, content_type: Option<&str>) -> GeoArrowResult<()> {
+        match SourceFormat::detect(&self.path, content_type, content) {
+            SourceFormat::Json => {
+                // EsriJSON shares the JSON extension and media types, so it is told apart by content
+                if let Ok(value) = serde_json::from_str::<serde_json::Value>(content)
+                    && esri::is_esri_json(&value)
+                {
+                    return self.parse_esri_json(&value);
+                }
+                self.parse_geojson(content)
+            }
+            SourceFormat::NdJson => self.parse_ndjson(content.as_bytes()),
+            SourceFormat::Csv => {
+                let columns = self.csv_columns.clone();
+                let (lat_col, lng_col) = columns.as_ref().map(|(lat, lng)| (lat.as_str(), lng.as_str())).unzip();
+                self.parse_csv(content, lat_col, lng_col).map(|_| ())
+            }
+            SourceFormat::Parquet => Err(GeoArrowError::Serialization(
+                "Parquet format not yet implemented".to_string(),
+            )),
+            SourceFormat::Protobuf => Err(GeoArrowError::Serialization(
+                "Protobuf vector tiles are not supported".to_string(),
+            )),
+            SourceFormat::Unknown => Err(GeoArrowError::Serialization(
+                "Unknown file format".to_string(),
+            )),
+        }
+    }
+
     fn parse_geojson(&mut self, content: &str) -> GeoArrowResult<()> {
         let geojson: geojson::GeoJson = content
             .parse()
             .map_err(|e| GeoArrowError::Serialization(format!("Invalid GeoJSON: {}", e)))?;
 
-        match geojson {
+        match &geojson {
             geojson::GeoJson::FeatureCollection(fc) => {
                 self.feature_count = Some(fc.features.len());
                 tracing::info!("Loaded {} features from GeoJSON", fc.features.len());
@@ -188,12 +531,65 @@ impl GeoArrowFile {
                 tracing::info!("Loaded single geometry from GeoJSON");
             }
         }
+        self.collection = Some(collection_from_geojson(geojson));
 
         // TODO: Convert to Arrow schema when geoarrow integration is ready
         self.schema = None;
         Ok(())
     }
 
+    fn parse_esri_json(&mut self, value: &serde_json::Value) -> GeoArrowResult<()> {
+        let features = esri::features_from_esri_json(value)?;
+        self.feature_count = Some(features.len());
+        tracing::info!("Loaded {} features from EsriJSON", features.len());
+        self.collection = Some(features_to_collection(&features, None));
+        self.schema = None;
+        Ok(())
+    }
+
+    /// Read CSV rows into Point features, with every other column as a property (numbers
+    /// become JSON numbers, empty cells null). Coordinate columns left as `None` are detected
+    /// by their common names (`lat`/`latitude`/`y`, `lng`/`lon`/`longitude`/`x`). Rows whose
+    /// coordinates don't parse or are out of range are skipped; their count is returned.
+    pub fn parse_csv(&mut self, content: &str, lat_col: Option<&str>, lng_col: Option<&str>) -> GeoArrowResult<usize> {
+        let (features, skipped) = csv_features(content, lat_col, lng_col)?;
+        if skipped > 0 {
+            tracing::warn!("Skipped {} CSV rows with unparseable coordinates", skipped);
+        }
+        tracing::info!("Loaded {} features from CSV", features.len());
+        self.feature_count = Some(features.len());
+        self.collection = Some(FeatureCollection { bbox: None, features, foreign_members: None });
+        self.schema = None;
+        Ok(skipped)
+    }
+
+    /// Stream the features of a local newline-delimited GeoJSON file that intersect `bounds`.
+    /// Lines are parsed one at a time and non-intersecting features are dropped before their
+    /// geometry is decoded, so memory stays proportional to the matching features.
+    pub fn stream_features_in_bounds(
+        &self,
+        bounds: &GeoBounds,
+    ) -> GeoArrowResult<impl Iterator<Item = GeoArrowResult<GeoFeature>> + use<>> {
+        if self.path.starts_with("http") {
+            return Err(GeoArrowError::Io(
+                "Streaming is only supported for local files".to_string(),
+            ));
+        }
+        let file = std::fs::File::open(&self.path).map_err(|e| {
+            GeoArrowError::Io(format!("Failed to read file {}: {}", self.path, e))
+        })?;
+        Ok(stream_ndgeojson_in_bounds(std::io::BufReader::new(file), bounds.clone()))
+    }
+
+    /// Drop the collection cached by `open` and fetch the source again
+    pub async fn reload(&mut self) -> GeoArrowResult<()> {
+        self.collection = None;
+        self.feature_count = None;
+        self.open().await
+    }
+
+    /// The features parsed by `open`, without fetching the source again. Sources `open` only
+    /// counts, such as newline-delimited GeoJSON, are read afresh.
     pub async fn get_features(&self) -> GeoArrowResult<FeatureCollection> {
         // Load and parse the content first if not already done
         if self.feature_count.is_none() {
@@ -202,6 +598,13 @@ impl GeoArrowFile {
             ));
         }
 
+        match &self.collection {
+            Some(collection) => Ok(collection.clone()),
+            None => self.fetch_collection().await,
+        }
+    }
+
+    async fn fetch_collection(&self) -> GeoArrowResult<FeatureCollection> {
         let content = if self.path.starts_with("http") || self.path.starts_with("https") {
             let resp = reqwest::get(&self.path)
                 .await
@@ -215,36 +618,43 @@ impl GeoArrowFile {
             })?
         };
 
-        let geojson: geojson::GeoJson = content
-            .parse()
-            .map_err(|e| GeoArrowError::Serialization(format!("Invalid GeoJSON: {}", e)))?;
-
-        match geojson {
-            geojson::GeoJson::FeatureCollection(fc) => Ok(fc),
-            geojson::GeoJson::Feature(f) => {
-                let mut fc = FeatureCollection {
-                    bbox: None,
-                    features: vec![f],
-                    foreign_members: None,
-                };
-                Ok(fc)
-            }
-            geojson::GeoJson::Geometry(g) => {
-                let feature = Feature {
-                    bbox: None,
-                    geometry: Some(g),
-                    id: None,
-                    properties: None,
-                    foreign_members: None,
-                };
-                let fc = FeatureCollection {
-                    bbox: None,
-                    features: vec![feature],
-                    foreign_members: None,
+        let mut collection = match SourceFormat::detect(&self.path, None, &content) {
+            SourceFormat::NdJson => {
+                let Some(columns) = &self.columns else {
+                    let features = ndjson_features(content.as_bytes(), &self.path).collect::<GeoArrowResult<_>>()?;
+                    return Ok(FeatureCollection { bbox: None, features, foreign_members: None });
                 };
-                Ok(fc)
+                // Line by line, unselected columns are dropped before the next feature is read
+                let mut found = std::collections::HashSet::new();
+                let mut features = Vec::new();
+                for feature in ndjson_features(content.as_bytes(), &self.path) {
+                    let mut feature = feature?;
+                    if let Some(properties) = &feature.properties {
+                        found.extend(columns.iter().map(String::as_str).filter(|column| properties.contains_key(*column)));
+                    }
+                    project_feature(&mut feature, columns);
+                    features.push(feature);
+                }
+                check_columns(columns, |column| found.contains(column))?;
+                return Ok(FeatureCollection { bbox: None, features, foreign_members: None });
+            }
+            SourceFormat::Csv => {
+                let (lat_col, lng_col) =
+                    self.csv_columns.as_ref().map(|(lat, lng)| (lat.as_str(), lng.as_str())).unzip();
+                let (features, _) = csv_features(&content, lat_col, lng_col)?;
+                FeatureCollection { bbox: None, features, foreign_members: None }
+            }
+            _ => {
+                let geojson: geojson::GeoJson = content
+                    .parse()
+                    .map_err(|e| GeoArrowError::Serialization(format!("Invalid GeoJSON: {}", e)))?;
+                collection_from_geojson(geojson)
             }
+        };
+        if let Some(columns) = &self.columns {
+            project_columns(&mut collection, columns)?;
         }
+        Ok(collection)
     }
 
     pub fn file_path(&self) -> &str {
@@ -252,30 +662,445 @@ impl GeoArrowFile {
     }
 }
 
+// Point features of CSV rows, and the number of rows skipped for unusable coordinates
+fn csv_features(content: &str, lat_col: Option<&str>, lng_col: Option<&str>) -> GeoArrowResult<(Vec<Feature>, usize)> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .flexible(true)
+        .from_reader(content.as_bytes());
+    let headers = reader
+        .headers()
+        .map_err(|e| GeoArrowError::Serialization(format!("Invalid CSV header: {}", e)))?
+        .clone();
+    let lat_index = csv_column(&headers, lat_col, CSV_LATITUDE_NAMES)?;
+    let lng_index = csv_column(&headers, lng_col, CSV_LONGITUDE_NAMES)?;
+
+    let mut features = Vec::new();
+    let mut skipped = 0;
+    for record in reader.records() {
+        let record = record.map_err(|e| GeoArrowError::Serialization(format!("Invalid CSV: {}", e)))?;
+        let coordinate = |index: usize| record.get(index).and_then(|field| field.parse::<f64>().ok());
+        let point = match (coordinate(lat_index), coordinate(lng_index)) {
+            (Some(lat), Some(lng)) => GeoPoint::new(lat, lng),
+            _ => {
+                skipped += 1;
+                continue;
+            }
+        };
+        if !point.is_valid() {
+            skipped += 1;
+            continue;
+        }
+
+        let properties = headers
+            .iter()
+            .zip(record.iter())
+            .enumerate()
+            .filter(|(index, _)| *index != lat_index && *index != lng_index)
+            .map(|(_, (name, field))| (name.to_string(), csv_value(field)))
+            .collect();
+        features.push(Feature {
+            bbox: None,
+            geometry: Some(Geometry::new(GeoValue::Point(vec![point.lng, point.lat]))),
+            id: None,
+            properties: Some(properties),
+            foreign_members: None,
+        });
+    }
+    Ok((features, skipped))
+}
+
+// Header names recognized as CSV coordinate columns, compared case-insensitively
+const CSV_LATITUDE_NAMES: &[&str] = &["lat", "latitude", "y"];
+const CSV_LONGITUDE_NAMES: &[&str] = &["lng", "lon", "long", "longitude", "x"];
+
+// Index of the named CSV column, or of the first header matching one of `candidates`
+fn csv_column(headers: &csv::StringRecord, name: Option<&str>, candidates: &[&str]) -> GeoArrowResult<usize> {
+    match name {
+        Some(name) => headers
+            .iter()
+            .position(|header| header == name)
+            .ok_or_else(|| GeoArrowError::Serialization(format!("Column {} not found", name))),
+        None => headers
+            .iter()
+            .position(|header| candidates.iter().any(|candidate| header.eq_ignore_ascii_case(candidate)))
+            .ok_or_else(|| {
+                GeoArrowError::Serialization(format!("No CSV column named any of {}", candidates.join(", ")))
+            }),
+    }
+}
+
+// CSV cell as a property value: numbers when they parse, null when empty, otherwise text
+fn csv_value(field: &str) -> serde_json::Value {
+    if field.is_empty() {
+        return serde_json::Value::Null;
+    }
+    if let Ok(integer) = field.parse::<i64>() {
+        return integer.into();
+    }
+    field
+        .parse::<f64>()
+        .ok()
+        .and_then(serde_json::Number::from_f64)
+        .map_or_else(|| field.into(), serde_json::Value::Number)
+}
+
+// Features of newline-delimited GeoJSON, one per line. Blank lines and RFC 8142 record
+// separators are skipped; parse errors name the 1-based line.
+fn ndjson_features<R: std::io::BufRead>(reader: R, path: &str) -> impl Iterator<Item = GeoArrowResult<Feature>> {
+    reader.lines().enumerate().filter_map(move |(number, line)| {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => return Some(Err(GeoArrowError::Io(format!("Failed to read file {}: {}", path, e)))),
+        };
+        let record = line.trim().trim_start_matches('\u{1e}');
+        if record.is_empty() {
+            return None;
+        }
+        Some(record.parse::<Feature>().map_err(|e| {
+            GeoArrowError::Serialization(format!("Invalid GeoJSON on line {}: {}", number + 1, e))
+        }))
+    })
+}
+
+// Wrap a lone feature or geometry in a collection
+fn collection_from_geojson(geojson: geojson::GeoJson) -> FeatureCollection {
+    match geojson {
+        geojson::GeoJson::FeatureCollection(fc) => fc,
+        geojson::GeoJson::Feature(f) => {
+            FeatureCollection {
+                bbox: None,
+                features: vec![f],
+                foreign_members: None,
+            }
+        }
+        geojson::GeoJson::Geometry(g) => {
+            let feature = Feature {
+                bbox: None,
+                geometry: Some(g),
+                id: None,
+                properties: None,
+                foreign_members: None,
+            };
+            FeatureCollection {
+                bbox: None,
+                features: vec![feature],
+                foreign_members: None,
+            }
+        }
+    }
+}
+
+// Name under which `GeoArrowFile::with_columns` may list the geometry column, which is always kept
+const GEOMETRY_COLUMN: &str = "geometry";
+
+// Keep only the selected columns of every feature, after checking each one exists
+fn project_columns(collection: &mut FeatureCollection, columns: &[String]) -> GeoArrowResult<()> {
+    check_columns(columns, |column| {
+        collection
+            .features
+            .iter()
+            .any(|feature| feature.properties.as_ref().is_some_and(|properties| properties.contains_key(column)))
+    })?;
+    collection.features.iter_mut().for_each(|feature| project_feature(feature, columns));
+    Ok(())
+}
+
+// Error for the first selected property column that `has_column` says no feature carries
+fn check_columns(columns: &[String], has_column: impl Fn(&str) -> bool) -> GeoArrowResult<()> {
+    match columns.iter().find(|column| column.as_str() != GEOMETRY_COLUMN && !has_column(column)) {
+        Some(missing) => Err(GeoArrowError::Serialization(format!("Column {} not found", missing))),
+        None => Ok(()),
+    }
+}
+
+fn project_feature(feature: &mut Feature, columns: &[String]) {
+    if let Some(properties) = &mut feature.properties {
+        properties.retain(|name, _| columns.contains(name));
+    }
+}
+
 // Core data models for tile-based visualization
 
 // Unique identifiers
-pub type FeatureId = String;
 pub type LayerId = String;
 pub type Timestamp = u64;
 
-// Geographic point (latitude, longitude)
+/// Feature identifier.
+///
+/// GeoJSON ids may be strings or numbers. The original type is preserved, so a numeric id `1`
+/// and a string id `"1"` are different ids and never collide in a feature index. `Display`
+/// prints either variant without decoration.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum FeatureId {
+    String(String),
+    Number(serde_json::Number),
+}
+
+impl FeatureId {
+    pub fn from_geojson_id(id: &geojson::feature::Id) -> Self {
+        match id {
+            geojson::feature::Id::String(s) => FeatureId::String(s.clone()),
+            geojson::feature::Id::Number(n) => FeatureId::Number(n.clone()),
+        }
+    }
+
+    pub fn to_geojson_id(&self) -> geojson::feature::Id {
+        match self {
+            FeatureId::String(s) => geojson::feature::Id::String(s.clone()),
+            FeatureId::Number(n) => geojson::feature::Id::Number(n.clone()),
+        }
+    }
+}
+
+impl std::fmt::Display for FeatureId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FeatureId::String(s) => write!(f, "{}", s),
+            FeatureId::Number(n) => write!(f, "{}", n),
+        }
+    }
+}
+
+impl From<&str> for FeatureId {
+    fn from(id: &str) -> Self {
+        FeatureId::String(id.to_string())
+    }
+}
+
+impl From<String> for FeatureId {
+    fn from(id: String) -> Self {
+        FeatureId::String(id)
+    }
+}
+
+impl From<u64> for FeatureId {
+    fn from(id: u64) -> Self {
+        FeatureId::Number(id.into())
+    }
+}
+
+// Geographic point (latitude, longitude), with the Z value of 3D positions when present
 #[derive(Clone, Debug, PartialEq)]
 pub struct GeoPoint {
     pub lat: f64,
     pub lng: f64,
+    pub elevation: Option<f64>,
 }
 
 impl GeoPoint {
     pub fn new(lat: f64, lng: f64) -> Self {
-        GeoPoint { lat, lng }
+        GeoPoint { lat, lng, elevation: None }
+    }
+
+    /// Point with an elevation, e.g. the Z of a GeoJSON `[lng, lat, z]` position
+    pub fn new_3d(lat: f64, lng: f64, elevation: f64) -> Self {
+        GeoPoint { lat, lng, elevation: Some(elevation) }
     }
 
+    /// In-range latitude and longitude, and a finite elevation when there is one
     pub fn is_valid(&self) -> bool {
-        self.lat >= -90.0 && self.lat <= 90.0 && self.lng >= -180.0 && self.lng <= 180.0
+        self.lat >= -90.0
+            && self.lat <= 90.0
+            && self.lng >= -180.0
+            && self.lng <= 180.0
+            && self.elevation.is_none_or(f64::is_finite)
+    }
+
+    /// Great-circle distance to `other` in meters, by the haversine formula on the mean earth radius
+    pub fn distance_to(&self, other: &GeoPoint) -> f64 {
+        let (lat1, lat2) = (self.lat.to_radians(), other.lat.to_radians());
+        let d_lat = lat2 - lat1;
+        let d_lng = (other.lng - self.lng).to_radians();
+        let h = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lng / 2.0).sin().powi(2);
+        // Rounding can leave `h` a hair outside [0, 1], where the square root or arcsine is NaN
+        2.0 * MEAN_EARTH_RADIUS * h.clamp(0.0, 1.0).sqrt().asin()
+    }
+
+    /// Initial great-circle bearing towards `other`, in degrees clockwise from north in [0, 360).
+    /// Identical points have no direction and give 0.0.
+    pub fn bearing_to(&self, other: &GeoPoint) -> f64 {
+        let (lat1, lat2) = (self.lat.to_radians(), other.lat.to_radians());
+        let d_lng = (other.lng - self.lng).to_radians();
+        let y = d_lng.sin() * lat2.cos();
+        let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * d_lng.cos();
+        if x == 0.0 && y == 0.0 {
+            return 0.0;
+        }
+        y.atan2(x).to_degrees().rem_euclid(360.0)
+    }
+
+    /// Build a point from degree/minute/second latitude and longitude. The latitude must use
+    /// `North`/`South` and the longitude `East`/`West`.
+    pub fn from_dms(lat: Dms, lng: Dms) -> GeoArrowResult<Self> {
+        if !matches!(lat.hemisphere, Hemisphere::North | Hemisphere::South)
+            || !matches!(lng.hemisphere, Hemisphere::East | Hemisphere::West)
+        {
+            return Err(GeoArrowError::Serialization(
+                "DMS latitude must be N/S and longitude E/W".to_string(),
+            ));
+        }
+
+        let point = GeoPoint::new(lat.to_decimal()?, lng.to_decimal()?);
+        if point.is_valid() {
+            Ok(point)
+        } else {
+            Err(GeoArrowError::Serialization(format!("DMS coordinates out of range: {:?}", point)))
+        }
+    }
+}
+
+// Compass hemisphere for DMS coordinates
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Hemisphere {
+    North,
+    South,
+    East,
+    West,
+}
+
+// Degree/minute/second angle, e.g. 40° 26' 46" N
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Dms {
+    pub degrees: f64,
+    pub minutes: f64,
+    pub seconds: f64,
+    pub hemisphere: Hemisphere,
+}
+
+impl Dms {
+    pub fn new(degrees: f64, minutes: f64, seconds: f64, hemisphere: Hemisphere) -> Self {
+        Dms { degrees, minutes, seconds, hemisphere }
+    }
+
+    /// Signed decimal degrees; southern and western hemispheres are negative
+    pub fn to_decimal(&self) -> GeoArrowResult<f64> {
+        let in_range = self.degrees >= 0.0
+            && (0.0..60.0).contains(&self.minutes)
+            && (0.0..60.0).contains(&self.seconds);
+        if !in_range {
+            return Err(GeoArrowError::Serialization(format!("Invalid DMS angle: {:?}", self)));
+        }
+
+        let magnitude = self.degrees + self.minutes / 60.0 + self.seconds / 3600.0;
+        Ok(match self.hemisphere {
+            Hemisphere::North | Hemisphere::East => magnitude,
+            Hemisphere::South | Hemisphere::West => -magnitude,
+        })
+    }
+
+    /// Split a signed decimal latitude into degrees, minutes and seconds
+    pub fn latitude(decimal: f64) -> Self {
+        let hemisphere = if decimal < 0.0 { Hemisphere::South } else { Hemisphere::North };
+        Self::from_magnitude(decimal.abs(), hemisphere)
+    }
+
+    /// Split a signed decimal longitude into degrees, minutes and seconds
+    pub fn longitude(decimal: f64) -> Self {
+        let hemisphere = if decimal < 0.0 { Hemisphere::West } else { Hemisphere::East };
+        Self::from_magnitude(decimal.abs(), hemisphere)
+    }
+
+    fn from_magnitude(magnitude: f64, hemisphere: Hemisphere) -> Self {
+        let degrees = magnitude.trunc();
+        let minutes = ((magnitude - degrees) * 60.0).trunc();
+        let seconds = (magnitude - degrees - minutes / 60.0) * 3600.0;
+        Dms { degrees, minutes, seconds: seconds.max(0.0), hemisphere }
+    }
+}
+
+impl std::fmt::Display for Hemisphere {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let letter = match self {
+            Hemisphere::North => "N",
+            Hemisphere::South => "S",
+            Hemisphere::East => "E",
+            Hemisphere::West => "W",
+        };
+        f.write_str(letter)
+    }
+}
+
+impl std::fmt::Display for Dms {
+    // Seconds to two decimals, carrying into minutes and degrees so 59.999" never prints as 60.00"
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let hundredths = ((self.degrees * 3600.0 + self.minutes * 60.0 + self.seconds) * 100.0).round() as u64;
+        let (degrees, rest) = (hundredths / 360_000, hundredths % 360_000);
+        let (minutes, rest) = (rest / 6_000, rest % 6_000);
+        write!(f, "{}° {:02}' {:02}.{:02}\" {}", degrees, minutes, rest / 100, rest % 100, self.hemisphere)
     }
 }
 
+// How `Viewport::format_coordinate` renders a location
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CoordFormat {
+    /// Decimal degrees with hemisphere letters, e.g. `52.52000° N, 13.40500° E`
+    #[default]
+    DecimalDegrees,
+    /// Degrees, minutes and seconds, e.g. `52° 31' 12.00" N, 13° 24' 18.00" E`
+    Dms,
+    /// UTM grid zone designator plus easting/northing in meters, without the 100 km square
+    /// letters of full MGRS, e.g. `33U 391779E 5820072N`
+    MgrsLite,
+}
+
+// MGRS latitude bands, 8° each from 80° S; X stretches to 84° N
+const MGRS_LATITUDE_BANDS: &[u8] = b"CDEFGHJKLMNPQRSTUVWXX";
+
+// UTM central meridian scale factor, and the WGS84 ellipsoid's semi-major axis (meters) and
+// flattening
+const UTM_SCALE_FACTOR: f64 = 0.9996;
+const WGS84_SEMI_MAJOR_AXIS: f64 = 6_378_137.0;
+const WGS84_FLATTENING: f64 = 1.0 / 298.257_223_563;
+
+fn format_decimal_degrees(point: &GeoPoint) -> String {
+    let lat_hemisphere = if point.lat < 0.0 { Hemisphere::South } else { Hemisphere::North };
+    let lng_hemisphere = if point.lng < 0.0 { Hemisphere::West } else { Hemisphere::East };
+    format!("{:.5}° {}, {:.5}° {}", point.lat.abs(), lat_hemisphere, point.lng.abs(), lng_hemisphere)
+}
+
+// UTM zone, band and meters via the Snyder transverse Mercator series. Polar latitudes belong
+// to UPS rather than UTM, so they fall back to decimal degrees.
+fn format_mgrs_lite(point: &GeoPoint) -> String {
+    if !(-80.0..=84.0).contains(&point.lat) {
+        return format_decimal_degrees(point);
+    }
+    let band = MGRS_LATITUDE_BANDS[(((point.lat + 80.0) / 8.0).floor() as usize).min(MGRS_LATITUDE_BANDS.len() - 1)] as char;
+    let zone = (((point.lng + 180.0) / 6.0).floor() as i32 + 1).clamp(1, 60);
+    let central_meridian = (zone as f64 - 1.0) * 6.0 - 177.0;
+
+    let e2 = WGS84_FLATTENING * (2.0 - WGS84_FLATTENING);
+    let (e4, e6) = (e2 * e2, e2 * e2 * e2);
+    let ep2 = e2 / (1.0 - e2);
+    let phi = point.lat.to_radians();
+    let (sin, cos, tan) = (phi.sin(), phi.cos(), phi.tan());
+
+    let n = WGS84_SEMI_MAJOR_AXIS / (1.0 - e2 * sin * sin).sqrt();
+    let t = tan * tan;
+    let c = ep2 * cos * cos;
+    let a = cos * (point.lng - central_meridian).to_radians();
+    let m = WGS84_SEMI_MAJOR_AXIS
+        * ((1.0 - e2 / 4.0 - 3.0 * e4 / 64.0 - 5.0 * e6 / 256.0) * phi
+            - (3.0 * e2 / 8.0 + 3.0 * e4 / 32.0 + 45.0 * e6 / 1024.0) * (2.0 * phi).sin()
+            + (15.0 * e4 / 256.0 + 45.0 * e6 / 1024.0) * (4.0 * phi).sin()
+            - (35.0 * e6 / 3072.0) * (6.0 * phi).sin());
+
+    let easting = UTM_SCALE_FACTOR
+        * n
+        * (a + (1.0 - t + c) * a.powi(3) / 6.0 + (5.0 - 18.0 * t + t * t + 72.0 * c - 58.0 * ep2) * a.powi(5) / 120.0)
+        + 500_000.0;
+    let mut northing = UTM_SCALE_FACTOR
+        * (m + n
+            * tan
+            * (a * a / 2.0
+                + (5.0 - t + 9.0 * c + 4.0 * c * c) * a.powi(4) / 24.0
+                + (61.0 - 58.0 * t + t * t + 600.0 * c - 330.0 * ep2) * a.powi(6) / 720.0));
+    if point.lat < 0.0 {
+        northing += 10_000_000.0;
+    }
+    format!("{}{} {:.0}E {:.0}N", zone, band, easting, northing)
+}
+
 // Pixel size
 #[derive(Clone, Debug, PartialEq)]
 pub struct PixelSize {
@@ -345,13 +1170,13 @@ impl Tile {
             .as_secs();
     }
 
+    /// Record a failed fetch (404, network error, ...) so the tile renders a placeholder
+    pub fn mark_failed(&mut self, reason: impl Into<String>) {
+        self.status = TileStatus::Error(reason.into());
+    }
+
     pub fn add_feature(&mut self, feature: GeoFeature) -> GeoArrowResult<()> {
-        if !feature.bounds.intersects(&GeoBounds {
-            min_x: self.bounds.min_x,
-            min_y: self.bounds.min_y,
-            max_x: self.bounds.max_x,
-            max_y: self.bounds.max_y,
-        }) {
+        if !feature.bounds.intersects(&self.bounds.to_geo_bounds()) {
             return Err(GeoArrowError::Serialization(
                 "Feature does not intersect tile bounds".to_string(),
             ));
@@ -361,57 +1186,533 @@ impl Tile {
     }
 }
 
-// Feature structure with geometry and properties
-#[derive(Clone, Debug)]
-pub struct GeoFeature {
-    pub id: FeatureId,
-    pub geometry: FeatureGeometry,
-    pub properties: DashMap<String, serde_json::Value>,
-    pub bounds: GeoBounds,
+// Options controlling how GeoJSON features are turned into GeoFeatures
+#[derive(Clone, Debug, Default)]
+pub struct LoadOptions {
+    /// Keep the raw GeoJSON geometry and decode it on first access instead of at load time
+    pub lazy: bool,
+    /// Precompute a simplified copy at this tolerance (degrees) for rendering.
+    /// Simplifying needs the decoded geometry, so it takes precedence over `lazy`.
+    pub simplify_tolerance: Option<f64>,
+    /// What to do when a feature id is already present in the scene
+    pub duplicate_ids: DuplicateIdPolicy,
+    /// Skip the per-coordinate range check when decoding. Only for pre-validated, trusted
+    /// sources: out-of-range coordinates are then accepted silently. Short coordinate arrays
+    /// are still rejected.
+    pub trust_input: bool,
+    /// Keep non-standard GeoJSON members of each feature so export writes them back
+    pub preserve_foreign_members: bool,
+    /// Skip features whose geometry has no coordinates instead of loading them
+    pub drop_empty_geometries: bool,
+    /// What to do when a geometry mixes 2D and 3D positions
+    pub dimension_policy: DimensionPolicy,
 }
 
-impl GeoFeature {
-    pub fn new(
-        id: FeatureId,
-        geometry: FeatureGeometry,
-        properties: DashMap<String, serde_json::Value>,
-    ) -> Self {
-        let bounds = geometry.calculate_bounds();
-        GeoFeature {
-            id,
-            geometry,
-            properties,
-            bounds,
-        }
-    }
+// Line and ring simplification method used by `FeatureGeometry::simplify_with`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SimplifyAlgorithm {
+    /// Ramer–Douglas–Peucker; the tolerance is a distance in degrees
+    #[default]
+    DouglasPeucker,
+    /// Visvalingam–Whyatt; the tolerance is a triangle area in square degrees. Tends to keep
+    /// polygon outlines smoother and their areas closer to the original.
+    Visvalingam,
+}
+
+// Handling of geometries whose positions disagree on having a Z value
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DimensionPolicy {
+    /// Fail the load
+    Error,
+    /// Keep the Z values that are present; the other points get `elevation: None`
+    #[default]
+    FillNull,
+    /// Discard every Z value so the geometry is uniformly 2D
+    DropZ,
+}
+
+// Resolution for features that share an id with one already loaded
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DuplicateIdPolicy {
+    /// Fail the load
+    Error,
+    /// Keep the feature loaded first and drop the newcomer
+    KeepFirst,
+    /// Replace the existing feature with the newcomer
+    #[default]
+    KeepLast,
+    /// Keep both, giving the newcomer a `-1`, `-2`, ... suffix
+    Rename,
+}
+
+impl LoadOptions {
+    pub fn with_lazy(mut self, lazy: bool) -> Self {
+        self.lazy = lazy;
+        self
+    }
+
+    pub fn with_simplify_on_load(mut self, tolerance: f64) -> Self {
+        self.simplify_tolerance = Some(tolerance);
+        self
+    }
+
+    pub fn with_duplicate_id_policy(mut self, policy: DuplicateIdPolicy) -> Self {
+        self.duplicate_ids = policy;
+        self
+    }
+
+    pub fn with_trust_input(mut self, trust_input: bool) -> Self {
+        self.trust_input = trust_input;
+        self
+    }
+
+    pub fn with_preserve_foreign_members(mut self, preserve: bool) -> Self {
+        self.preserve_foreign_members = preserve;
+        self
+    }
+
+    pub fn with_drop_empty_geometries(mut self, drop: bool) -> Self {
+        self.drop_empty_geometries = drop;
+        self
+    }
+
+    pub fn with_dimension_policy(mut self, policy: DimensionPolicy) -> Self {
+        self.dimension_policy = policy;
+        self
+    }
+
+    // Whether `feature` survives the load; features without any geometry are kept so they
+    // still report their error
+    fn keeps(&self, feature: &Feature) -> bool {
+        !self.drop_empty_geometries || feature.geometry.as_ref().is_none_or(|g| !raw_geometry_is_empty(&g.value))
+    }
+}
+
+// Feature structure with geometry and properties
+#[derive(Clone, Debug)]
+pub struct GeoFeature {
+    pub id: FeatureId,
+    geometry: OnceLock<FeatureGeometry>,
+    raw_geometry: Option<GeoValue>,
+    // Whether `raw_geometry` is range-checked when decoded; off for features loaded with
+    // `trust_input`
+    validate_raw: bool,
+    simplified: Option<FeatureGeometry>,
+    pub properties: DashMap<String, serde_json::Value>,
+    pub bounds: GeoBounds,
+    /// Non-standard top-level members of the source GeoJSON feature, kept only when loaded
+    /// with `preserve_foreign_members`
+    pub foreign_members: Option<geojson::JsonObject>,
+}
+
+impl GeoFeature {
+    pub fn new(
+        id: FeatureId,
+        geometry: FeatureGeometry,
+        properties: DashMap<String, serde_json::Value>,
+    ) -> Self {
+        let bounds = geometry.calculate_bounds();
+        GeoFeature {
+            id,
+            geometry: OnceLock::from(geometry),
+            raw_geometry: None,
+            validate_raw: true,
+            simplified: None,
+            properties,
+            bounds,
+            foreign_members: None,
+        }
+    }
+
+    /// Build a feature whose geometry stays as the raw GeoJSON value until first accessed.
+    /// Bounds are taken from the raw coordinates so culling never forces a decode.
+    pub fn new_lazy(
+        id: FeatureId,
+        raw_geometry: GeoValue,
+        properties: DashMap<String, serde_json::Value>,
+    ) -> Self {
+        let bounds = raw_geometry_bounds(&raw_geometry);
+        GeoFeature {
+            id,
+            geometry: OnceLock::new(),
+            raw_geometry: Some(raw_geometry),
+            validate_raw: true,
+            simplified: None,
+            properties,
+            bounds,
+            foreign_members: None,
+        }
+    }
 
     pub fn from_geojson_feature(feature: &Feature) -> GeoArrowResult<Self> {
+        Self::from_geojson_feature_with(feature, &LoadOptions::default())
+    }
+
+    pub fn from_geojson_feature_with(feature: &Feature, options: &LoadOptions) -> GeoArrowResult<Self> {
         let id = feature
             .id
             .as_ref()
-            .map(|id| match id {
-                geojson::feature::Id::String(s) => s.clone(),
-                geojson::feature::Id::Number(n) => n.to_string(),
-            })
-            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+            .map(FeatureId::from_geojson_id)
+            .unwrap_or_else(|| FeatureId::String(uuid::Uuid::new_v4().to_string()));
 
-        let geometry = if let Some(geom) = &feature.geometry {
-            FeatureGeometry::from_geojson_geometry(geom)?
-        } else {
+        let Some(geom) = &feature.geometry else {
             return Err(GeoArrowError::Serialization(
                 "Feature has no geometry".to_string(),
             ));
         };
 
-        if let Some(props) = &feature.properties {
-            let properties: DashMap<String, serde_json::Value> = DashMap::new();
+        let properties: DashMap<String, serde_json::Value> = feature
+            .properties
+            .iter()
+            .flatten()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+
+        if options.dimension_policy == DimensionPolicy::Error && has_mixed_dimensions(&geom.value) {
+            return Err(feature_error(
+                &id,
+                GeoArrowError::Serialization("Geometry mixes 2D and 3D coordinates".to_string()),
+            ));
         }
-        todo!()
+        let keep_z = options.dimension_policy != DimensionPolicy::DropZ;
+
+        let mut loaded = if let Some(tolerance) = options.simplify_tolerance {
+            let geometry = FeatureGeometry::decode(geom, !options.trust_input, keep_z).map_err(|e| feature_error(&id, e))?;
+            let mut feature = GeoFeature::new(id, geometry, properties);
+            feature.simplify_for_rendering(tolerance)?;
+            feature
+        } else if options.lazy {
+            let mut raw = geom.value.clone();
+            if !keep_z {
+                drop_z(&mut raw);
+            }
+            let mut feature = GeoFeature::new_lazy(id, raw, properties);
+            feature.validate_raw = !options.trust_input;
+            feature
+        } else {
+            let geometry = FeatureGeometry::decode(geom, !options.trust_input, keep_z).map_err(|e| feature_error(&id, e))?;
+            GeoFeature::new(id, geometry, properties)
+        };
+        if options.preserve_foreign_members {
+            loaded.foreign_members = feature.foreign_members.clone();
+        }
+        Ok(loaded)
+    }
+
+    /// Convert back to a GeoJSON feature, writing out any preserved foreign members. A lazily
+    /// loaded feature whose geometry was never decoded writes its source geometry unchanged.
+    pub fn to_geojson_feature(&self) -> Feature {
+        let properties: geojson::JsonObject = self
+            .properties
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect();
+        let geometry = match self.geometry.get() {
+            Some(geometry) => Some(geometry.to_geojson_geometry()),
+            None => self.raw_geometry.clone().map(Geometry::new),
+        };
+        Feature {
+            bbox: None,
+            geometry,
+            id: Some(self.id.to_geojson_id()),
+            properties: Some(properties),
+            foreign_members: self.foreign_members.clone(),
+        }
+    }
+
+    /// Store a simplified copy used for drawing; the original stays available through
+    /// `geometry()` for hit-testing and export
+    pub fn simplify_for_rendering(&mut self, tolerance: f64) -> GeoArrowResult<()> {
+        self.simplified = Some(self.geometry()?.simplify(tolerance));
+        Ok(())
+    }
+
+    /// Geometry to draw: the simplified copy when one was precomputed, otherwise the original
+    pub fn render_geometry(&self) -> GeoArrowResult<&FeatureGeometry> {
+        match &self.simplified {
+            Some(simplified) => Ok(simplified),
+            None => self.geometry(),
+        }
+    }
+
+    /// Whether `p` hits this feature's geometry (see `FeatureGeometry::contains_point`). The
+    /// bounding box rejects most misses before the geometry is decoded or walked.
+    pub fn hit_test(&self, p: &GeoPoint) -> bool {
+        if !self.bounds.buffer(HIT_BUFFER_DEG).contains(p.lng, p.lat) {
+            return false;
+        }
+        self.geometry().is_ok_and(|geometry| geometry.contains_point(p))
+    }
+
+    /// The decoded geometry, decoding the raw GeoJSON value on first call for lazy features
+    pub fn geometry(&self) -> GeoArrowResult<&FeatureGeometry> {
+        if let Some(geometry) = self.geometry.get() {
+            return Ok(geometry);
+        }
+        let raw = self.raw_geometry.as_ref().ok_or_else(|| {
+            GeoArrowError::Serialization(format!("Feature {} has no geometry", self.id))
+        })?;
+        let decoded = FeatureGeometry::decode(&Geometry::new(raw.clone()), self.validate_raw, true)
+            .map_err(|e| feature_error(&self.id, e))?;
+        Ok(self.geometry.get_or_init(|| decoded))
+    }
+
+    pub fn geometry_mut(&mut self) -> GeoArrowResult<&mut FeatureGeometry> {
+        self.geometry()?;
+        self.raw_geometry = None;
+        self.simplified = None;
+        self.geometry.get_mut().ok_or_else(|| {
+            GeoArrowError::Serialization(format!("Feature {} has no geometry", self.id))
+        })
+    }
+
+    pub fn set_geometry(&mut self, geometry: FeatureGeometry) {
+        self.bounds = geometry.calculate_bounds();
+        self.geometry = OnceLock::from(geometry);
+        self.raw_geometry = None;
+        self.simplified = None;
+    }
+
+    pub fn is_decoded(&self) -> bool {
+        self.geometry.get().is_some()
+    }
+
+    /// Recompute bounds from the decoded geometry; undecoded lazy features keep their raw bounds
+    pub fn recalculate_bounds(&mut self) {
+        if let Some(geometry) = self.geometry.get() {
+            self.bounds = geometry.calculate_bounds();
+        }
+    }
+
+    /// Split a multi-geometry into one feature per part with ids `<id>-0`, `<id>-1`, ...,
+    /// each carrying a copy of the properties. Single geometries come back unchanged.
+    pub fn explode(&self) -> GeoArrowResult<Vec<GeoFeature>> {
+        let Some(parts) = self.geometry()?.parts() else {
+            return Ok(vec![self.clone()]);
+        };
+        Ok(parts
+            .into_iter()
+            .enumerate()
+            .map(|(i, part)| {
+                let id = FeatureId::String(format!("{}-{}", self.id, i));
+                let mut feature = GeoFeature::new(id, part, self.properties.clone());
+                feature.foreign_members = self.foreign_members.clone();
+                feature
+            })
+            .collect())
+    }
+}
+
+// Export features as a collection, with optional top-level foreign members
+pub fn features_to_collection(
+    features: &[GeoFeature],
+    foreign_members: Option<geojson::JsonObject>,
+) -> FeatureCollection {
+    FeatureCollection {
+        bbox: None,
+        features: features.iter().map(GeoFeature::to_geojson_feature).collect(),
+        foreign_members,
+    }
+}
+
+/// Export features as a GeoJSON FeatureCollection, e.g. to save them after filtering or
+/// simplifying in memory
+pub fn features_to_geojson(features: &[GeoFeature]) -> FeatureCollection {
+    features_to_collection(features, None)
+}
+
+// Convert every feature of a collection, honoring the load options
+pub fn features_from_collection(
+    collection: &FeatureCollection,
+    options: &LoadOptions,
+) -> GeoArrowResult<Vec<GeoFeature>> {
+    collection
+        .features
+        .iter()
+        .filter(|feature| options.keeps(feature))
+        .map(|feature| GeoFeature::from_geojson_feature_with(feature, options))
+        .collect()
+}
+
+/// Read-only memory map of a local file, so large files are parsed without a heap copy
+#[cfg(not(target_arch = "wasm32"))]
+pub fn map_local_file(path: &std::path::Path) -> std::io::Result<memmap2::Mmap> {
+    let file = std::fs::File::open(path)?;
+    // SAFETY: the map is read-only. As with any mmap, the file must not be truncated by another
+    // process while the map is alive.
+    unsafe { memmap2::Mmap::map(&file) }
+}
+
+/// Parse a GeoJSON document (collection, feature or bare geometry) into features
+pub fn features_from_geojson_str(content: &str, options: &LoadOptions) -> GeoArrowResult<Vec<GeoFeature>> {
+    let geojson: geojson::GeoJson = content
+        .parse()
+        .map_err(|e| GeoArrowError::Serialization(format!("Invalid GeoJSON: {}", e)))?;
+
+    let feature = match geojson {
+        geojson::GeoJson::FeatureCollection(collection) => return features_from_collection(&collection, options),
+        geojson::GeoJson::Feature(feature) => feature,
+        geojson::GeoJson::Geometry(geometry) => Feature::from(geometry),
+    };
+    if !options.keeps(&feature) {
+        return Ok(Vec::new());
+    }
+    Ok(vec![GeoFeature::from_geojson_feature_with(&feature, options)?])
+}
+
+/// Incrementally parse newline-delimited GeoJSON (one Feature per line, RFC 8142 record
+/// separators allowed) and yield only the features whose bounds intersect `bounds`.
+pub fn stream_ndgeojson_in_bounds<R: std::io::BufRead>(
+    reader: R,
+    bounds: GeoBounds,
+) -> impl Iterator<Item = GeoArrowResult<GeoFeature>> {
+    let lazy = LoadOptions::default().with_lazy(true);
+    reader
+        .lines()
+        .filter_map(move |line| {
+            let parsed = line
+                .map_err(|e| GeoArrowError::Io(format!("Failed to read line: {}", e)))
+                .and_then(|line| {
+                    let record = line.trim().trim_start_matches('\u{1e}');
+                    if record.is_empty() {
+                        return Ok(None);
+                    }
+                    let geojson: geojson::GeoJson = record
+                        .parse()
+                        .map_err(|e| GeoArrowError::Serialization(format!("Invalid GeoJSON line: {}", e)))?;
+                    let feature = Feature::try_from(geojson)
+                        .map_err(|e| GeoArrowError::Serialization(format!("Expected a Feature: {}", e)))?;
+                    let feature = GeoFeature::from_geojson_feature_with(&feature, &lazy)?;
+                    Ok(feature.bounds.intersects(&bounds).then_some(feature))
+                });
+            parsed.transpose()
+        })
+}
+
+// Largest endpoint gap, in degrees, at which `is_valid` still treats a ring as closed. Absorbs
+// float noise from coordinates written with fixed decimals.
+pub const RING_CLOSURE_TOLERANCE: f64 = 1e-9;
+
+fn ring_is_closed(ring: &[GeoPoint], tolerance: f64) -> bool {
+    match (ring.first(), ring.last()) {
+        (Some(first), Some(last)) => {
+            (first.lat - last.lat).abs() <= tolerance && (first.lng - last.lng).abs() <= tolerance
+        }
+        _ => false,
+    }
+}
+
+// GeoJSON `[lng, lat, ...]` position to a GeoPoint, range-checked when `validate` is set. Short
+// arrays are always rejected instead of panicking.
+fn position_to_point(position: &[f64], kind: &str, validate: bool, keep_z: bool) -> GeoArrowResult<GeoPoint> {
+    let [lng, lat, rest @ ..] = position else {
+        return Err(GeoArrowError::Serialization(format!(
+            "Invalid {} coordinates: expected at least 2 values, found {}",
+            kind,
+            position.len()
+        )));
+    };
+    let point = match rest.first() {
+        Some(&z) if keep_z => GeoPoint::new_3d(*lat, *lng, z),
+        _ => GeoPoint::new(*lat, *lng),
+    };
+    if validate && !point.is_valid() {
+        return Err(GeoArrowError::Serialization(format!("Invalid {} coordinates", kind)));
+    }
+    Ok(point)
+}
+
+// Prefix a decoding error with the id of the feature it came from
+fn feature_error(id: &FeatureId, error: GeoArrowError) -> GeoArrowError {
+    match error {
+        GeoArrowError::Serialization(message) => {
+            GeoArrowError::Serialization(format!("Feature {}: {}", id, message))
+        }
+        other => other,
+    }
+}
+
+// Every position of a raw geometry
+fn raw_positions(value: &GeoValue) -> Box<dyn Iterator<Item = &Vec<f64>> + '_> {
+    match value {
+        GeoValue::Point(position) => Box::new(std::iter::once(position)),
+        GeoValue::MultiPoint(positions) | GeoValue::LineString(positions) => Box::new(positions.iter()),
+        GeoValue::Polygon(rings) | GeoValue::MultiLineString(rings) => Box::new(rings.iter().flatten()),
+        GeoValue::MultiPolygon(polygons) => Box::new(polygons.iter().flatten().flatten()),
+        GeoValue::GeometryCollection(geometries) => {
+            Box::new(geometries.iter().flat_map(|geometry| raw_positions(&geometry.value)))
+        }
+    }
+}
+
+fn raw_positions_mut(value: &mut GeoValue) -> Box<dyn Iterator<Item = &mut Vec<f64>> + '_> {
+    match value {
+        GeoValue::Point(position) => Box::new(std::iter::once(position)),
+        GeoValue::MultiPoint(positions) | GeoValue::LineString(positions) => Box::new(positions.iter_mut()),
+        GeoValue::Polygon(rings) | GeoValue::MultiLineString(rings) => Box::new(rings.iter_mut().flatten()),
+        GeoValue::MultiPolygon(polygons) => Box::new(polygons.iter_mut().flatten().flatten()),
+        GeoValue::GeometryCollection(geometries) => {
+            Box::new(geometries.iter_mut().flat_map(|geometry| raw_positions_mut(&mut geometry.value)))
+        }
+    }
+}
+
+// Whether some positions carry a Z value and others don't
+fn has_mixed_dimensions(value: &GeoValue) -> bool {
+    let mut positions = raw_positions(value).map(|position| position.len() > 2);
+    let Some(first) = positions.next() else {
+        return false;
+    };
+    positions.any(|has_z| has_z != first)
+}
+
+fn drop_z(value: &mut GeoValue) {
+    raw_positions_mut(value).for_each(|position| position.truncate(2));
+}
+
+fn raw_geometry_is_empty(value: &GeoValue) -> bool {
+    match value {
+        GeoValue::Point(position) => position.is_empty(),
+        GeoValue::MultiPoint(positions) | GeoValue::LineString(positions) => positions.is_empty(),
+        GeoValue::Polygon(rings) | GeoValue::MultiLineString(rings) => rings.iter().all(Vec::is_empty),
+        GeoValue::MultiPolygon(polygons) => polygons.iter().flatten().all(Vec::is_empty),
+        GeoValue::GeometryCollection(geometries) => geometries.iter().all(|g| raw_geometry_is_empty(&g.value)),
     }
 }
 
+// Bounding box straight from raw GeoJSON positions, without building GeoPoints
+fn raw_geometry_bounds(value: &GeoValue) -> GeoBounds {
+    fn visit(value: &GeoValue, bounds: &mut GeoBounds) {
+        let mut extend = |position: &Vec<f64>| {
+            if position.len() >= 2 {
+                bounds.min_x = bounds.min_x.min(position[0]);
+                bounds.min_y = bounds.min_y.min(position[1]);
+                bounds.max_x = bounds.max_x.max(position[0]);
+                bounds.max_y = bounds.max_y.max(position[1]);
+            }
+        };
+        match value {
+            GeoValue::Point(position) => extend(position),
+            GeoValue::MultiPoint(positions) | GeoValue::LineString(positions) => {
+                positions.iter().for_each(extend)
+            }
+            GeoValue::Polygon(rings) | GeoValue::MultiLineString(rings) => {
+                rings.iter().flatten().for_each(extend)
+            }
+            GeoValue::MultiPolygon(polygons) => polygons.iter().flatten().flatten().for_each(extend),
+            GeoValue::GeometryCollection(geometries) => {
+                geometries.iter().for_each(|geometry| visit(&geometry.value, bounds))
+            }
+        }
+    }
+
+    let mut bounds = GeoBounds::new(f64::INFINITY, f64::INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY);
+    visit(value, &mut bounds);
+    bounds
+}
+
 // Geometry types for features
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum FeatureGeometry {
     Point(GeoPoint),
     LineString(Vec<GeoPoint>),
@@ -423,84 +1724,41 @@ pub enum FeatureGeometry {
 
 impl FeatureGeometry {
     pub fn from_geojson_geometry(geometry: &Geometry) -> GeoArrowResult<Self> {
+        Self::decode(geometry, true, true)
+    }
+
+    /// Decode without checking that coordinates are in range, for trusted sources only
+    pub fn from_geojson_geometry_trusted(geometry: &Geometry) -> GeoArrowResult<Self> {
+        Self::decode(geometry, false, true)
+    }
+
+    fn decode(geometry: &Geometry, validate: bool, keep_z: bool) -> GeoArrowResult<Self> {
         match &geometry.value {
             GeoValue::Point(coords) => {
-                let point = GeoPoint::new(coords[1], coords[0]); // lat, lng
-                if !point.is_valid() {
-                    return Err(GeoArrowError::Serialization(
-                        "Invalid point coordinates".to_string(),
-                    ));
-                }
-                Ok(FeatureGeometry::Point(point))
+                Ok(FeatureGeometry::Point(position_to_point(coords, "point", validate, keep_z)?))
             }
             GeoValue::LineString(coords) => {
-                let points: Result<Vec<_>, _> = coords
-                    .iter()
-                    .map(|pos| {
-                        let point = GeoPoint::new(pos[1], pos[0]);
-                        if point.is_valid() {
-                            Ok(point)
-                        } else {
-                            Err(GeoArrowError::Serialization(
-                                "Invalid line coordinates".to_string(),
-                            ))
-                        }
-                    })
-                    .collect();
+                let points: Result<Vec<_>, _> =
+                    coords.iter().map(|pos| position_to_point(pos, "line", validate, keep_z)).collect();
                 Ok(FeatureGeometry::LineString(points?))
             }
             GeoValue::Polygon(rings) => {
                 let polygon_rings: Result<Vec<_>, _> = rings
                     .iter()
-                    .map(|ring| {
-                        ring.iter()
-                            .map(|pos| {
-                                let point = GeoPoint::new(pos[1], pos[0]);
-                                if point.is_valid() {
-                                    Ok(point)
-                                } else {
-                                    Err(GeoArrowError::Serialization(
-                                        "Invalid polygon coordinates".to_string(),
-                                    ))
-                                }
-                            })
-                            .collect()
-                    })
+                    .map(|ring| ring.iter().map(|pos| position_to_point(pos, "polygon", validate, keep_z)).collect())
                     .collect();
                 Ok(FeatureGeometry::Polygon(polygon_rings?))
             }
             GeoValue::MultiPoint(coords) => {
-                let points: Result<Vec<_>, _> = coords
-                    .iter()
-                    .map(|pos| {
-                        let point = GeoPoint::new(pos[1], pos[0]);
-                        if point.is_valid() {
-                            Ok(point)
-                        } else {
-                            Err(GeoArrowError::Serialization(
-                                "Invalid multipoint coordinates".to_string(),
-                            ))
-                        }
-                    })
-                    .collect();
+                let points: Result<Vec<_>, _> =
+                    coords.iter().map(|pos| position_to_point(pos, "multipoint", validate, keep_z)).collect();
                 Ok(FeatureGeometry::MultiPoint(points?))
             }
             GeoValue::MultiLineString(lines) => {
                 let line_strings: Result<Vec<_>, _> = lines
                     .iter()
                     .map(|line| {
-                        line.iter()
-                            .map(|pos| {
-                                let point = GeoPoint::new(pos[1], pos[0]);
-                                if point.is_valid() {
-                                    Ok(point)
-                                } else {
-                                    Err(GeoArrowError::Serialization(
-                                        "Invalid multilinestring coordinates".to_string(),
-                                    ))
-                                }
-                            })
-                            .collect()
+                        line.iter().map(|pos| position_to_point(pos, "multilinestring", validate, keep_z)).collect()
                     })
                     .collect();
                 Ok(FeatureGeometry::MultiLineString(line_strings?))
@@ -512,18 +1770,7 @@ impl FeatureGeometry {
                         rings
                             .iter()
                             .map(|ring| {
-                                ring.iter()
-                                    .map(|pos| {
-                                        let point = GeoPoint::new(pos[1], pos[0]);
-                                        if point.is_valid() {
-                                            Ok(point)
-                                        } else {
-                                            Err(GeoArrowError::Serialization(
-                                                "Invalid multipolygon coordinates".to_string(),
-                                            ))
-                                        }
-                                    })
-                                    .collect()
+                                ring.iter().map(|pos| position_to_point(pos, "multipolygon", validate, keep_z)).collect()
                             })
                             .collect()
                     })
@@ -536,6 +1783,31 @@ impl FeatureGeometry {
         }
     }
 
+    pub fn to_geojson_geometry(&self) -> Geometry {
+        let position = |point: &GeoPoint| match point.elevation {
+            Some(z) => vec![point.lng, point.lat, z],
+            None => vec![point.lng, point.lat],
+        };
+        let line = |points: &[GeoPoint]| points.iter().map(position).collect::<Vec<_>>();
+        let polygon = |rings: &[Vec<GeoPoint>]| rings.iter().map(|ring| line(ring)).collect::<Vec<_>>();
+        let value = match self {
+            FeatureGeometry::Point(point) => GeoValue::Point(position(point)),
+            FeatureGeometry::LineString(points) => GeoValue::LineString(line(points)),
+            FeatureGeometry::Polygon(rings) => GeoValue::Polygon(polygon(rings)),
+            FeatureGeometry::MultiPoint(points) => GeoValue::MultiPoint(line(points)),
+            FeatureGeometry::MultiLineString(lines) => {
+                GeoValue::MultiLineString(lines.iter().map(|points| line(points)).collect())
+            }
+            FeatureGeometry::MultiPolygon(polygons) => {
+                GeoValue::MultiPolygon(polygons.iter().map(|rings| polygon(rings)).collect())
+            }
+        };
+        Geometry::new(value)
+    }
+
+    /// Bounding box of every coordinate, always west to east. Known limitation: a shape that
+    /// RFC 7946 splits at the antimeridian into parts on either side gets the full -180..180
+    /// width, never a box crossing the antimeridian.
     pub fn calculate_bounds(&self) -> GeoBounds {
         let mut min_x = f64::INFINITY;
         let mut min_y = f64::INFINITY;
@@ -587,42 +1859,649 @@ impl FeatureGeometry {
             }
         }
 
-        GeoBounds::new(min_x, min_y, max_x, max_y)
+        GeoBounds::new(min_x, min_y, max_x, max_y)
+    }
+
+    /// Whether every polygon ring, or every line, ends within `tolerance` degrees of where it
+    /// starts. Points never close.
+    pub fn is_closed(&self, tolerance: f64) -> bool {
+        match self {
+            FeatureGeometry::Point(_) | FeatureGeometry::MultiPoint(_) => false,
+            FeatureGeometry::LineString(points) => ring_is_closed(points, tolerance),
+            FeatureGeometry::Polygon(rings) | FeatureGeometry::MultiLineString(rings) => {
+                !rings.is_empty() && rings.iter().all(|ring| ring_is_closed(ring, tolerance))
+            }
+            FeatureGeometry::MultiPolygon(polygons) => {
+                !polygons.is_empty() && polygons.iter().flatten().all(|ring| ring_is_closed(ring, tolerance))
+            }
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        match self {
+            FeatureGeometry::Point(point) => point.is_valid(),
+            FeatureGeometry::LineString(points) | FeatureGeometry::MultiPoint(points) => {
+                !points.is_empty() && points.iter().all(|p| p.is_valid())
+            }
+            FeatureGeometry::Polygon(rings) => {
+                !rings.is_empty()
+                    && rings.iter().all(|ring| {
+                        ring.len() >= 4
+                            && ring.iter().all(|p| p.is_valid())
+                            && ring_is_closed(ring, RING_CLOSURE_TOLERANCE)
+                    })
+            }
+            FeatureGeometry::MultiLineString(lines) => {
+                !lines.is_empty()
+                    && lines
+                        .iter()
+                        .all(|line| !line.is_empty() && line.iter().all(|p| p.is_valid()))
+            }
+            FeatureGeometry::MultiPolygon(polygons) => {
+                !polygons.is_empty()
+                    && polygons.iter().all(|rings| {
+                        !rings.is_empty()
+                            && rings.iter().all(|ring| {
+                                ring.len() >= 4
+                                    && ring.iter().all(|p| p.is_valid())
+                                    && ring_is_closed(ring, RING_CLOSURE_TOLERANCE)
+                            })
+                    })
+            }
+        }
+    }
+
+    /// Ramer–Douglas–Peucker simplification with `tolerance` in degrees.
+    /// Endpoints are kept, polygon rings stay closed and never drop below 4 points;
+    /// points and multipoints are returned unchanged.
+    pub fn simplify(&self, tolerance: f64) -> FeatureGeometry {
+        self.simplify_with(SimplifyAlgorithm::DouglasPeucker, tolerance)
+    }
+
+    /// `simplify` with a choice of algorithm; see `SimplifyAlgorithm` for what `tolerance` means
+    pub fn simplify_with(&self, algorithm: SimplifyAlgorithm, tolerance: f64) -> FeatureGeometry {
+        let simplify_line = |points: &[GeoPoint]| match algorithm {
+            SimplifyAlgorithm::DouglasPeucker => douglas_peucker(points, tolerance),
+            SimplifyAlgorithm::Visvalingam => simplify_visvalingam(points, tolerance),
+        };
+        let simplify_ring = |ring: &Vec<GeoPoint>| {
+            let simplified = simplify_line(ring);
+            if simplified.len() < 4 { ring.clone() } else { simplified }
+        };
+
+        match self {
+            FeatureGeometry::Point(_) | FeatureGeometry::MultiPoint(_) => self.clone(),
+            FeatureGeometry::LineString(points) => FeatureGeometry::LineString(simplify_line(points)),
+            FeatureGeometry::MultiLineString(lines) => FeatureGeometry::MultiLineString(
+                lines.iter().map(|line| simplify_line(line)).collect(),
+            ),
+            FeatureGeometry::Polygon(rings) => {
+                FeatureGeometry::Polygon(rings.iter().map(simplify_ring).collect())
+            }
+            FeatureGeometry::MultiPolygon(polygons) => FeatureGeometry::MultiPolygon(
+                polygons
+                    .iter()
+                    .map(|rings| rings.iter().map(simplify_ring).collect())
+                    .collect(),
+            ),
+        }
+    }
+
+    /// The single-geometry parts of a multi-geometry, or `None` for single geometries
+    pub fn parts(&self) -> Option<Vec<FeatureGeometry>> {
+        match self {
+            FeatureGeometry::Point(_) | FeatureGeometry::LineString(_) | FeatureGeometry::Polygon(_) => None,
+            FeatureGeometry::MultiPoint(points) => Some(points.iter().cloned().map(FeatureGeometry::Point).collect()),
+            FeatureGeometry::MultiLineString(lines) => {
+                Some(lines.iter().cloned().map(FeatureGeometry::LineString).collect())
+            }
+            FeatureGeometry::MultiPolygon(polygons) => {
+                Some(polygons.iter().cloned().map(FeatureGeometry::Polygon).collect())
+            }
+        }
+    }
+
+    /// True when the geometry has no coordinates at all, e.g. a LineString without points
+    pub fn is_empty(&self) -> bool {
+        match self {
+            FeatureGeometry::Point(_) => false,
+            FeatureGeometry::LineString(points) | FeatureGeometry::MultiPoint(points) => points.is_empty(),
+            FeatureGeometry::Polygon(rings) | FeatureGeometry::MultiLineString(rings) => {
+                rings.iter().all(Vec::is_empty)
+            }
+            FeatureGeometry::MultiPolygon(polygons) => polygons.iter().flatten().all(Vec::is_empty),
+        }
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        match self {
+            FeatureGeometry::Point(_) => 1,
+            FeatureGeometry::LineString(points) | FeatureGeometry::MultiPoint(points) => points.len(),
+            FeatureGeometry::Polygon(rings) | FeatureGeometry::MultiLineString(rings) => {
+                rings.iter().map(Vec::len).sum()
+            }
+            FeatureGeometry::MultiPolygon(polygons) => {
+                polygons.iter().flatten().map(Vec::len).sum()
+            }
+        }
+    }
+
+    /// Whether `p` falls on the geometry: inside a polygon and outside its holes, or within
+    /// `HIT_BUFFER_DEG` of a point, a line or a polygon ring (so ring boundaries count as hits)
+    pub fn contains_point(&self, p: &GeoPoint) -> bool {
+        let near_path = |points: &[GeoPoint]| match points {
+            [single] => segment_distance(p, single, single) <= HIT_BUFFER_DEG,
+            _ => points.windows(2).any(|pair| segment_distance(p, &pair[0], &pair[1]) <= HIT_BUFFER_DEG),
+        };
+
+        match self {
+            FeatureGeometry::Point(point) => near_path(std::slice::from_ref(point)),
+            FeatureGeometry::MultiPoint(points) => points.iter().any(|point| near_path(std::slice::from_ref(point))),
+            FeatureGeometry::LineString(points) => near_path(points),
+            FeatureGeometry::MultiLineString(lines) => lines.iter().any(|line| near_path(line)),
+            FeatureGeometry::Polygon(_) | FeatureGeometry::MultiPolygon(_) => self.polygons().any(|rings| {
+                // Even-odd across all rings, so a point inside a hole is outside the polygon
+                rings.iter().any(|ring| near_path(ring))
+                    || rings.iter().fold(false, |inside, ring| inside ^ ring_contains_point(ring, p))
+            }),
+        }
+    }
+
+    /// Geodesic area in square meters; see `area_with`
+    pub fn area(&self) -> f64 {
+        self.area_with(AreaMethod::default())
+    }
+
+    /// Great-circle length in meters along lines. Non-linear geometries have no length.
+    pub fn length(&self) -> f64 {
+        match self {
+            FeatureGeometry::LineString(points) => path_length(points),
+            FeatureGeometry::MultiLineString(lines) => lines.iter().map(|line| path_length(line)).sum(),
+            _ => 0.0,
+        }
+    }
+
+    /// Great-circle length in meters around every polygon ring, holes included. Non-areal
+    /// geometries have no perimeter.
+    pub fn perimeter(&self) -> f64 {
+        self.polygons().flatten().map(|ring| path_length(ring)).sum()
+    }
+
+    /// Area in square meters, exterior rings minus holes. Non-areal geometries have no area.
+    pub fn area_with(&self, method: AreaMethod) -> f64 {
+        let polygon_area = |rings: &Vec<Vec<GeoPoint>>| {
+            rings
+                .iter()
+                .enumerate()
+                .map(|(i, ring)| {
+                    let area = method.ring_area(ring);
+                    if i == 0 { area } else { -area }
+                })
+                .sum::<f64>()
+        };
+
+        match self {
+            FeatureGeometry::Polygon(rings) => polygon_area(rings),
+            FeatureGeometry::MultiPolygon(polygons) => polygons.iter().map(polygon_area).sum(),
+            _ => 0.0,
+        }
+    }
+
+    /// Rings whose orientation contradicts RFC 7946 (exterior counter-clockwise, holes
+    /// clockwise). The geometry is not modified; see `normalize_winding`.
+    pub fn check_winding(&self) -> Vec<WindingIssue> {
+        self.polygons()
+            .enumerate()
+            .flat_map(|(polygon, rings)| {
+                rings.iter().enumerate().filter_map(move |(ring, points)| {
+                    let area = signed_ring_area(points);
+                    let kind = match ring {
+                        0 if area < 0.0 => WindingIssueKind::ClockwiseExterior,
+                        1.. if area > 0.0 => WindingIssueKind::CounterClockwiseHole,
+                        _ => return None,
+                    };
+                    Some(WindingIssue { polygon, ring, kind })
+                })
+            })
+            .collect()
+    }
+
+    /// Reverse every ring reported by `check_winding` so the geometry follows RFC 7946
+    pub fn normalize_winding(&mut self) {
+        let issues = self.check_winding();
+        let mut polygons: Vec<&mut Vec<Vec<GeoPoint>>> = match self {
+            FeatureGeometry::Polygon(rings) => vec![rings],
+            FeatureGeometry::MultiPolygon(polygons) => polygons.iter_mut().collect(),
+            _ => return,
+        };
+        for issue in issues {
+            polygons[issue.polygon][issue.ring].reverse();
+        }
+    }
+
+    // Ring lists of each polygon part; empty for non-areal geometries
+    fn polygons(&self) -> impl Iterator<Item = &Vec<Vec<GeoPoint>>> {
+        let polygons: &[Vec<Vec<GeoPoint>>] = match self {
+            FeatureGeometry::Polygon(rings) => std::slice::from_ref(rings),
+            FeatureGeometry::MultiPolygon(polygons) => polygons,
+            _ => &[],
+        };
+        polygons.iter()
+    }
+
+    /// Minimum-area oriented bounding rectangle, as four corners in order around the box.
+    /// An optimal rectangle is flush with an edge of the convex hull (the rotating-calipers
+    /// argument), so each hull edge is tried as a side. Collinear input yields a zero-width box
+    /// along the line and a single point yields four copies of it.
+    pub fn oriented_bbox(&self) -> [GeoPoint; 4] {
+        let hull = convex_hull(self.points());
+        match hull.as_slice() {
+            [] => [GeoPoint::new(0.0, 0.0), GeoPoint::new(0.0, 0.0), GeoPoint::new(0.0, 0.0), GeoPoint::new(0.0, 0.0)],
+            [p] => [p.clone(), p.clone(), p.clone(), p.clone()],
+            [a, b] => [a.clone(), b.clone(), b.clone(), a.clone()],
+            _ => {
+                let mut best: Option<(f64, [GeoPoint; 4])> = None;
+                for (i, a) in hull.iter().enumerate() {
+                    let b = &hull[(i + 1) % hull.len()];
+                    let length = (b.lng - a.lng).hypot(b.lat - a.lat);
+                    if length == 0.0 {
+                        continue;
+                    }
+                    // Unit vectors along the edge (u) and perpendicular to it (v)
+                    let u = ((b.lng - a.lng) / length, (b.lat - a.lat) / length);
+                    let v = (-u.1, u.0);
+
+                    let (mut min_u, mut max_u, mut min_v, mut max_v) =
+                        (f64::INFINITY, f64::NEG_INFINITY, f64::INFINITY, f64::NEG_INFINITY);
+                    for p in &hull {
+                        let (du, dv) = (p.lng * u.0 + p.lat * u.1, p.lng * v.0 + p.lat * v.1);
+                        min_u = min_u.min(du);
+                        max_u = max_u.max(du);
+                        min_v = min_v.min(dv);
+                        max_v = max_v.max(dv);
+                    }
+
+                    let area = (max_u - min_u) * (max_v - min_v);
+                    if best.as_ref().is_none_or(|(best_area, _)| area < *best_area) {
+                        let corner = |s: f64, t: f64| GeoPoint::new(s * u.1 + t * v.1, s * u.0 + t * v.0);
+                        best = Some((
+                            area,
+                            [corner(min_u, min_v), corner(max_u, min_v), corner(max_u, max_v), corner(min_u, max_v)],
+                        ));
+                    }
+                }
+                best.map(|(_, corners)| corners).unwrap_or_else(|| {
+                    let p = hull[0].clone();
+                    [p.clone(), p.clone(), p.clone(), p]
+                })
+            }
+        }
+    }
+
+    /// Area-weighted centroid for polygons (holes subtracted), the mean vertex otherwise.
+    /// It can fall outside a concave polygon; `label_point` always lands inside.
+    pub fn centroid(&self) -> GeoPoint {
+        let mut weighted = (0.0, 0.0, 0.0);
+        for rings in self.polygons() {
+            for (i, ring) in rings.iter().enumerate() {
+                let (area, center) = ring_centroid(ring);
+                let area = if i == 0 { area.abs() } else { -area.abs() };
+                weighted = (weighted.0 + area, weighted.1 + area * center.lng, weighted.2 + area * center.lat);
+            }
+        }
+        if weighted.0.abs() > f64::EPSILON {
+            return GeoPoint::new(weighted.2 / weighted.0, weighted.1 / weighted.0);
+        }
+
+        let points = self.points();
+        let count = points.len().max(1) as f64;
+        let (lng, lat) = points.iter().fold((0.0, 0.0), |(lng, lat), p| (lng + p.lng, lat + p.lat));
+        GeoPoint::new(lat / count, lng / count)
+    }
+
+    /// Label anchor: the pole of inaccessibility (the interior point farthest from the boundary,
+    /// as in polylabel) of the polygon, or of the largest part of a multipolygon, found to
+    /// within `precision` degrees. Other geometries use their centroid.
+    pub fn label_point(&self, precision: f64) -> GeoPoint {
+        let largest = self.polygons().max_by(|a, b| {
+            let area = |rings: &Vec<Vec<GeoPoint>>| rings.first().map_or(0.0, |ring| signed_ring_area(ring).abs());
+            area(a).total_cmp(&area(b))
+        });
+        match largest {
+            Some(rings) if rings.first().is_some_and(|ring| ring.len() >= 3) => {
+                pole_of_inaccessibility(rings, precision.max(f64::EPSILON))
+            }
+            _ => self.centroid(),
+        }
+    }
+
+    fn points(&self) -> Vec<&GeoPoint> {
+        match self {
+            FeatureGeometry::Point(point) => vec![point],
+            FeatureGeometry::LineString(points) | FeatureGeometry::MultiPoint(points) => points.iter().collect(),
+            FeatureGeometry::Polygon(rings) | FeatureGeometry::MultiLineString(rings) => rings.iter().flatten().collect(),
+            FeatureGeometry::MultiPolygon(polygons) => polygons.iter().flatten().flatten().collect(),
+        }
+    }
+}
+
+// A ring wound against the RFC 7946 convention
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WindingIssue {
+    /// Polygon index (always 0 for a Polygon)
+    pub polygon: usize,
+    /// Ring index within the polygon; 0 is the exterior
+    pub ring: usize,
+    pub kind: WindingIssueKind,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WindingIssueKind {
+    ClockwiseExterior,
+    CounterClockwiseHole,
+}
+
+// How polygon areas are measured
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AreaMethod {
+    /// Shoelace over Web Mercator meters. Fast but inflated away from the equator, and edges
+    /// are straight in projected space.
+    Planar,
+    /// Spherical excess on a sphere of the mean earth radius, with great-circle edges
+    #[default]
+    Geodesic,
+}
+
+impl AreaMethod {
+    // Unsigned area of a single ring in square meters
+    fn ring_area(&self, ring: &[GeoPoint]) -> f64 {
+        let edges = ring.iter().zip(ring.iter().cycle().skip(1));
+        match self {
+            AreaMethod::Planar => {
+                let project = |p: &GeoPoint| {
+                    let lat = p.lat.clamp(-85.051_128_78, 85.051_128_78).to_radians();
+                    (
+                        WEB_MERCATOR_RADIUS * p.lng.to_radians(),
+                        WEB_MERCATOR_RADIUS * (std::f64::consts::FRAC_PI_4 + lat / 2.0).tan().ln(),
+                    )
+                };
+                let sum: f64 = edges
+                    .map(|(a, b)| {
+                        let ((x1, y1), (x2, y2)) = (project(a), project(b));
+                        x1 * y2 - x2 * y1
+                    })
+                    .sum();
+                sum.abs() / 2.0
+            }
+            AreaMethod::Geodesic => {
+                // Sum of the signed excess of the triangle each edge forms with the pole
+                let excess: f64 = edges
+                    .map(|(a, b)| {
+                        let delta = (b.lng - a.lng + 540.0).rem_euclid(360.0) - 180.0;
+                        let (t1, t2) = ((a.lat.to_radians() / 2.0).tan(), (b.lat.to_radians() / 2.0).tan());
+                        2.0 * ((delta.to_radians() / 2.0).tan() * (t1 + t2)).atan2(1.0 + t1 * t2)
+                    })
+                    .sum();
+                excess.abs() * MEAN_EARTH_RADIUS * MEAN_EARTH_RADIUS
+            }
+        }
+    }
+}
+
+// Sum of the great-circle distances between consecutive points
+fn path_length(points: &[GeoPoint]) -> f64 {
+    points.windows(2).map(|pair| pair[0].distance_to(&pair[1])).sum()
+}
+
+// Andrew's monotone chain; counter-clockwise hull without repeated points. Collinear input
+// collapses to its two extreme points.
+fn convex_hull(mut points: Vec<&GeoPoint>) -> Vec<GeoPoint> {
+    points.sort_by(|a, b| a.lng.total_cmp(&b.lng).then(a.lat.total_cmp(&b.lat)));
+    points.dedup_by(|a, b| a == b);
+    if points.len() < 3 {
+        return points.into_iter().cloned().collect();
+    }
+
+    let cross = |o: &GeoPoint, a: &GeoPoint, b: &GeoPoint| {
+        (a.lng - o.lng) * (b.lat - o.lat) - (a.lat - o.lat) * (b.lng - o.lng)
+    };
+    let mut hull: Vec<&GeoPoint> = Vec::with_capacity(points.len() * 2);
+    for pass in [points.clone(), points.iter().rev().copied().collect()] {
+        let start = hull.len();
+        for p in pass {
+            while hull.len() >= start + 2 && cross(hull[hull.len() - 2], hull[hull.len() - 1], p) <= 0.0 {
+                hull.pop();
+            }
+            hull.push(p);
+        }
+        hull.pop();
+    }
+    hull.into_iter().cloned().collect()
+}
+
+// Douglas–Peucker over a point sequence, distances measured in degrees
+fn douglas_peucker(points: &[GeoPoint], tolerance: f64) -> Vec<GeoPoint> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+
+    let mut stack = vec![(0, points.len() - 1)];
+    while let Some((start, end)) = stack.pop() {
+        let (farthest, distance) = (start + 1..end)
+            .map(|i| (i, segment_distance(&points[i], &points[start], &points[end])))
+            .fold((start, 0.0), |best, current| if current.1 > best.1 { current } else { best });
+
+        if distance > tolerance {
+            keep[farthest] = true;
+            stack.push((start, farthest));
+            stack.push((farthest, end));
+        }
+    }
+
+    points
+        .iter()
+        .zip(keep)
+        .filter(|(_, kept)| *kept)
+        .map(|(point, _)| point.clone())
+        .collect()
+}
+
+/// Visvalingam–Whyatt simplification: repeatedly drop the vertex forming the smallest triangle
+/// with its neighbours until every remaining triangle covers at least `area_threshold` square
+/// degrees. Endpoints are kept, so closed rings stay closed and never drop below 4 points.
+pub fn simplify_visvalingam(coords: &[GeoPoint], area_threshold: f64) -> Vec<GeoPoint> {
+    use std::cmp::Reverse;
+    use std::collections::BinaryHeap;
+
+    let closed = coords.len() >= 4
+        && coords.first().map(|p| (p.lat, p.lng)) == coords.last().map(|p| (p.lat, p.lng));
+    let min_len = if closed { 4 } else { 2 };
+    if coords.len() <= min_len {
+        return coords.to_vec();
+    }
+
+    let last = coords.len() - 1;
+    let mut prev: Vec<usize> = (0..coords.len()).map(|i| i.saturating_sub(1)).collect();
+    let mut next: Vec<usize> = (1..=coords.len()).collect();
+    let mut area = vec![f64::INFINITY; coords.len()];
+    // Areas are non-negative, so their bit patterns sort like the values themselves
+    let mut heap = BinaryHeap::new();
+    for i in 1..last {
+        area[i] = triangle_area(&coords[i - 1], &coords[i], &coords[i + 1]);
+        heap.push(Reverse((area[i].to_bits(), i)));
+    }
+
+    let mut removed = vec![false; coords.len()];
+    let mut remaining = coords.len();
+    while let Some(Reverse((bits, i))) = heap.pop() {
+        // Skip entries superseded by a later area update
+        if removed[i] || bits != area[i].to_bits() {
+            continue;
+        }
+        if area[i] >= area_threshold || remaining <= min_len {
+            break;
+        }
+        removed[i] = true;
+        remaining -= 1;
+        let (before, after) = (prev[i], next[i]);
+        next[before] = after;
+        prev[after] = before;
+        for neighbour in [before, after] {
+            if neighbour != 0 && neighbour != last {
+                // A neighbour's effective area never drops below that of the vertex just removed
+                area[neighbour] = triangle_area(&coords[prev[neighbour]], &coords[neighbour], &coords[next[neighbour]])
+                    .max(area[i]);
+                heap.push(Reverse((area[neighbour].to_bits(), neighbour)));
+            }
+        }
+    }
+
+    coords
+        .iter()
+        .zip(removed)
+        .filter(|(_, removed)| !removed)
+        .map(|(point, _)| point.clone())
+        .collect()
+}
+
+// Planar area of the triangle a-b-c in square degrees
+fn triangle_area(a: &GeoPoint, b: &GeoPoint, c: &GeoPoint) -> f64 {
+    ((b.lng - a.lng) * (c.lat - a.lat) - (c.lng - a.lng) * (b.lat - a.lat)).abs() / 2.0
+}
+
+// Distance in degrees within which a point counts as touching a point, line or ring. About 0.1 m
+// at the equator: it absorbs rounding in the queried coordinate, while screen-space pick radii
+// belong to `MapView::set_hit_tolerance`.
+const HIT_BUFFER_DEG: f64 = 1e-6;
+
+// Planar distance from `point` to the segment `a`-`b` in degree space
+fn segment_distance(point: &GeoPoint, a: &GeoPoint, b: &GeoPoint) -> f64 {
+    let (dx, dy) = (b.lng - a.lng, b.lat - a.lat);
+    let length_sq = dx * dx + dy * dy;
+    let t = if length_sq == 0.0 {
+        0.0
+    } else {
+        (((point.lng - a.lng) * dx + (point.lat - a.lat) * dy) / length_sq).clamp(0.0, 1.0)
+    };
+    let (px, py) = (a.lng + t * dx, a.lat + t * dy);
+    ((point.lng - px).powi(2) + (point.lat - py).powi(2)).sqrt()
+}
+
+// Signed shoelace area and centroid of one ring
+fn ring_centroid(ring: &[GeoPoint]) -> (f64, GeoPoint) {
+    let (mut area, mut lng, mut lat) = (0.0, 0.0, 0.0);
+    for (a, b) in ring.iter().zip(ring.iter().cycle().skip(1)) {
+        let cross = a.lng * b.lat - b.lng * a.lat;
+        area += cross;
+        lng += (a.lng + b.lng) * cross;
+        lat += (a.lat + b.lat) * cross;
+    }
+    if area == 0.0 {
+        return (0.0, GeoPoint::new(0.0, 0.0));
+    }
+    (area / 2.0, GeoPoint::new(lat / (3.0 * area), lng / (3.0 * area)))
+}
+
+// Distance from `point` to the nearest ring edge, positive inside the polygon and negative outside
+fn signed_boundary_distance(point: &GeoPoint, rings: &[Vec<GeoPoint>]) -> f64 {
+    let mut inside = false;
+    let mut distance = f64::INFINITY;
+    for ring in rings {
+        inside ^= ring_contains_point(ring, point);
+        for (a, b) in ring.iter().zip(ring.iter().cycle().skip(1)) {
+            distance = distance.min(segment_distance(point, a, b));
+        }
+    }
+    if inside { distance } else { -distance }
+}
+
+// Square search cell for the pole of inaccessibility, ordered by the best distance any point
+// inside it could reach
+struct PoleCell {
+    center: GeoPoint,
+    half_size: f64,
+    distance: f64,
+    potential: f64,
+}
+
+impl PoleCell {
+    fn new(lng: f64, lat: f64, half_size: f64, rings: &[Vec<GeoPoint>]) -> Self {
+        let center = GeoPoint::new(lat, lng);
+        let distance = signed_boundary_distance(&center, rings);
+        PoleCell { center, half_size, distance, potential: distance + half_size * std::f64::consts::SQRT_2 }
     }
+}
 
-    pub fn is_valid(&self) -> bool {
-        match self {
-            FeatureGeometry::Point(point) => point.is_valid(),
-            FeatureGeometry::LineString(points) | FeatureGeometry::MultiPoint(points) => {
-                !points.is_empty() && points.iter().all(|p| p.is_valid())
-            }
-            FeatureGeometry::Polygon(rings) => {
-                !rings.is_empty()
-                    && rings.iter().all(|ring| {
-                        ring.len() >= 4
-                            && ring.iter().all(|p| p.is_valid())
-                            && ring.first() == ring.last() // Closed ring
-                    })
-            }
-            FeatureGeometry::MultiLineString(lines) => {
-                !lines.is_empty()
-                    && lines
-                        .iter()
-                        .all(|line| !line.is_empty() && line.iter().all(|p| p.is_valid()))
-            }
-            FeatureGeometry::MultiPolygon(polygons) => {
-                !polygons.is_empty()
-                    && polygons.iter().all(|rings| {
-                        !rings.is_empty()
-                            && rings.iter().all(|ring| {
-                                ring.len() >= 4
-                                    && ring.iter().all(|p| p.is_valid())
-                                    && ring.first() == ring.last()
-                            })
-                    })
-            }
+impl PartialEq for PoleCell {
+    fn eq(&self, other: &Self) -> bool {
+        self.potential == other.potential
+    }
+}
+
+impl Eq for PoleCell {}
+
+impl PartialOrd for PoleCell {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PoleCell {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.potential.total_cmp(&other.potential)
+    }
+}
+
+// Polylabel: cover the exterior ring's bounds with square cells and keep subdividing the cells
+// that could still beat the best point found by more than `precision`
+fn pole_of_inaccessibility(rings: &[Vec<GeoPoint>], precision: f64) -> GeoPoint {
+    let outer = &rings[0];
+    let (min_lng, max_lng) = outer.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| (lo.min(p.lng), hi.max(p.lng)));
+    let (min_lat, max_lat) = outer.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| (lo.min(p.lat), hi.max(p.lat)));
+    let cell_size = (max_lng - min_lng).min(max_lat - min_lat);
+    if cell_size <= 0.0 {
+        return outer[0].clone();
+    }
+
+    let half = cell_size / 2.0;
+    let mut cells = std::collections::BinaryHeap::new();
+    let mut lng = min_lng;
+    while lng < max_lng {
+        let mut lat = min_lat;
+        while lat < max_lat {
+            cells.push(PoleCell::new(lng + half, lat + half, half, rings));
+            lat += cell_size;
+        }
+        lng += cell_size;
+    }
+
+    // Seed with the centroid, which is often already a good answer
+    let centroid = FeatureGeometry::Polygon(rings.to_vec()).centroid();
+    let mut best = PoleCell::new(centroid.lng, centroid.lat, 0.0, rings);
+    let middle = PoleCell::new((min_lng + max_lng) / 2.0, (min_lat + max_lat) / 2.0, 0.0, rings);
+    if middle.distance > best.distance {
+        best = middle;
+    }
+
+    // Cells come off the heap by potential, so once the top can't improve on the best point by
+    // more than `precision`, none can
+    while let Some(cell) = cells.pop() {
+        if cell.potential - best.distance <= precision {
+            break;
+        }
+        let quarter = cell.half_size / 2.0;
+        for (dx, dy) in [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)] {
+            cells.push(PoleCell::new(cell.center.lng + dx * quarter, cell.center.lat + dy * quarter, quarter, rings));
+        }
+        if cell.distance > best.distance {
+            best = cell;
         }
     }
+    best.center
 }
 
 // Data source enumeration
@@ -661,6 +2540,28 @@ pub struct LayerStyle {
     pub point_style: PointStyle,
     pub line_style: LineStyle,
     pub polygon_style: PolygonStyle,
+    /// Drop shadow under points and polygons
+    pub shadow: Option<ShadowStyle>,
+    /// Zoom-dependent overrides, resolved by `at_zoom`
+    pub zoom_stops: ZoomStops,
+}
+
+// Style values that follow the zoom level; unset entries keep the static value
+#[derive(Clone, Debug, Default)]
+pub struct ZoomStops {
+    pub line_width: Option<ZoomStop<f64>>,
+    pub point_radius: Option<ZoomStop<f64>>,
+    /// Multiplies every point, line and polygon opacity
+    pub opacity: Option<ZoomStop<f32>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShadowStyle {
+    pub color: String,
+    /// Blur radius in pixels
+    pub blur: f64,
+    pub offset_x: f64,
+    pub offset_y: f64,
 }
 
 #[derive(Clone, Debug)]
@@ -676,6 +2577,11 @@ pub struct LineStyle {
     pub width: f64,
     pub opacity: f32,
     pub dash_pattern: Option<Vec<f64>>,
+    /// Render as a Catmull-Rom curve through the vertices instead of straight segments
+    pub smooth: bool,
+    /// Feature property holding one width in pixels per vertex (a number array). LineStrings
+    /// that have it are filled as a tapered ribbon instead of stroked at `width`.
+    pub width_property: Option<String>,
 }
 
 #[derive(Clone, Debug)]
@@ -685,6 +2591,8 @@ pub struct PolygonStyle {
     pub stroke_width: f64,
     pub fill_opacity: f32,
     pub stroke_opacity: f32,
+    /// Round ring corners with this radius in pixels; 0.0 keeps them sharp
+    pub corner_radius: f64,
 }
 
 impl Default for LayerStyle {
@@ -700,18 +2608,44 @@ impl Default for LayerStyle {
                 width: 2.0,
                 opacity: 1.0,
                 dash_pattern: None,
+                smooth: false,
+                width_property: None,
             },
             polygon_style: PolygonStyle {
-                fill_color: "rgba(0, 255, 0, 0.3)".to_string(),
+                fill_color: "#00FF00".to_string(),
                 stroke_color: "#00FF00".to_string(),
                 stroke_width: 1.0,
                 fill_opacity: 0.3,
                 stroke_opacity: 1.0,
+                corner_radius: 0.0,
             },
+            shadow: None,
+            zoom_stops: ZoomStops::default(),
         }
     }
 }
 
+impl LayerStyle {
+    /// This style with its zoom stops evaluated at `zoom` (fractional zooms interpolate)
+    pub fn at_zoom(&self, zoom: f64) -> LayerStyle {
+        let mut style = self.clone();
+        let stops = &self.zoom_stops;
+        if let Some(width) = stops.line_width.as_ref().and_then(|stop| stop.evaluate(zoom)) {
+            style.line_style.width = width;
+        }
+        if let Some(radius) = stops.point_radius.as_ref().and_then(|stop| stop.evaluate(zoom)) {
+            style.point_style.radius = radius;
+        }
+        if let Some(opacity) = stops.opacity.as_ref().and_then(|stop| stop.evaluate(zoom)) {
+            style.point_style.opacity *= opacity;
+            style.line_style.opacity *= opacity;
+            style.polygon_style.fill_opacity *= opacity;
+            style.polygon_style.stroke_opacity *= opacity;
+        }
+        style
+    }
+}
+
 // Layer management
 #[derive(Clone, Debug)]
 pub struct Layer {
@@ -724,6 +2658,8 @@ pub struct Layer {
     pub opacity: f32,
     pub min_zoom: u8,
     pub max_zoom: u8,
+    /// Reload the data source this often; `None` keeps the layer static after its first load
+    pub refresh_interval: Option<Duration>,
 }
 
 impl Layer {
@@ -738,6 +2674,7 @@ impl Layer {
             opacity: 1.0,
             min_zoom: 0,
             max_zoom: 20,
+            refresh_interval: None,
         }
     }
 
@@ -794,6 +2731,42 @@ impl Layer {
         self.max_zoom = max_zoom.min(20);
         self
     }
+
+    pub fn with_refresh_interval(mut self, interval: Duration) -> Self {
+        self.refresh_interval = Some(interval);
+        self
+    }
+
+    /// Whether a layer last loaded at `last_loaded` is due for a reload at `now`.
+    /// Layers that were never loaded are always due.
+    pub fn needs_refresh(&self, last_loaded: Option<Duration>, now: Duration) -> bool {
+        match (last_loaded, self.refresh_interval) {
+            (None, _) => true,
+            (Some(last), Some(interval)) => now.saturating_sub(last) >= interval,
+            (Some(_), None) => false,
+        }
+    }
+}
+
+// IUGG mean earth radius and the WGS84 semi-major axis used by Web Mercator, in meters
+pub const MEAN_EARTH_RADIUS: f64 = 6_371_008.8;
+const WEB_MERCATOR_RADIUS: f64 = 6_378_137.0;
+
+// Web Mercator ground resolution at zoom 0 on the equator: 2π·6378137 m over 256 pixels
+const WEB_MERCATOR_EQUATOR_METERS_PER_PIXEL: f64 = 156_543.033_928_040_97;
+
+// Spherical Web Mercator meters of a point, with the latitude clamped to the projection's limit
+fn mercator_forward(point: &GeoPoint) -> (f64, f64) {
+    let lat = point.lat.clamp(-WEB_MERCATOR_MAX_LAT, WEB_MERCATOR_MAX_LAT).to_radians();
+    (WEB_MERCATOR_RADIUS * point.lng.to_radians(), WEB_MERCATOR_RADIUS * lat.tan().asinh())
+}
+
+// Zoom `Viewport::fit_bounds` uses for a single point, where any zoom would fit
+const FIT_POINT_ZOOM: f64 = 15.0;
+
+fn mercator_inverse(x: f64, y: f64) -> GeoPoint {
+    let lat = (y / WEB_MERCATOR_RADIUS).sinh().atan().to_degrees();
+    GeoPoint::new(lat.clamp(-WEB_MERCATOR_MAX_LAT, WEB_MERCATOR_MAX_LAT), (x / WEB_MERCATOR_RADIUS).to_degrees())
 }
 
 // Viewport for map view management
@@ -805,6 +2778,8 @@ pub struct Viewport {
     pub size: PixelSize,
     pub bounds: GeoBounds,
     pub pixel_bounds: PixelBounds,
+    /// Projection whose tiling scheme `get_required_tiles` selects from
+    pub projection: Arc<dyn Projection>,
 }
 
 impl Viewport {
@@ -814,7 +2789,7 @@ impl Viewport {
                 "Invalid center coordinates".to_string(),
             ));
         }
-        if zoom < 0.0 || zoom > 20.0 {
+        if !(0.0..=20.0).contains(&zoom) {
             return Err(GeoArrowError::Serialization(
                 "Zoom must be between 0.0 and 20.0".to_string(),
             ));
@@ -832,12 +2807,18 @@ impl Viewport {
             size: size.clone(),
             bounds: GeoBounds::new(0.0, 0.0, 0.0, 0.0),
             pixel_bounds: PixelBounds::new(0.0, 0.0, size.width as f64, size.height as f64),
+            projection: Arc::new(WebMercator),
         };
 
         viewport.recalculate_bounds();
         Ok(viewport)
     }
 
+    pub fn with_projection(mut self, projection: impl Projection + 'static) -> Self {
+        self.projection = Arc::new(projection);
+        self
+    }
+
     pub fn pan(&mut self, new_center: GeoPoint) -> GeoArrowResult<()> {
         if !new_center.is_valid() {
             return Err(GeoArrowError::Serialization(
@@ -850,7 +2831,7 @@ impl Viewport {
     }
 
     pub fn zoom_to(&mut self, new_zoom: f64) -> GeoArrowResult<()> {
-        if new_zoom < 0.0 || new_zoom > 20.0 {
+        if !(0.0..=20.0).contains(&new_zoom) {
             return Err(GeoArrowError::Serialization(
                 "Zoom must be between 0.0 and 20.0".to_string(),
             ));
@@ -878,71 +2859,1210 @@ impl Viewport {
         self.recalculate_bounds();
     }
 
-    fn recalculate_bounds(&mut self) {
-        // Calculate the geographic bounds based on center, zoom, and size
-        // This is a simplified calculation for Web Mercator projection
-        let scale = 1.0 / (1u32 << self.zoom as u32) as f64;
-        let half_width = (self.size.width as f64 / 2.0) * scale * 360.0 / 256.0;
-        let half_height = (self.size.height as f64 / 2.0) * scale * 180.0 / 256.0;
-
-        self.bounds = GeoBounds::new(
-            self.center.lng - half_width,
-            self.center.lat - half_height,
-            self.center.lng + half_width,
-            self.center.lat + half_height,
-        );
-    }
+    /// Center on `bounds` and pick the (fractional) zoom at which they fill the canvas, less
+    /// `padding` pixels on every side. The zoom is clamped to 0–20; a single point gets zoom 15.
+    /// Bounds crossing the antimeridian are framed across it.
+    pub fn fit_bounds(&mut self, bounds: &GeoBounds, padding: f64) -> GeoArrowResult<()> {
+        let finite = [bounds.min_x, bounds.min_y, bounds.max_x, bounds.max_y].iter().all(|v| v.is_finite());
+        if !finite || bounds.min_y > bounds.max_y || !(padding.is_finite() && padding >= 0.0) {
+            return Err(GeoArrowError::Serialization(format!(
+                "Cannot fit bounds {:?} with padding {}",
+                bounds, padding
+            )));
+        }
 
-    pub fn world_to_screen(&self, point: &GeoPoint) -> (f64, f64) {
-        if self.bounds.is_empty() {
-            return (0.0, 0.0);
+        let max_lng = if bounds.crosses_antimeridian() { bounds.max_x + 360.0 } else { bounds.max_x };
+        let (west, south) = mercator_forward(&GeoPoint::new(bounds.min_y, bounds.min_x));
+        let (east, north) = mercator_forward(&GeoPoint::new(bounds.max_y, max_lng));
+        let (width, height) = self.canvas_size();
+        let available = |size: f64| (size.max(MIN_CANVAS_PX) - 2.0 * padding).max(1.0);
+
+        // At zoom z a pixel covers EQUATOR_METERS_PER_PIXEL / 2^z meters, so the largest zoom
+        // that fits an extent is log2(pixels * EQUATOR_METERS_PER_PIXEL / meters)
+        let fit = |pixels: f64, meters: f64| (pixels * WEB_MERCATOR_EQUATOR_METERS_PER_PIXEL / meters).log2();
+        let zoom = match (east - west > 0.0, north - south > 0.0) {
+            (false, false) => FIT_POINT_ZOOM,
+            (true, false) => fit(available(width), east - west),
+            (false, true) => fit(available(height), north - south),
+            (true, true) => fit(available(width), east - west).min(fit(available(height), north - south)),
+        };
+
+        // Centered on the Mercator plane, so the data sits in the middle of the screen
+        let mut center = mercator_inverse((west + east) / 2.0, (south + north) / 2.0);
+        if center.lng > 180.0 {
+            center.lng -= 360.0;
         }
+        self.center = center;
+        self.zoom = zoom.clamp(0.0, 20.0);
+        self.recalculate_bounds();
+        Ok(())
+    }
 
-        let x_ratio = (point.lng - self.bounds.min_x) / (self.bounds.max_x - self.bounds.min_x);
-        let y_ratio = (point.lat - self.bounds.min_y) / (self.bounds.max_y - self.bounds.min_y);
+    // Geographic corners of the canvas, found by projecting the center to Web Mercator meters
+    // and stepping out half the canvas at the zoom's resolution. A rotated canvas is covered by
+    // its axis-aligned extent. Tiny canvases still get a usable extent.
+    fn recalculate_bounds(&mut self) {
+        let (width, height) = transforms::view_frame(self.canvas_size(), self.rotation);
+        let (center_x, center_y) = mercator_forward(&self.center);
+        let (half_width, half_height) = (width / 2.0 * self.resolution(), height / 2.0 * self.resolution());
+        let south_west = mercator_inverse(center_x - half_width, center_y - half_height);
+        let north_east = mercator_inverse(center_x + half_width, center_y + half_height);
+
+        self.bounds = GeoBounds::new(south_west.lng, south_west.lat, north_east.lng, north_east.lat);
+    }
 
-        let screen_x = x_ratio * self.size.width as f64;
-        let screen_y = self.size.height as f64 - (y_ratio * self.size.height as f64); // Flip Y axis
+    // Web Mercator meters per pixel at the equator, for 256px tiles
+    fn resolution(&self) -> f64 {
+        WEB_MERCATOR_EQUATOR_METERS_PER_PIXEL / self.zoom.exp2()
+    }
 
-        (screen_x, screen_y)
+    /// Pixel position of `point`, with the map turned `rotation` degrees about the canvas center.
+    /// Projects through `transforms::world_to_screen`, the same as `RenderContext`.
+    pub fn world_to_screen(&self, point: &GeoPoint) -> (f64, f64) {
+        transforms::world_to_screen((point.lng, point.lat), self.canvas_size(), self.rotation, &self.bounds)
     }
 
+    /// Inverse of `world_to_screen`; latitudes beyond the Mercator limit are clamped to ±85.0511°
     pub fn screen_to_world(&self, x: f64, y: f64) -> GeoPoint {
-        if self.bounds.is_empty() {
-            return self.center.clone();
-        }
+        let (lng, lat) = transforms::screen_to_world((x, y), self.canvas_size(), self.rotation, &self.bounds);
+        GeoPoint::new(lat, lng)
+    }
 
-        let x_ratio = x / self.size.width as f64;
-        let y_ratio = (self.size.height as f64 - y) / self.size.height as f64; // Flip Y axis
+    fn canvas_size(&self) -> (f64, f64) {
+        (self.size.width as f64, self.size.height as f64)
+    }
 
-        let lng = self.bounds.min_x + x_ratio * (self.bounds.max_x - self.bounds.min_x);
-        let lat = self.bounds.min_y + y_ratio * (self.bounds.max_y - self.bounds.min_y);
+    /// Location under a screen pixel as text, taking the view's rotation into account
+    pub fn format_coordinate(&self, screen_x: f64, screen_y: f64, format: CoordFormat) -> String {
+        let point = self.screen_to_world(screen_x, screen_y);
+        match format {
+            CoordFormat::DecimalDegrees => format_decimal_degrees(&point),
+            CoordFormat::Dms => format!("{}, {}", Dms::latitude(point.lat), Dms::longitude(point.lng)),
+            CoordFormat::MgrsLite => format_mgrs_lite(&point),
+        }
+    }
 
-        GeoPoint::new(lat, lng)
+    /// Ground resolution at the center latitude in meters per pixel, for 256px Web Mercator tiles
+    pub fn meters_per_pixel(&self) -> f64 {
+        WEB_MERCATOR_EQUATOR_METERS_PER_PIXEL * self.center.lat.to_radians().cos() / self.zoom.exp2()
     }
 
+    /// Map scale denominator (the N in 1:N) at the center latitude for a display of `dpi`.
+    /// Use the OGC standard pixel of 0.28 mm (`25.4 / 0.28` dpi) to match WMTS scale sets.
+    pub fn scale_denominator(&self, dpi: f64) -> f64 {
+        self.meters_per_pixel() * dpi / 0.0254
+    }
+
+    /// Tiles covering the current bounds on the active projection's grid
     pub fn get_required_tiles(&self) -> Vec<(u32, u32, u8)> {
         let z = self.zoom.floor() as u8;
         if z > 20 {
             return Vec::new();
         }
+        tiles_covering(self.projection.as_ref(), &self.bounds, z)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::projection::Equirectangular;
+
+    fn sample_collection() -> FeatureCollection {
+        r#"{
+            "type": "FeatureCollection",
+            "features": [
+                {"type": "Feature", "id": "a", "properties": {"name": "A"},
+                 "geometry": {"type": "Point", "coordinates": [10.0, 20.0]}},
+                {"type": "Feature", "id": "b", "properties": null,
+                 "geometry": {"type": "LineString", "coordinates": [[0.0, 0.0], [5.0, -5.0]]}}
+            ]
+        }"#
+        .parse()
+        .unwrap()
+    }
+
+    // Serve one canned HTTP response on a local port and return the URL to fetch
+    fn serve_once(content_type: &'static str, body: &'static str) -> String {
+        use std::io::{Read, Write};
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0u8; 1024];
+            let _ = stream.read(&mut request);
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                content_type,
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).unwrap();
+        });
+        format!("http://{}/api/features", address)
+    }
+
+    #[tokio::test]
+    async fn test_content_type_selects_parser_without_extension() {
+        let body = r#"{"type":"FeatureCollection","features":[{"type":"Feature","geometry":{"type":"Point","coordinates":[1,2]},"properties":{}}]}"#;
+        let mut file = GeoArrowFile::new(serve_once("application/geo+json; charset=utf-8", body), 0, String::new());
+        file.open().await.unwrap();
+        assert_eq!(file.feature_count, Some(1));
+
+        // Bodies that sniff as the other format are still parsed as the header says, so each
+        // of these fails where sniffing alone would have loaded it
+        let ndjson = "{\"type\":\"Feature\",\"geometry\":null,\"properties\":{}}\n{\"type\":\"Feature\",\"geometry\":null,\"properties\":{}}\n";
+        assert_eq!(SourceFormat::detect("http://host/api/features", None, ndjson), SourceFormat::NdJson);
+        let mut file = GeoArrowFile::new(serve_once("application/geo+json", ndjson), 0, String::new());
+        assert!(file.open().await.unwrap_err().to_string().contains("Invalid GeoJSON"));
+
+        assert_eq!(SourceFormat::detect("http://host/api/features", None, body), SourceFormat::Json);
+        let mut file = GeoArrowFile::new(serve_once("application/geo+json-seq", body), 0, String::new());
+        assert!(file.open().await.unwrap_err().to_string().contains("Invalid GeoJSON on line 1"));
+
+        assert_eq!(
+            SourceFormat::detect("http://host/tiles/1/2/3", Some("application/x-protobuf"), ""),
+            SourceFormat::Protobuf
+        );
+        assert_eq!(SourceFormat::detect("http://host/data.parquet?token=1", None, ""), SourceFormat::Parquet);
+    }
+
+    #[tokio::test]
+    async fn test_esri_json_file_loads_features() {
+        let esri = r#"{
+            "geometryType": "esriGeometryPoint",
+            "features": [
+                {"attributes": {"OBJECTID": 1, "name": "Berlin"}, "geometry": {"x": 13.4, "y": 52.5}},
+                {"attributes": {"OBJECTID": 2, "name": "Paris"}, "geometry": {"x": 2.35, "y": 48.85}}
+            ]
+        }"#;
+        let path = std::env::temp_dir().join(format!("geoarrow-esri-{}.json", std::process::id()));
+        std::fs::write(&path, esri).unwrap();
+
+        let mut file = GeoArrowFile::new(path.to_string_lossy().into_owned(), 0, String::new());
+        file.open().await.unwrap();
+        let collection = file.get_features().await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(file.feature_count, Some(2));
+        assert_eq!(collection.features.len(), 2);
+        let berlin = &collection.features[0];
+        assert_eq!(berlin.property("name").and_then(|name| name.as_str()), Some("Berlin"));
+        assert_eq!(
+            berlin.geometry.as_ref().map(|geometry| geometry.value.clone()),
+            Some(geojson::Value::Point(vec![13.4, 52.5]))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_local_load_reports_progress_in_chunks() {
+        let line = r#"{"type":"Feature","geometry":{"type":"Point","coordinates":[13.4,52.5]},"properties":{"name":"Berlin"}}"#;
+        let directory = std::env::temp_dir().join(format!("geoarrow-progress-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let nd_path = directory.join("features.ndjson");
+        std::fs::write(&nd_path, vec![line; 5_000].join("\n")).unwrap();
+        let collection_path = directory.join("features.geojson");
+        let collection = format!(r#"{{"type":"FeatureCollection","features":[{}]}}"#, vec![line; 5_000].join(","));
+        std::fs::write(&collection_path, collection).unwrap();
+
+        for path in [&nd_path, &collection_path] {
+            let size = std::fs::metadata(path).unwrap().len();
+            let mut file = GeoArrowFile::new(path.to_string_lossy().into_owned(), 0, String::new());
+            let mut reports = Vec::new();
+            file.open_with_progress(|progress| reports.push(progress)).await.unwrap();
+
+            assert_eq!(file.feature_count, Some(5_000));
+            assert!(reports.len() as u64 >= size / LOAD_CHUNK_SIZE as u64);
+            assert!(reports.windows(2).all(|pair| pair[0].bytes_read < pair[1].bytes_read));
+            assert_eq!(reports.last(), Some(&LoadProgress { bytes_read: size, total_bytes: Some(size) }));
+        }
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mmap_load_matches_heap_load() {
+        let line = r#"{"type":"Feature","geometry":{"type":"LineString","coordinates":[[0,0],[1,1]]},"properties":{"id":7}}"#;
+        let directory = std::env::temp_dir().join(format!("geoarrow-mmap-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let nd_path = directory.join("features.ndjson");
+        std::fs::write(&nd_path, vec![line; 300].join("\n")).unwrap();
+        let collection_path = directory.join("features.geojson");
+        std::fs::write(&collection_path, format!(r#"{{"type":"FeatureCollection","features":[{}]}}"#, vec![line; 300].join(","))).unwrap();
+        // No extension: only the mapped content says it is newline-delimited
+        let sniffed_path = directory.join("export");
+        std::fs::write(&sniffed_path, vec![line; 300].join("\n")).unwrap();
+
+        for path in [&nd_path, &collection_path, &sniffed_path] {
+            let path = path.to_string_lossy().into_owned();
+            let mut heap = GeoArrowFile::new(path.clone(), 0, String::new());
+            heap.open().await.unwrap();
+            let mut mapped = GeoArrowFile::new(path, 0, String::new()).with_mmap(true);
+            let mut reports = Vec::new();
+            mapped.open_with_progress(|progress| reports.push(progress)).await.unwrap();
+
+            assert_eq!(mapped.feature_count, Some(300));
+            assert_eq!(mapped.feature_count, heap.feature_count);
+            assert_eq!(mapped.schema, heap.schema);
+            assert_eq!(reports.len(), 1);
+            let features = mapped.get_features().await.unwrap();
+            assert_eq!(features.features.len(), 300);
+            assert_eq!(features, heap.get_features().await.unwrap());
+        }
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_sniffed_ndjson_stream_loads_line_by_line() {
+        let line = |lng: u32| format!(r#"{{"type":"Feature","geometry":{{"type":"Point","coordinates":[{},1]}},"properties":{{}}}}"#, lng);
+        let content = format!("{}\n\n{}\n{}\n", line(1), line(2), line(3));
+        assert_eq!(SourceFormat::detect("export", None, &content), SourceFormat::NdJson);
+        assert_eq!(SourceFormat::detect("export", None, &line(1)), SourceFormat::Json);
+        assert_eq!(SourceFormat::detect("export", None, "{\n  \"type\": \"Feature\"\n}"), SourceFormat::Json);
+
+        let path = std::env::temp_dir().join(format!("geoarrow-sniff-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&path, &content).unwrap();
+        let mut file = GeoArrowFile::new(path.to_string_lossy().into_owned(), 0, String::new());
+        file.open().await.unwrap();
+        assert_eq!(file.feature_count, Some(3));
+        assert_eq!(file.get_features().await.unwrap().features.len(), 3);
+
+        std::fs::write(&path, format!("{}\n{}\n{{\"type\":\n", line(1), line(2))).unwrap();
+        let error = file.reload().await.unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert!(error.to_string().contains("line 3"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn test_csv_rows_load_as_point_features() {
+        let content = "name,Latitude,Lon,population,note\nBerlin,52.52,13.40,3645000,\nBad,north,13.4,1,x\nPole,95,0,0,\nOslo, 59.91 , 10.75,709000,fjord\n";
+        assert_eq!(SourceFormat::detect("cities.csv", None, content), SourceFormat::Csv);
+        assert_eq!(SourceFormat::detect("export", Some("text/csv; charset=utf-8"), content), SourceFormat::Csv);
+
+        let mut file = GeoArrowFile::new("cities.csv".to_string(), 0, String::new());
+        assert_eq!(file.parse_csv(content, None, None).unwrap(), 2);
+        assert_eq!(file.feature_count, Some(2));
+        let features = file.get_features().await.unwrap().features;
+        let geometry = features[1].geometry.as_ref().unwrap();
+        assert_eq!(geometry.value, GeoValue::Point(vec![10.75, 59.91]));
+        let properties = features[0].properties.as_ref().unwrap();
+        assert_eq!(properties.len(), 3);
+        assert_eq!(properties["name"], "Berlin");
+        assert_eq!(properties["population"], 3645000);
+        assert!(properties["note"].is_null());
+
+        let error = file.parse_csv(content, Some("lat"), Some("Lon")).unwrap_err();
+        assert!(error.to_string().contains("Column lat not found"), "{}", error);
+
+        // Configured columns are used when the file is opened
+        let path = std::env::temp_dir().join(format!("geoarrow-{}.csv", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "id,north,east\n1,48.85,2.35\n").unwrap();
+        let mut file = GeoArrowFile::new(path.to_string_lossy().into_owned(), 0, String::new()).with_csv_columns("north", "east");
+        file.open().await.unwrap();
+        file.reload().await.unwrap();
+        let features = file.get_features().await.unwrap().features;
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(features[0].geometry.as_ref().unwrap().value, GeoValue::Point(vec![2.35, 48.85]));
+    }
+
+    #[test]
+    fn test_douglas_peucker_simplification() {
+        // Straight line with a 0.01° wobble, then a 1° detour at the end
+        let mut line: Vec<GeoPoint> = (0..=10).map(|i| GeoPoint::new((i % 2) as f64 * 0.01, i as f64)).collect();
+        line.push(GeoPoint::new(1.0, 10.0));
+        let FeatureGeometry::LineString(simplified) = FeatureGeometry::LineString(line.clone()).simplify(0.1) else {
+            panic!("expected a linestring");
+        };
+        assert_eq!(simplified, vec![line[0].clone(), line[10].clone(), line[11].clone()]);
+
+        // A small square would collapse entirely, so the ring is kept as it was
+        let square = vec![
+            GeoPoint::new(0.0, 0.0),
+            GeoPoint::new(0.0, 0.1),
+            GeoPoint::new(0.1, 0.1),
+            GeoPoint::new(0.1, 0.0),
+            GeoPoint::new(0.0, 0.0),
+        ];
+        let polygon = FeatureGeometry::MultiPolygon(vec![vec![square.clone()]]);
+        assert_eq!(polygon.simplify(1.0), polygon);
+        assert!(polygon.simplify(1.0).is_valid());
+
+        let points = FeatureGeometry::MultiPoint(square);
+        assert_eq!(points.simplify(1.0), points);
+    }
+
+    #[test]
+    fn test_visvalingam_differs_from_douglas_peucker() {
+        // Gear-like ring: 24 vertices alternating between radius 10 and 9.5, plus one deep notch
+        let mut ring: Vec<GeoPoint> = (0..24)
+            .map(|i| {
+                let angle = (i as f64 * 15.0).to_radians();
+                let radius = if i == 6 { 4.0 } else if i % 2 == 0 { 10.0 } else { 9.5 };
+                GeoPoint::new(radius * angle.sin(), radius * angle.cos())
+            })
+            .collect();
+        ring.push(ring[0].clone());
+        let polygon = FeatureGeometry::Polygon(vec![ring.clone()]);
+
+        let dp = polygon.simplify_with(SimplifyAlgorithm::DouglasPeucker, 1.0);
+        let vw = polygon.simplify_with(SimplifyAlgorithm::Visvalingam, 2.0);
+        assert_ne!(dp, vw);
+        for simplified in [&dp, &vw] {
+            let FeatureGeometry::Polygon(rings) = simplified else { panic!("expected a polygon") };
+            assert!(rings[0].len() >= 4 && rings[0].len() < ring.len());
+            assert_eq!(rings[0].first(), rings[0].last());
+            assert!(simplified.is_valid());
+            // The notch is large enough for both to keep
+            assert!(rings[0].contains(&ring[6]));
+        }
+        assert_eq!(polygon.simplify(1.0), dp);
+        assert_eq!(simplify_visvalingam(&ring, 0.0), ring);
+    }
+
+    #[tokio::test]
+    async fn test_get_features_returns_cached_collection_until_reload() {
+        let path = std::env::temp_dir().join(format!("geoarrow-cache-{}.geojson", std::process::id()));
+        let point = r#"{"type":"Feature","geometry":{"type":"Point","coordinates":[1,2]},"properties":{}}"#;
+        let collection = |count: usize| format!(r#"{{"type":"FeatureCollection","features":[{}]}}"#, vec![point; count].join(","));
+        std::fs::write(&path, collection(2)).unwrap();
+
+        let mut file = GeoArrowFile::new(path.to_string_lossy().into_owned(), 0, String::new());
+        assert!(file.get_features().await.is_err());
+        file.open().await.unwrap();
+
+        // Changing the source behind the file's back proves nothing is read again
+        std::fs::write(&path, collection(5)).unwrap();
+        assert_eq!(file.get_features().await.unwrap().features.len(), 2);
+
+        file.reload().await.unwrap();
+        assert_eq!(file.feature_count, Some(5));
+        assert_eq!(file.get_features().await.unwrap().features.len(), 5);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_column_subset_omits_unselected_properties() {
+        let path = std::env::temp_dir().join(format!("geoarrow-columns-{}.geojson", std::process::id()));
+        std::fs::write(
+            &path,
+            r#"{"type":"FeatureCollection","features":[
+                {"type":"Feature","geometry":{"type":"Point","coordinates":[1,2]},"properties":{"name":"a","population":10,"notes":"x"}},
+                {"type":"Feature","geometry":{"type":"Point","coordinates":[3,4]},"properties":{"name":"b","population":20}}
+            ]}"#,
+        )
+        .unwrap();
+        let path = path.to_string_lossy().into_owned();
+
+        let mut file = GeoArrowFile::new(path.clone(), 0, String::new()).with_columns(&["geometry", "name"]);
+        file.open().await.unwrap();
+        let collection = file.get_features().await.unwrap();
+        for feature in &collection.features {
+            let properties = feature.properties.as_ref().unwrap();
+            assert_eq!(properties.keys().collect::<Vec<_>>(), vec!["name"]);
+            assert!(feature.geometry.is_some());
+        }
+
+        // The stored collection is already projected
+        assert!(file.collection.as_ref().unwrap().features.iter()
+            .all(|feature| feature.properties.as_ref().unwrap().len() == 1));
+
+        let mut file = GeoArrowFile::new(path.clone(), 0, String::new()).with_columns(&["name", "height"]);
+        let error = file.open().await.unwrap_err();
+        assert!(matches!(&error, GeoArrowError::Serialization(message) if message.contains("height")));
+        std::fs::remove_file(&path).unwrap();
+
+        // Newline-delimited files are projected line by line as they are read
+        let nd_path = std::env::temp_dir().join(format!("geoarrow-columns-{}.ndjson", std::process::id()));
+        std::fs::write(
+            &nd_path,
+            concat!(
+                r#"{"type":"Feature","geometry":{"type":"Point","coordinates":[1,2]},"properties":{"name":"a","population":10}}"#, "\n",
+                r#"{"type":"Feature","geometry":{"type":"Point","coordinates":[3,4]},"properties":{"population":20}}"#,
+            ),
+        )
+        .unwrap();
+        let nd_path = nd_path.to_string_lossy().into_owned();
+        let mut file = GeoArrowFile::new(nd_path.clone(), 0, String::new()).with_columns(&["name"]);
+        file.open().await.unwrap();
+        let collection = file.get_features().await.unwrap();
+        // Geometry is kept even when not listed
+        assert!(collection.features.iter().all(|feature| feature.geometry.is_some()));
+        assert_eq!(collection.features[0].properties.as_ref().unwrap().keys().collect::<Vec<_>>(), vec!["name"]);
+        assert!(collection.features[1].properties.as_ref().unwrap().is_empty());
+        let mut file = GeoArrowFile::new(nd_path.clone(), 0, String::new()).with_columns(&["height"]);
+        file.open().await.unwrap();
+        assert!(file.get_features().await.unwrap_err().to_string().contains("height"));
+        std::fs::remove_file(&nd_path).unwrap();
+    }
+
+    #[test]
+    fn test_tile_bounds_are_geographic() {
+        use crate::engine::projection::WEB_MERCATOR_MAX_LAT;
+
+        let world = TileBounds::from_tile_coords(0, 0, 0);
+        assert_eq!((world.min_x, world.max_x), (-180.0, 180.0));
+        assert!((world.max_y - WEB_MERCATOR_MAX_LAT).abs() < 1e-9);
+        assert!((world.min_y + WEB_MERCATOR_MAX_LAT).abs() < 1e-9);
+
+        // Tile 1/1/0 is the north-east quadrant
+        let north_east = TileBounds::from_tile_coords(1, 0, 1);
+        assert_eq!((north_east.min_x, north_east.min_y, north_east.max_x), (0.0, 0.0, 180.0));
+
+        // Berlin lies in OSM tile 10/550/335
+        let mut tile = Tile::new(550, 335, 10);
+        let berlin = GeoFeature::new("berlin".into(), FeatureGeometry::Point(GeoPoint::new(52.52, 13.405)), DashMap::new());
+        let paris = GeoFeature::new("paris".into(), FeatureGeometry::Point(GeoPoint::new(48.8566, 2.3522)), DashMap::new());
+        assert!(tile.add_feature(paris).is_err());
+        tile.add_feature(berlin).unwrap();
+    }
+
+    #[test]
+    fn test_bounds_union_and_center() {
+        let a = GeoBounds::new(0.0, 0.0, 10.0, 10.0);
+        let overlapping = GeoBounds::new(5.0, -5.0, 15.0, 5.0);
+        let disjoint = GeoBounds::new(-30.0, 20.0, -20.0, 40.0);
+        let empty = GeoBounds::new(0.0, 0.0, 0.0, 0.0);
+
+        assert_eq!(a.union(&overlapping), GeoBounds::new(0.0, -5.0, 15.0, 10.0));
+        assert_eq!(a.union(&disjoint), GeoBounds::new(-30.0, 0.0, 10.0, 40.0));
+        assert_eq!(a.union(&disjoint), disjoint.union(&a));
+        assert_eq!(a.union(&empty), a);
+        assert_eq!(empty.union(&a), a);
+        assert!(empty.union(&GeoBounds::new(50.0, 60.0, 60.0, 50.0)).is_empty());
+
+        assert_eq!(a.center(), GeoPoint::new(5.0, 5.0));
+        assert_eq!(disjoint.center(), GeoPoint::new(30.0, -25.0));
+    }
+
+    #[test]
+    fn test_bounds_across_antimeridian() {
+        let fiji = GeoBounds::new(177.0, -19.2, -178.0, -16.0);
+        assert!(fiji.crosses_antimeridian() && !fiji.is_empty());
+        assert!(fiji.contains(179.5, -17.0) && fiji.contains(-179.5, -17.0));
+        assert!(!fiji.contains(0.0, -17.0));
+        assert_eq!(fiji.center(), GeoPoint::new(-17.6, 179.5));
+
+        assert!(fiji.intersects(&GeoBounds::new(-179.0, -18.0, -170.0, -10.0)));
+        assert!(GeoBounds::new(170.0, -18.0, 178.0, -10.0).intersects(&fiji));
+        assert!(!fiji.intersects(&GeoBounds::new(-170.0, -18.0, 170.0, -10.0)));
+        assert!(fiji.intersects(&GeoBounds::new(179.0, -18.0, -179.0, -17.0)));
+
+        // Unions take the short way around, across the seam
+        assert_eq!(fiji.union(&GeoBounds::new(170.0, -18.0, 176.0, -10.0)), GeoBounds::new(170.0, -19.2, -178.0, -10.0));
+        assert_eq!(fiji.union(&GeoBounds::new(-175.0, -20.0, -170.0, -18.0)), GeoBounds::new(177.0, -20.0, -170.0, -16.0));
+        assert_eq!(fiji.union(&GeoBounds::new(179.0, -18.0, 179.5, -17.0)), fiji);
+        assert_eq!(fiji.union(&GeoBounds::new(-10.0, 0.0, 10.0, 1.0)), GeoBounds::new(-10.0, -19.2, -178.0, 1.0));
+        assert_eq!(fiji.buffer(1.0), GeoBounds::new(176.0, -20.2, -177.0, -15.0));
+        assert_eq!(fiji.buffer(180.0), GeoBounds::new(-180.0, -90.0, 180.0, 90.0));
+
+        // Fiji as RFC 7946 splits it: the geometry's bounds span the world rather than the seam
+        let square = |west: f64, east: f64| vec![vec![
+            GeoPoint::new(-19.2, west),
+            GeoPoint::new(-19.2, east),
+            GeoPoint::new(-16.0, east),
+            GeoPoint::new(-16.0, west),
+            GeoPoint::new(-19.2, west),
+        ]];
+        let geometry = FeatureGeometry::MultiPolygon(vec![square(177.0, 180.0), square(-180.0, -178.0)]);
+        let bounds = geometry.calculate_bounds();
+        assert_eq!(bounds, GeoBounds::new(-180.0, -19.2, 180.0, -16.0));
+        assert!(!bounds.crosses_antimeridian() && bounds.contains(179.5, -17.0) && bounds.contains(-179.5, -17.0));
+
+        // A viewport centered on the seam needs tiles from both edges of the grid
+        let viewport = Viewport::new(GeoPoint::new(-17.6, 179.9), 5.0, PixelSize { width: 512, height: 512 }).unwrap();
+        let columns: Vec<u32> = viewport.get_required_tiles().iter().map(|tile| tile.0).collect();
+        assert!(columns.contains(&0) && columns.contains(&31));
+        assert!(columns.iter().all(|&x| x < 32));
+    }
+
+    #[test]
+    fn test_ring_closed_within_tolerance() {
+        let ring = vec![
+            GeoPoint::new(0.0, 0.0),
+            GeoPoint::new(0.0, 1.0),
+            GeoPoint::new(1.0, 1.0),
+            GeoPoint::new(1e-12, 0.0),
+        ];
+        let polygon = FeatureGeometry::Polygon(vec![ring]);
+
+        assert!(polygon.is_closed(RING_CLOSURE_TOLERANCE));
+        assert!(!polygon.is_closed(0.0));
+        assert!(polygon.is_valid());
+
+        let open = FeatureGeometry::LineString(vec![GeoPoint::new(0.0, 0.0), GeoPoint::new(1e-6, 0.0)]);
+        assert!(!open.is_closed(RING_CLOSURE_TOLERANCE));
+    }
+
+    #[test]
+    fn test_check_winding() {
+        let clockwise = vec![
+            GeoPoint::new(0.0, 0.0),
+            GeoPoint::new(1.0, 0.0),
+            GeoPoint::new(1.0, 1.0),
+            GeoPoint::new(0.0, 1.0),
+            GeoPoint::new(0.0, 0.0),
+        ];
+        let mut polygon = FeatureGeometry::Polygon(vec![clockwise.clone()]);
+
+        assert_eq!(
+            polygon.check_winding(),
+            vec![WindingIssue { polygon: 0, ring: 0, kind: WindingIssueKind::ClockwiseExterior }]
+        );
+        // Checking leaves the data alone
+        assert_eq!(polygon, FeatureGeometry::Polygon(vec![clockwise]));
+
+        polygon.normalize_winding();
+        assert!(polygon.check_winding().is_empty());
+    }
 
-        let tile_count = 1u32 << z;
+    #[test]
+    fn test_fit_bounds_frames_the_data() {
+        let size = PixelSize { width: 800, height: 600 };
+        let mut viewport = Viewport::new(GeoPoint::new(0.0, 0.0), 2.0, size).unwrap();
+        let berlin = GeoBounds::new(13.09, 52.34, 13.76, 52.68);
+        viewport.fit_bounds(&berlin, 20.0).unwrap();
+
+        // The data fits inside the padded canvas and fills it along one axis
+        let (west, north) = viewport.world_to_screen(&GeoPoint::new(berlin.max_y, berlin.min_x));
+        let (east, south) = viewport.world_to_screen(&GeoPoint::new(berlin.min_y, berlin.max_x));
+        assert!(west >= 20.0 - 1e-6 && east <= 780.0 + 1e-6 && north >= 20.0 - 1e-6 && south <= 580.0 + 1e-6);
+        assert!((east - west - 760.0).abs() < 1e-6 || (south - north - 560.0).abs() < 1e-6);
+        assert!(((west + east) / 2.0 - 400.0).abs() < 1e-6 && ((north + south) / 2.0 - 300.0).abs() < 1e-6);
+        assert!(viewport.zoom.fract() != 0.0 && (9.0..11.0).contains(&viewport.zoom));
+
+        viewport.fit_bounds(&GeoBounds::new(13.4, 52.5, 13.4, 52.5), 20.0).unwrap();
+        assert_eq!(viewport.zoom, FIT_POINT_ZOOM);
+        assert!((viewport.center.lat - 52.5).abs() < 1e-9 && (viewport.center.lng - 13.4).abs() < 1e-9);
+        // The whole world fits 600px at log2(600 / 256); with oversized padding it clamps to 0
+        viewport.fit_bounds(&GeoBounds::new(-180.0, -85.0, 180.0, 85.0), 0.0).unwrap();
+        assert!((viewport.zoom - (600.0f64 / 256.0).log2()).abs() < 0.01);
+        viewport.fit_bounds(&GeoBounds::new(-180.0, -85.0, 180.0, 85.0), 1000.0).unwrap();
+        assert_eq!(viewport.zoom, 0.0);
+        viewport.fit_bounds(&GeoBounds::new(177.0, -19.2, -178.0, -16.0), 0.0).unwrap();
+        assert!((viewport.center.lng - 179.5).abs() < 1e-9);
+        assert!(viewport.fit_bounds(&GeoBounds::new(0.0, f64::NAN, 1.0, 1.0), 0.0).is_err());
+    }
 
-        // Calculate tile bounds
-        let min_tile_x = ((self.bounds.min_x + 180.0) / 360.0 * tile_count as f64).floor() as u32;
-        let max_tile_x = ((self.bounds.max_x + 180.0) / 360.0 * tile_count as f64).ceil() as u32;
-        let min_tile_y =
-            ((1.0 - (self.bounds.max_y + 90.0) / 180.0) * tile_count as f64).floor() as u32;
-        let max_tile_y =
-            ((1.0 - (self.bounds.min_y + 90.0) / 180.0) * tile_count as f64).ceil() as u32;
+    #[test]
+    fn test_required_tiles_follow_projection() {
+        let viewport = Viewport::new(GeoPoint::new(60.0, 10.0), 4.0, PixelSize { width: 256, height: 256 }).unwrap();
+        let mercator = viewport.get_required_tiles();
+        let equirectangular = viewport.clone().with_projection(Equirectangular).get_required_tiles();
+
+        assert!(!mercator.is_empty() && !equirectangular.is_empty());
+        assert_ne!(mercator, equirectangular);
+        // The Mercator grid runs to 85°, so 60°N falls on rows further from the top edge
+        let min_row = |tiles: &[(u32, u32, u8)]| tiles.iter().map(|t| t.1).min().unwrap();
+        assert!(min_row(&mercator) > min_row(&equirectangular));
+    }
 
-        let mut tiles = Vec::new();
-        for x in min_tile_x..=max_tile_x.min(tile_count - 1) {
-            for y in min_tile_y..=max_tile_y.min(tile_count - 1) {
-                tiles.push((x, y, z));
+    #[test]
+    fn test_viewport_and_render_context_project_identically() {
+        use crate::engine::RenderContext;
+        use crate::view::view::MapStyle;
+
+        let close = |a: (f64, f64), b: (f64, f64)| (a.0 - b.0).abs() < 1e-6 && (a.1 - b.1).abs() < 1e-6;
+        let points = [GeoPoint::new(47.6, -122.3), GeoPoint::new(47.65, -122.41), GeoPoint::new(10.0, 10.0)];
+        // A regular canvas and one below MIN_CANVAS_PX
+        for (width, height) in [(800, 600), (2, 2)] {
+            let viewport = Viewport::new(GeoPoint::new(47.6, -122.3), 11.0, PixelSize::new(width, height)).unwrap();
+            let canvas = (width as f64, height as f64);
+            let context = RenderContext::new(viewport.bounds.clone(), canvas, 11, MapStyle::default());
+            for point in &points {
+                let expected = viewport.world_to_screen(point);
+                let (x, y) = (RenderContext::world_to_screen)(&context, point.lng, point.lat);
+                assert!(close(expected, (x, y)), "{:?} vs {:?}", expected, (x, y));
+                let back = transforms::screen_to_world((x, y), canvas, 0.0, &context.viewport_bounds);
+                assert!(close(back, (point.lng, point.lat)));
             }
+            // The center lands mid-canvas at any size
+            assert!(close(viewport.world_to_screen(&viewport.center), (canvas.0 / 2.0, canvas.1 / 2.0)));
+        }
+
+        // Rotating turns points about the center without changing the scale
+        let flat = Viewport::new(GeoPoint::new(47.6, -122.3), 11.0, PixelSize::new(800, 600)).unwrap();
+        let mut turned = flat.clone();
+        turned.rotate(30.0);
+        for point in &points[1..] {
+            let (fx, fy) = flat.world_to_screen(point);
+            let (tx, ty) = turned.world_to_screen(point);
+            let radius = |x: f64, y: f64| (x - 400.0).hypot(y - 300.0);
+            assert!((radius(fx, fy) - radius(tx, ty)).abs() < 1e-6);
+            let angle = |x: f64, y: f64| (y - 300.0).atan2(x - 400.0).to_degrees();
+            assert!(((angle(fx, fy) - angle(tx, ty)).rem_euclid(360.0) - 30.0).abs() < 1e-6);
+            let back = turned.screen_to_world(tx, ty);
+            assert!(close((back.lng, back.lat), (point.lng, point.lat)));
+        }
+
+        // A context carrying the rotation draws where the rotated viewport projects
+        let context = RenderContext::new(turned.bounds.clone(), (800.0, 600.0), 11, MapStyle::default()).with_rotation(30.0);
+        for point in &points {
+            let (x, y) = (RenderContext::world_to_screen)(&context, point.lng, point.lat);
+            assert!(close(turned.world_to_screen(point), (x, y)));
+        }
+    }
+
+    #[test]
+    fn test_viewport_uses_web_mercator() {
+        let viewport = Viewport::new(GeoPoint::new(60.0, 10.0), 14.0, PixelSize::new(800, 600)).unwrap();
+        let (x, y) = viewport.world_to_screen(&GeoPoint::new(60.0, 10.0));
+        assert!((x - 400.0).abs() < 1e-6 && (y - 300.0).abs() < 1e-6);
+
+        let point = GeoPoint::new(60.01, 10.02);
+        let (x, y) = viewport.world_to_screen(&point);
+        let back = viewport.screen_to_world(x, y);
+        assert!((back.lat - point.lat).abs() < 1e-9 && (back.lng - point.lng).abs() < 1e-9);
+
+        // A pixel covers meters_per_pixel of ground in both directions
+        let one_down = viewport.screen_to_world(400.0, 301.0);
+        let meters = (60.0 - one_down.lat).to_radians() * WEB_MERCATOR_RADIUS;
+        assert!((meters / viewport.meters_per_pixel() - 1.0).abs() < 1e-3);
+        // Mercator stretches latitude more towards the pole, so the north edge is fewer degrees away
+        assert!(viewport.bounds.max_y - 60.0 < 60.0 - viewport.bounds.min_y);
+
+        let world = Viewport::new(GeoPoint::new(0.0, 0.0), 0.0, PixelSize::new(1024, 1024)).unwrap();
+        assert_eq!(world.bounds.max_y, WEB_MERCATOR_MAX_LAT);
+        assert_eq!(world.screen_to_world(512.0, 0.0).lat, WEB_MERCATOR_MAX_LAT);
+    }
+
+    #[test]
+    fn test_tiny_viewport_round_trips() {
+        let viewport = Viewport::new(GeoPoint::new(10.0, 20.0), 20.0, PixelSize::new(2, 2)).unwrap();
+        assert!(!viewport.bounds.is_empty());
+
+        let (x, y) = viewport.world_to_screen(&GeoPoint::new(10.0, 20.0));
+        assert!(x.is_finite() && y.is_finite());
+        let back = viewport.screen_to_world(x, y);
+        assert!((back.lat - 10.0).abs() < 1e-9 && (back.lng - 20.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_geodesic_area() {
+        // Octant bounded by the equator and two meridians covers exactly 1/8 of the sphere
+        let octant = FeatureGeometry::Polygon(vec![vec![
+            GeoPoint::new(0.0, 0.0),
+            GeoPoint::new(0.0, 90.0),
+            GeoPoint::new(90.0, 0.0),
+            GeoPoint::new(0.0, 0.0),
+        ]]);
+        let sphere = 4.0 * std::f64::consts::PI * MEAN_EARTH_RADIUS * MEAN_EARTH_RADIUS;
+        assert!((octant.area_with(AreaMethod::Geodesic) / (sphere / 8.0) - 1.0).abs() < 1e-9);
+
+        // 10° box around 45°N: Mercator inflates area by roughly sec²(45°) = 2
+        let ring = vec![
+            GeoPoint::new(40.0, 0.0),
+            GeoPoint::new(40.0, 10.0),
+            GeoPoint::new(50.0, 10.0),
+            GeoPoint::new(50.0, 0.0),
+            GeoPoint::new(40.0, 0.0),
+        ];
+        let hole = vec![
+            GeoPoint::new(44.0, 4.0),
+            GeoPoint::new(46.0, 4.0),
+            GeoPoint::new(46.0, 6.0),
+            GeoPoint::new(44.0, 6.0),
+            GeoPoint::new(44.0, 4.0),
+        ];
+        let box_only = FeatureGeometry::Polygon(vec![ring.clone()]);
+        let ratio = box_only.area_with(AreaMethod::Planar) / box_only.area_with(AreaMethod::Geodesic);
+        assert!((ratio - 2.0).abs() < 0.1, "ratio {}", ratio);
+
+        let with_hole = FeatureGeometry::Polygon(vec![ring, hole]);
+        assert!(with_hole.area_with(AreaMethod::Geodesic) < box_only.area_with(AreaMethod::Geodesic));
+        assert_eq!(FeatureGeometry::Point(GeoPoint::new(0.0, 0.0)).area_with(AreaMethod::Geodesic), 0.0);
+    }
+
+    #[test]
+    fn test_length_and_perimeter_in_meters() {
+        let degree = MEAN_EARTH_RADIUS * std::f64::consts::PI / 180.0;
+        let equator = vec![GeoPoint::new(0.0, 0.0), GeoPoint::new(0.0, 1.0), GeoPoint::new(0.0, 3.0)];
+        let meridian = vec![GeoPoint::new(0.0, 0.0), GeoPoint::new(2.0, 0.0)];
+        assert!((FeatureGeometry::LineString(equator.clone()).length() - 3.0 * degree).abs() < 1e-6);
+        let lines = FeatureGeometry::MultiLineString(vec![equator, meridian]);
+        assert!((lines.length() - 5.0 * degree).abs() < 1e-6);
+        assert_eq!(lines.area(), 0.0);
+        assert_eq!(lines.perimeter(), 0.0);
+
+        // 1° square on the equator with a hole; the perimeter counts both rings
+        let square = |min: f64, size: f64| {
+            vec![
+                GeoPoint::new(min, min),
+                GeoPoint::new(min, min + size),
+                GeoPoint::new(min + size, min + size),
+                GeoPoint::new(min + size, min),
+                GeoPoint::new(min, min),
+            ]
+        };
+        let polygon = FeatureGeometry::Polygon(vec![square(0.0, 1.0), square(0.25, 0.5)]);
+        assert_eq!(polygon.area(), polygon.area_with(AreaMethod::Geodesic));
+        assert!((polygon.area() / (0.75 * degree * degree) - 1.0).abs() < 0.01);
+        assert!((polygon.perimeter() / (6.0 * degree) - 1.0).abs() < 0.01);
+        assert_eq!(polygon.length(), 0.0);
+        assert_eq!(FeatureGeometry::Point(GeoPoint::new(1.0, 1.0)).area(), 0.0);
+    }
+
+    #[test]
+    fn test_contains_point_respects_holes_and_boundaries() {
+        let square = |min: f64, size: f64| {
+            vec![
+                GeoPoint::new(min, min),
+                GeoPoint::new(min, min + size),
+                GeoPoint::new(min + size, min + size),
+                GeoPoint::new(min + size, min),
+                GeoPoint::new(min, min),
+            ]
+        };
+        let polygon = FeatureGeometry::Polygon(vec![square(0.0, 10.0), square(4.0, 2.0)]);
+        assert!(polygon.contains_point(&GeoPoint::new(2.0, 2.0)));
+        assert!(!polygon.contains_point(&GeoPoint::new(5.0, 5.0)));
+        assert!(!polygon.contains_point(&GeoPoint::new(11.0, 5.0)));
+        // On the outer ring and on the hole's edge
+        assert!(polygon.contains_point(&GeoPoint::new(0.0, 5.0)));
+        assert!(polygon.contains_point(&GeoPoint::new(4.0, 5.0)));
+
+        let line = FeatureGeometry::LineString(vec![GeoPoint::new(0.0, 0.0), GeoPoint::new(0.0, 10.0)]);
+        assert!(line.contains_point(&GeoPoint::new(1e-7, 5.0)));
+        assert!(!line.contains_point(&GeoPoint::new(0.01, 5.0)));
+
+        let feature = GeoFeature::new("lake".into(), polygon, DashMap::new());
+        assert!(feature.hit_test(&GeoPoint::new(2.0, 2.0)));
+        assert!(!feature.hit_test(&GeoPoint::new(5.0, 5.0)));
+        assert!(!feature.hit_test(&GeoPoint::new(50.0, 50.0)));
+        let city = GeoFeature::new("city".into(), FeatureGeometry::Point(GeoPoint::new(1.0, 1.0)), DashMap::new());
+        assert!(city.hit_test(&GeoPoint::new(1.0, 1.0 + 1e-7)));
+        assert!(!city.hit_test(&GeoPoint::new(1.0, 1.001)));
+    }
+
+    #[test]
+    fn test_scale_denominator_matches_wmts() {
+        let ogc_dpi = 25.4 / 0.28;
+        let size = PixelSize::new(256, 256);
+
+        // GoogleMapsCompatible well-known scale set, levels 0 and 10
+        let world = Viewport::new(GeoPoint::new(0.0, 0.0), 0.0, size.clone()).unwrap();
+        assert!((world.scale_denominator(ogc_dpi) - 559_082_264.028_717_8).abs() < 1e-3);
+        let city = Viewport::new(GeoPoint::new(0.0, 0.0), 10.0, size.clone()).unwrap();
+        assert!((city.scale_denominator(ogc_dpi) - 545_978.773_465_544_7).abs() < 1e-3);
+
+        // Mercator stretch halves the denominator at 60° latitude
+        let north = Viewport::new(GeoPoint::new(60.0, 0.0), 10.0, size).unwrap();
+        assert!((north.scale_denominator(ogc_dpi) - 545_978.773_465_544_7 / 2.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_label_point_inside_c_shape() {
+        // A "C" opening to the east: 3x3 square minus the 2x1 notch (1..3, 1..2)
+        let ring: Vec<GeoPoint> = [(0.0, 0.0), (3.0, 0.0), (3.0, 1.0), (1.0, 1.0), (1.0, 2.0), (3.0, 2.0), (3.0, 3.0), (0.0, 3.0), (0.0, 0.0)]
+            .into_iter()
+            .map(|(lng, lat)| GeoPoint::new(lat, lng))
+            .collect();
+        let geometry = FeatureGeometry::Polygon(vec![ring.clone()]);
+
+        let centroid = geometry.centroid();
+        assert!((centroid.lng - 9.5 / 7.0).abs() < 1e-9 && (centroid.lat - 1.5).abs() < 1e-9);
+        assert!(!ring_contains_point(&ring, &centroid));
+
+        let label = geometry.label_point(0.01);
+        assert!(ring_contains_point(&ring, &label));
+        // The widest part is the left bar, whose best point is half its width from the boundary
+        assert!(signed_boundary_distance(&label, &[ring]) > 0.5 - 0.01);
+    }
+
+    #[test]
+    fn test_oriented_bbox_of_rotated_rectangle() {
+        // 10 x 1 rectangle rotated by 45 degrees
+        let (c, s) = (std::f64::consts::FRAC_1_SQRT_2, std::f64::consts::FRAC_1_SQRT_2);
+        let rotate = |x: f64, y: f64| GeoPoint::new(x * s + y * c, x * c - y * s);
+        let ring = vec![rotate(0.0, 0.0), rotate(10.0, 0.0), rotate(10.0, 1.0), rotate(0.0, 1.0), rotate(0.0, 0.0)];
+        let geometry = FeatureGeometry::Polygon(vec![ring]);
+
+        let corners = geometry.oriented_bbox();
+        let side = |a: &GeoPoint, b: &GeoPoint| (b.lng - a.lng).hypot(b.lat - a.lat);
+        let oriented_area = side(&corners[0], &corners[1]) * side(&corners[1], &corners[2]);
+        let bounds = geometry.calculate_bounds();
+        let aligned_area = (bounds.max_x - bounds.min_x) * (bounds.max_y - bounds.min_y);
+
+        assert!((oriented_area - 10.0).abs() < 1e-9);
+        assert!(oriented_area < aligned_area / 4.0);
+    }
+
+    #[test]
+    fn test_oriented_bbox_of_collinear_points() {
+        let line = FeatureGeometry::LineString(vec![
+            GeoPoint::new(0.0, 0.0),
+            GeoPoint::new(1.0, 1.0),
+            GeoPoint::new(2.0, 2.0),
+        ]);
+        let corners = line.oriented_bbox();
+        assert_eq!(corners[0], GeoPoint::new(0.0, 0.0));
+        assert_eq!(corners[1], GeoPoint::new(2.0, 2.0));
+    }
+
+    #[test]
+    fn test_buffer_bounds() {
+        let buffered = GeoBounds::new(10.0, 20.0, 11.0, 21.0).buffer(0.5);
+        assert_eq!(buffered, GeoBounds::new(9.5, 19.5, 11.5, 21.5));
+        assert_eq!(buffered.max_x - buffered.min_x, 2.0);
+
+        let clamped = GeoBounds::new(179.0, 89.0, 180.0, 90.0).buffer(2.0);
+        assert_eq!(clamped, GeoBounds::new(177.0, 87.0, 180.0, 90.0));
+    }
+
+    #[test]
+    fn test_stream_features_in_bounds() {
+        let path = std::env::temp_dir().join(format!("{}.ndjson", uuid::Uuid::new_v4()));
+        let lines: Vec<String> = (0..10)
+            .map(|i| {
+                format!(
+                    r#"{{"type":"Feature","id":{},"geometry":{{"type":"Point","coordinates":[{},{}]}},"properties":{{}}}}"#,
+                    i,
+                    i as f64 * 20.0 - 90.0,
+                    i as f64 * 5.0 - 25.0
+                )
+            })
+            .collect();
+        std::fs::write(&path, lines.join("\n")).unwrap();
+
+        let file = GeoArrowFile::new(path.to_string_lossy().into_owned(), 0, String::new());
+        let window = GeoBounds::new(-35.0, -15.0, -5.0, -2.0);
+        let ids: Vec<FeatureId> = file
+            .stream_features_in_bounds(&window)
+            .unwrap()
+            .map(|feature| feature.unwrap().id)
+            .collect();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(ids, vec![FeatureId::from(3u64), FeatureId::from(4u64)]);
+    }
+
+    #[test]
+    fn test_dms_matches_decimal_degrees() {
+        let point = GeoPoint::from_dms(
+            Dms::new(40.0, 26.0, 46.0, Hemisphere::North),
+            Dms::new(79.0, 58.0, 56.0, Hemisphere::West),
+        )
+        .unwrap();
+
+        assert!((point.lat - 40.446_111).abs() < 1e-6);
+        assert!((point.lng - -79.982_222).abs() < 1e-6);
+        assert_eq!(Dms::new(33.0, 51.0, 54.0, Hemisphere::South).to_decimal().unwrap(), -(33.0 + 51.0 / 60.0 + 54.0 / 3600.0));
+    }
+
+    #[test]
+    fn test_format_coordinate_at_known_pixel() {
+        let mut viewport = Viewport::new(GeoPoint::new(52.52, 13.405), 12.0, PixelSize::new(800, 600)).unwrap();
+
+        assert_eq!(viewport.format_coordinate(400.0, 300.0, CoordFormat::DecimalDegrees), "52.52000° N, 13.40500° E");
+        assert_eq!(viewport.format_coordinate(400.0, 300.0, CoordFormat::Dms), "52° 31' 12.00\" N, 13° 24' 18.00\" E");
+        assert_eq!(viewport.format_coordinate(400.0, 300.0, CoordFormat::MgrsLite), "33U 391779E 5820072N");
+        assert_eq!(format_decimal_degrees(&GeoPoint::new(-33.865, -151.2094)), "33.86500° S, 151.20940° W");
+
+        // A quarter turn puts what was below the center to its right
+        let below = viewport.format_coordinate(400.0, 400.0, CoordFormat::Dms);
+        viewport.rotate(90.0);
+        assert_eq!(viewport.format_coordinate(500.0, 300.0, CoordFormat::Dms), below);
+    }
+
+    #[test]
+    fn test_rotated_viewport_round_trips() {
+        let mut viewport = Viewport::new(GeoPoint::new(52.52, 13.405), 12.0, PixelSize::new(800, 600)).unwrap();
+        let east = GeoPoint::new(52.52, 13.45);
+        let unrotated = viewport.world_to_screen(&east);
+        let unrotated_bounds = viewport.bounds.clone();
+        viewport.rotate(30.0);
+
+        // The offset from the center keeps its length but turns 30° counter-clockwise on screen
+        let (x, y) = viewport.world_to_screen(&east);
+        let (dx, dy) = (unrotated.0 - 400.0, unrotated.1 - 300.0);
+        let (sin, cos) = 30f64.to_radians().sin_cos();
+        assert!((x - (400.0 + dx * cos + dy * sin)).abs() < 1e-9);
+        assert!((y - (300.0 - dx * sin + dy * cos)).abs() < 1e-9);
+        assert!(y < 300.0);
+
+        let back = viewport.screen_to_world(x, y);
+        assert!((back.lat - east.lat).abs() < 1e-9 && (back.lng - east.lng).abs() < 1e-9);
+        let corner = viewport.screen_to_world(0.0, 0.0);
+        let (cx, cy) = viewport.world_to_screen(&corner);
+        assert!(cx.abs() < 1e-6 && cy.abs() < 1e-6);
+
+        // The bounds grow to cover the turned canvas corners
+        assert!(viewport.bounds.contains(corner.lng, corner.lat));
+        assert!(viewport.bounds.max_x - viewport.bounds.min_x > unrotated_bounds.max_x - unrotated_bounds.min_x);
+    }
+
+    #[test]
+    fn test_dms_rejects_invalid_input() {
+        let lat = Dms::new(10.0, 0.0, 0.0, Hemisphere::North);
+        assert!(GeoPoint::from_dms(lat, Dms::new(10.0, 0.0, 0.0, Hemisphere::North)).is_err());
+        assert!(GeoPoint::from_dms(lat, Dms::new(10.0, 61.0, 0.0, Hemisphere::East)).is_err());
+        assert!(GeoPoint::from_dms(Dms::new(91.0, 0.0, 0.0, Hemisphere::North), Dms::new(0.0, 0.0, 0.0, Hemisphere::East)).is_err());
+    }
+
+    #[test]
+    fn test_haversine_distance_between_cities() {
+        let new_york = GeoPoint::new(40.7128, -74.0060);
+        let los_angeles = GeoPoint::new(34.0522, -118.2437);
+        let london = GeoPoint::new(51.5074, -0.1278);
+        let paris = GeoPoint::new(48.8566, 2.3522);
+
+        assert!((new_york.distance_to(&los_angeles) / 3_935_000.0 - 1.0).abs() < 0.01);
+        assert!((london.distance_to(&paris) / 344_000.0 - 1.0).abs() < 0.01);
+        assert_eq!(paris.distance_to(&paris), 0.0);
+
+        assert!((new_york.bearing_to(&los_angeles) - 273.7).abs() < 0.1);
+        assert!((london.bearing_to(&paris) - 148.1).abs() < 0.1);
+        assert_eq!(GeoPoint::new(0.0, 0.0).bearing_to(&GeoPoint::new(0.0, 1.0)), 90.0);
+        assert_eq!(paris.bearing_to(&paris), 0.0);
+    }
+
+    #[test]
+    fn test_feature_properties_round_trip() {
+        let feature: Feature = r#"{"type":"Feature","id":"city",
+            "geometry":{"type":"Point","coordinates":[13.4,52.5]},
+            "properties":{"name":"Berlin","population":3645000,"area":891.8}}"#
+            .parse()
+            .unwrap();
+
+        let loaded = GeoFeature::from_geojson_feature(&feature).unwrap();
+        assert_eq!(loaded.id, FeatureId::from("city"));
+        assert_eq!(loaded.properties.get("name").unwrap().as_str(), Some("Berlin"));
+        assert_eq!(loaded.properties.get("population").unwrap().as_u64(), Some(3_645_000));
+        assert_eq!(loaded.properties.get("area").unwrap().as_f64(), Some(891.8));
+
+        let written = loaded.to_geojson_feature();
+        assert_eq!(written.properties, feature.properties);
+
+        // A null properties member loads as an empty map
+        let bare = Feature::from(Geometry::new(GeoValue::Point(vec![1.0, 2.0])));
+        assert!(GeoFeature::from_geojson_feature(&bare).unwrap().properties.is_empty());
+    }
+
+    #[test]
+    fn test_geojson_export_round_trips_every_geometry_type() {
+        let content = r#"{"type":"FeatureCollection","features":[
+            {"type":"Feature","id":7,"geometry":{"type":"Point","coordinates":[13.4,52.5,34.0]},"properties":{"name":"Berlin"}},
+            {"type":"Feature","id":"road","geometry":{"type":"LineString","coordinates":[[0.1,0.2],[1.5,-2.25]]},"properties":{"lanes":2,"toll":null}},
+            {"type":"Feature","id":"park","geometry":{"type":"Polygon","coordinates":[[[0,0],[0,4],[4,4],[4,0],[0,0]],[[1,1],[2,1],[2,2],[1,1]]]},"properties":{"tags":["a","b"]}},
+            {"type":"Feature","id":"stops","geometry":{"type":"MultiPoint","coordinates":[[1,2],[3,4]]},"properties":{}},
+            {"type":"Feature","id":"rails","geometry":{"type":"MultiLineString","coordinates":[[[0,0],[1,1]],[[2,2],[3,3]]]},"properties":{"nested":{"gauge":1435}}},
+            {"type":"Feature","id":"islands","geometry":{"type":"MultiPolygon","coordinates":[[[[0,0],[1,0],[1,1],[0,0]]],[[[5,5],[6,5],[6,6],[5,5]]]]},"properties":{"area":0.5}}
+        ]}"#;
+        let original: FeatureCollection = content.parse().unwrap();
+        let features = features_from_collection(&original, &LoadOptions::default()).unwrap();
+        let exported = features_to_geojson(&features);
+
+        assert_eq!(exported.features.len(), original.features.len());
+        for (written, read) in exported.features.iter().zip(&original.features) {
+            assert_eq!(written.id, read.id);
+            assert_eq!(written.geometry, read.geometry);
+            assert_eq!(written.properties, read.properties);
         }
-        tiles
+
+        // Lazily loaded features export without decoding their geometry
+        let lazy = features_from_collection(&original, &LoadOptions::default().with_lazy(true)).unwrap();
+        assert_eq!(features_to_geojson(&lazy), exported);
+        assert!(lazy.iter().all(|feature| !feature.is_decoded()));
+    }
+
+    #[test]
+    fn test_short_coordinate_array_errors_instead_of_panicking() {
+        // The GeoJSON text parser already rejects short positions, but geometries built in code are not checked
+        let mut feature = Feature::from(Geometry::new(GeoValue::Point(vec![1.5])));
+        feature.id = Some(geojson::feature::Id::String("broken".to_string()));
+
+        let error = GeoFeature::from_geojson_feature(&feature).unwrap_err();
+        assert!(matches!(&error, GeoArrowError::Serialization(message) if message.contains("broken")));
+
+        let polygon = GeoValue::Polygon(vec![vec![vec![0.0, 0.0], vec![1.0], vec![0.0, 1.0]]]);
+        assert!(FeatureGeometry::from_geojson_geometry(&Geometry::new(polygon.clone())).is_err());
+        // Lazy features defer the check until the geometry is decoded
+        let lazy = GeoFeature::new_lazy(FeatureId::from("lazy"), polygon, DashMap::new());
+        assert!(lazy.geometry().is_err());
+    }
+
+    #[test]
+    fn test_polygon_keeps_elevation_and_2d_bounds() {
+        let contour = Geometry::new(GeoValue::Polygon(vec![vec![
+            vec![-111.0, 40.0, 1500.0],
+            vec![-110.0, 40.0, 1510.0],
+            vec![-110.0, 41.0, 1520.0],
+            vec![-111.0, 40.0, 1500.0],
+        ]]));
+        let geometry = FeatureGeometry::from_geojson_geometry(&contour).unwrap();
+        let FeatureGeometry::Polygon(rings) = &geometry else { panic!("expected a polygon") };
+        assert_eq!(rings[0][2], GeoPoint::new_3d(41.0, -110.0, 1520.0));
+        assert_eq!(geometry.calculate_bounds(), GeoBounds::new(-111.0, 40.0, -110.0, 41.0));
+
+        assert!(GeoPoint::new_3d(40.0, -111.0, -420.0).is_valid());
+        assert!(!GeoPoint::new_3d(40.0, -111.0, f64::NAN).is_valid());
+        let unbounded = Geometry::new(GeoValue::Point(vec![-111.0, 40.0, f64::INFINITY]));
+        assert!(FeatureGeometry::from_geojson_geometry(&unbounded).is_err());
+    }
+
+    #[test]
+    fn test_mixed_dimensions_follow_policy() {
+        let feature: Feature = r#"{"type": "Feature", "id": "trail", "properties": {},
+            "geometry": {"type": "LineString", "coordinates": [[10, 45, 1200.5], [10.1, 45.1], [10.2, 45.2, 1350]]}}"#
+            .parse()
+            .unwrap();
+        let elevations = |options: &LoadOptions| {
+            let loaded = GeoFeature::from_geojson_feature_with(&feature, options).unwrap();
+            let FeatureGeometry::LineString(points) = loaded.geometry().unwrap() else { panic!("expected a line") };
+            points.iter().map(|p| p.elevation).collect::<Vec<_>>()
+        };
+
+        let fill_null = LoadOptions::default().with_dimension_policy(DimensionPolicy::FillNull);
+        assert_eq!(elevations(&fill_null), [Some(1200.5), None, Some(1350.0)]);
+        let drop = LoadOptions::default().with_dimension_policy(DimensionPolicy::DropZ);
+        assert_eq!(elevations(&drop), [None, None, None]);
+        assert_eq!(elevations(&drop.with_lazy(true)), [None, None, None]);
+
+        let strict = LoadOptions::default().with_dimension_policy(DimensionPolicy::Error);
+        let error = GeoFeature::from_geojson_feature_with(&feature, &strict).unwrap_err();
+        assert!(error.to_string().contains("trail"));
+    }
+
+    #[test]
+    fn test_drop_empty_geometries_on_load() {
+        let content = r#"{"type": "FeatureCollection", "features": [
+            {"type": "Feature", "id": "empty", "geometry": {"type": "LineString", "coordinates": []}, "properties": {}},
+            {"type": "Feature", "id": "hollow", "geometry": {"type": "MultiPolygon", "coordinates": [[[]]]}, "properties": {}},
+            {"type": "Feature", "id": "line", "geometry": {"type": "LineString", "coordinates": [[0, 0], [1, 1]]}, "properties": {}}
+        ]}"#;
+
+        let kept = features_from_geojson_str(content, &LoadOptions::default()).unwrap();
+        assert_eq!(kept.len(), 3);
+        assert!(kept[0].geometry().unwrap().is_empty());
+        assert!(kept[1].geometry().unwrap().is_empty());
+
+        let options = LoadOptions::default().with_drop_empty_geometries(true);
+        let dropped = features_from_geojson_str(content, &options).unwrap();
+        assert_eq!(dropped.iter().map(|f| f.id.to_string()).collect::<Vec<_>>(), ["line"]);
+    }
+
+    #[test]
+    fn test_foreign_members_survive_round_trip() {
+        let content = r#"{"type": "FeatureCollection", "features": [{
+            "type": "Feature", "id": "a",
+            "geometry": {"type": "Point", "coordinates": [13.4, 52.5]},
+            "properties": {"name": "Berlin"},
+            "source": {"survey": 2021}
+        }]}"#;
+
+        let dropped = features_from_geojson_str(content, &LoadOptions::default()).unwrap();
+        assert!(dropped[0].to_geojson_feature().foreign_members.is_none());
+
+        let options = LoadOptions::default().with_preserve_foreign_members(true);
+        let features = features_from_geojson_str(content, &options).unwrap();
+        let exported = features_to_collection(&features, None).to_string();
+        let reloaded = features_from_geojson_str(&exported, &options).unwrap();
+
+        let members = reloaded[0].foreign_members.as_ref().unwrap();
+        assert_eq!(members.get("source"), Some(&serde_json::json!({"survey": 2021})));
+        assert_eq!(reloaded[0].properties.get("name").unwrap().value(), "Berlin");
+        assert_eq!(reloaded[0].geometry().unwrap(), features[0].geometry().unwrap());
+    }
+
+    #[test]
+    fn test_trusted_load_matches_validated() {
+        let coordinates: Vec<String> = (0..2_000)
+            .map(|i| format!("[{}, {}]", (i % 360) as f64 - 180.0 + 0.5, (i % 180) as f64 - 90.0 + 0.25))
+            .collect();
+        let content = format!(
+            r#"{{"type":"Feature","id":"cloud","geometry":{{"type":"MultiPoint","coordinates":[{}]}},"properties":{{}}}}"#,
+            coordinates.join(",")
+        );
+        let feature: Feature = content.parse().unwrap();
+
+        let validated = GeoFeature::from_geojson_feature_with(&feature, &LoadOptions::default()).unwrap();
+        let trusted = GeoFeature::from_geojson_feature_with(&feature, &LoadOptions::default().with_trust_input(true)).unwrap();
+
+        assert_eq!(validated.geometry().unwrap(), trusted.geometry().unwrap());
+        assert_eq!(validated.bounds, trusted.bounds);
+
+        // Trusting input really does skip the range check
+        let out_of_range = Geometry::new(GeoValue::Point(vec![200.0, 95.0]));
+        assert!(FeatureGeometry::from_geojson_geometry(&out_of_range).is_err());
+        assert!(FeatureGeometry::from_geojson_geometry_trusted(&out_of_range).is_ok());
+
+        // Lazy features decode with the options they were loaded with
+        let feature = Feature { geometry: Some(out_of_range), ..feature };
+        let lazy = LoadOptions::default().with_lazy(true);
+        assert!(GeoFeature::from_geojson_feature_with(&feature, &lazy).unwrap().geometry().is_err());
+        let trusted = GeoFeature::from_geojson_feature_with(&feature, &lazy.with_trust_input(true)).unwrap();
+        assert!(!trusted.is_decoded());
+        assert!(trusted.geometry().is_ok());
+    }
+
+    // Timing comparison, run with `cargo test --release -- --ignored --nocapture bench_trusted_load`
+    #[test]
+    #[ignore]
+    fn bench_trusted_load() {
+        let coordinates: Vec<String> = (0..1_000_000)
+            .map(|i| format!("[{}, {}]", (i % 3600) as f64 / 10.0 - 180.0, (i % 1800) as f64 / 10.0 - 90.0))
+            .collect();
+        let content = format!(
+            r#"{{"type":"Feature","id":"cloud","geometry":{{"type":"MultiPoint","coordinates":[{}]}},"properties":{{}}}}"#,
+            coordinates.join(",")
+        );
+        let feature: Feature = content.parse().unwrap();
+
+        // Best of several loads, so a cold first run doesn't skew either side
+        let best = |options: &LoadOptions| {
+            (0..5)
+                .map(|_| {
+                    let start = std::time::Instant::now();
+                    GeoFeature::from_geojson_feature_with(&feature, options).unwrap();
+                    start.elapsed()
+                })
+                .min()
+                .unwrap()
+        };
+        let validated = best(&LoadOptions::default());
+        let trusted = best(&LoadOptions::default().with_trust_input(true));
+        println!("1M points: validated {:?}, trusted {:?}", validated, trusted);
+    }
+
+    #[test]
+    fn test_lazy_load_defers_geometry_decoding() {
+        let features = features_from_collection(&sample_collection(), &LoadOptions::default().with_lazy(true)).unwrap();
+
+        assert!(features.iter().all(|f| !f.is_decoded()));
+        assert_eq!(features[1].bounds, GeoBounds::new(0.0, -5.0, 5.0, 0.0));
+
+        let geometry = features[0].geometry().unwrap();
+        assert!(matches!(geometry, FeatureGeometry::Point(p) if p.lat == 20.0 && p.lng == 10.0));
+        assert!(features[0].is_decoded());
+        assert!(!features[1].is_decoded());
+    }
+
+    #[test]
+    fn test_eager_load_decodes_immediately() {
+        let features = features_from_collection(&sample_collection(), &LoadOptions::default()).unwrap();
+
+        assert!(features.iter().all(|f| f.is_decoded()));
+        assert_eq!(features[0].properties.get("name").unwrap().as_str(), Some("A"));
+    }
+
+    #[test]
+    fn test_simplify_on_load_keeps_original() {
+        let coordinates: Vec<String> = (0..=20)
+            .map(|i| format!("[{}, {}]", i as f64, if i % 2 == 0 { 0.0 } else { 0.001 }))
+            .collect();
+        let collection: FeatureCollection = format!(
+            r#"{{"type": "FeatureCollection", "features": [{{"type": "Feature", "properties": {{}},
+                "geometry": {{"type": "LineString", "coordinates": [{}]}}}}]}}"#,
+            coordinates.join(",")
+        )
+        .parse()
+        .unwrap();
+
+        let features =
+            features_from_collection(&collection, &LoadOptions::default().with_simplify_on_load(0.01)).unwrap();
+
+        let original = features[0].geometry().unwrap();
+        let rendered = features[0].render_geometry().unwrap();
+        assert_eq!(original.vertex_count(), 21);
+        assert!(rendered.vertex_count() < original.vertex_count());
+        assert_eq!(rendered.vertex_count(), 2);
     }
 }
@@ -0,0 +1,256 @@
+use serde_json::Value;
+use crate::model::{FeatureGeometry, GeoFeature, LayerStyle};
+
+// Rule-based styling: the first rule whose selector matches a feature provides its style
+
+// Broad geometry class a selector can match; multi-geometries match their single counterpart
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GeometryKind {
+    Point,
+    Line,
+    Polygon,
+}
+
+impl GeometryKind {
+    pub fn of(geometry: &FeatureGeometry) -> Self {
+        match geometry {
+            FeatureGeometry::Point(_) | FeatureGeometry::MultiPoint(_) => GeometryKind::Point,
+            FeatureGeometry::LineString(_) | FeatureGeometry::MultiLineString(_) => GeometryKind::Line,
+            FeatureGeometry::Polygon(_) | FeatureGeometry::MultiPolygon(_) => GeometryKind::Polygon,
+        }
+    }
+}
+
+// Test against a single feature property
+#[derive(Clone, Debug, PartialEq)]
+pub enum PropertyPredicate {
+    Equals(String, Value),
+    NotEquals(String, Value),
+    Exists(String),
+    GreaterThan(String, f64),
+    LessThan(String, f64),
+}
+
+impl PropertyPredicate {
+    pub fn matches(&self, feature: &GeoFeature) -> bool {
+        let number = |key: &str| feature.properties.get(key).and_then(|value| value.as_f64());
+        match self {
+            PropertyPredicate::Equals(key, expected) => {
+                feature.properties.get(key).is_some_and(|value| *value == *expected)
+            }
+            PropertyPredicate::NotEquals(key, expected) => {
+                feature.properties.get(key).is_none_or(|value| *value != *expected)
+            }
+            PropertyPredicate::Exists(key) => feature.properties.contains_key(key),
+            PropertyPredicate::GreaterThan(key, bound) => number(key).is_some_and(|n| n > *bound),
+            PropertyPredicate::LessThan(key, bound) => number(key).is_some_and(|n| n < *bound),
+        }
+    }
+}
+
+// Matches features of an optional geometry kind whose properties satisfy every predicate
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Selector {
+    pub geometry_kind: Option<GeometryKind>,
+    pub predicates: Vec<PropertyPredicate>,
+}
+
+impl Selector {
+    /// Selector matching every feature
+    pub fn any() -> Self {
+        Selector::default()
+    }
+
+    pub fn with_geometry_kind(mut self, kind: GeometryKind) -> Self {
+        self.geometry_kind = Some(kind);
+        self
+    }
+
+    pub fn with_predicate(mut self, predicate: PropertyPredicate) -> Self {
+        self.predicates.push(predicate);
+        self
+    }
+
+    /// Shorthand for `key == value`
+    pub fn with_property(self, key: &str, value: impl Into<Value>) -> Self {
+        self.with_predicate(PropertyPredicate::Equals(key.to_string(), value.into()))
+    }
+
+    pub fn matches(&self, feature: &GeoFeature) -> bool {
+        let kind_matches = self.geometry_kind.is_none_or(|kind| {
+            feature.geometry().is_ok_and(|geometry| GeometryKind::of(geometry) == kind)
+        });
+        kind_matches && self.predicates.iter().all(|predicate| predicate.matches(feature))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct StyleRule {
+    pub selector: Selector,
+    pub style: LayerStyle,
+}
+
+impl StyleRule {
+    pub fn new(selector: Selector, style: LayerStyle) -> Self {
+        StyleRule { selector, style }
+    }
+}
+
+// Ordered rules plus the style for features no rule matches
+#[derive(Clone, Debug, Default)]
+pub struct StyleSheet {
+    pub rules: Vec<StyleRule>,
+    pub default: LayerStyle,
+}
+
+impl StyleSheet {
+    pub fn new(default: LayerStyle) -> Self {
+        StyleSheet { rules: Vec::new(), default }
+    }
+
+    pub fn with_rule(mut self, rule: StyleRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Style of the first rule matching `feature`, or the default
+    pub fn style_for(&self, feature: &GeoFeature) -> &LayerStyle {
+        self.rules
+            .iter()
+            .find(|rule| rule.selector.matches(feature))
+            .map_or(&self.default, |rule| &rule.style)
+    }
+}
+
+/// Style value as a function of zoom: `(zoom, value)` stops, linearly interpolated between
+/// neighbouring stops and held constant beyond the first and last
+#[derive(Clone, Debug, PartialEq)]
+pub struct ZoomStop<T>(pub Vec<(u8, T)>);
+
+// Values a `ZoomStop` can interpolate between
+pub trait Interpolate: Clone {
+    fn lerp(&self, other: &Self, t: f64) -> Self;
+}
+
+impl Interpolate for f64 {
+    fn lerp(&self, other: &Self, t: f64) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Interpolate for f32 {
+    fn lerp(&self, other: &Self, t: f64) -> Self {
+        self + (other - self) * t as f32
+    }
+}
+
+impl<T: Interpolate> ZoomStop<T> {
+    /// Stops in any order; they are sorted by zoom
+    pub fn new(mut stops: Vec<(u8, T)>) -> Self {
+        stops.sort_by_key(|(zoom, _)| *zoom);
+        ZoomStop(stops)
+    }
+
+    /// Value at a possibly fractional zoom, or `None` without stops
+    pub fn evaluate(&self, zoom: f64) -> Option<T> {
+        let upper = self.0.iter().position(|(stop, _)| f64::from(*stop) >= zoom);
+        match upper {
+            Some(0) => self.0.first().map(|(_, value)| value.clone()),
+            Some(i) => {
+                let ((z0, v0), (z1, v1)) = (&self.0[i - 1], &self.0[i]);
+                let t = (zoom - f64::from(*z0)) / f64::from(z1 - z0);
+                Some(v0.lerp(v1, t))
+            }
+            None => self.0.last().map(|(_, value)| value.clone()),
+        }
+    }
+}
+
+// Categorical palette (Tableau 10): hues spaced for telling categories apart at a glance
+const CATEGORY_PALETTE: [&str; 10] = [
+    "#4e79a7", "#f28e2b", "#e15759", "#76b7b2", "#59a14f",
+    "#edc948", "#b07aa1", "#ff9da7", "#9c755f", "#bab0ac",
+];
+
+/// Number of distinct colors `category_color` cycles through
+pub const CATEGORY_PALETTE_SIZE: usize = CATEGORY_PALETTE.len();
+
+/// Stable palette color for a category value, for legends that must color consistently across
+/// reloads. Uses FNV-1a rather than the std hasher, whose output isn't guaranteed across releases.
+pub fn category_color(value: &str) -> String {
+    let hash = value.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    });
+    // FNV's low bits mix poorly; fold the high half in before taking the remainder
+    let hash = hash ^ (hash >> 32);
+    CATEGORY_PALETTE[(hash % CATEGORY_PALETTE_SIZE as u64) as usize].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::GeoPoint;
+    use dashmap::DashMap;
+
+    fn polygon(kind: &str) -> GeoFeature {
+        let ring = vec![
+            GeoPoint::new(0.0, 0.0),
+            GeoPoint::new(0.0, 1.0),
+            GeoPoint::new(1.0, 1.0),
+            GeoPoint::new(0.0, 0.0),
+        ];
+        let properties = DashMap::new();
+        properties.insert("type".to_string(), Value::from(kind));
+        GeoFeature::new(kind.into(), FeatureGeometry::Polygon(vec![ring]), properties)
+    }
+
+    #[test]
+    fn test_first_matching_rule_wins() {
+        let mut water = LayerStyle::default();
+        water.polygon_style.fill_color = "#0000FF".to_string();
+        let sheet = StyleSheet::new(LayerStyle::default()).with_rule(StyleRule::new(
+            Selector::any()
+                .with_geometry_kind(GeometryKind::Polygon)
+                .with_property("type", "water"),
+            water,
+        ));
+
+        assert_eq!(sheet.style_for(&polygon("water")).polygon_style.fill_color, "#0000FF");
+        assert_eq!(
+            sheet.style_for(&polygon("forest")).polygon_style.fill_color,
+            LayerStyle::default().polygon_style.fill_color
+        );
+    }
+
+    #[test]
+    fn test_category_color_is_stable() {
+        assert_eq!(category_color("forest"), category_color("forest"));
+        assert!(CATEGORY_PALETTE.contains(&category_color("forest").as_str()));
+
+        let categories = ["water", "forest", "urban", "farmland", "wetland", "desert", "glacier", "scrub"];
+        let distinct: std::collections::HashSet<String> = categories.iter().map(|c| category_color(c)).collect();
+        assert!(distinct.len() > categories.len() / 2);
+
+        // Also reachable as `style::category_color`
+        assert_eq!(crate::style::category_color("forest"), category_color("forest"));
+        assert_eq!(crate::style::CATEGORY_PALETTE_SIZE, CATEGORY_PALETTE.len());
+    }
+
+    #[test]
+    fn test_line_width_zoom_stop_interpolates() {
+        let width = ZoomStop::new(vec![(16, 4.0), (10, 1.0)]);
+        assert_eq!(width.evaluate(13.5), Some(2.75));
+        assert_eq!(width.evaluate(4.0), Some(1.0));
+        assert_eq!(width.evaluate(20.0), Some(4.0));
+        assert_eq!(ZoomStop::<f64>(Vec::new()).evaluate(12.0), None);
+
+        let mut style = LayerStyle::default();
+        style.zoom_stops.line_width = Some(width);
+        style.zoom_stops.opacity = Some(ZoomStop::new(vec![(0, 0.0), (10, 1.0)]));
+        let resolved = style.at_zoom(12.0);
+        assert_eq!(resolved.line_style.width, 2.0);
+        assert_eq!(resolved.line_style.opacity, 1.0);
+        assert_eq!(style.at_zoom(5.0).line_style.opacity, 0.5);
+        assert_eq!(resolved.point_style.radius, style.point_style.radius);
+    }
+}
@@ -0,0 +1,270 @@
+use crate::error::GeoArrowError;
+use crate::model::{FeatureGeometry, GeoArrowResult, GeoPoint};
+
+// Well-Known Binary geometry support, as stored by GeoParquet and returned by PostGIS. Both byte
+// orders are read, along with the ISO (1001, 2001, 3001, ...) and PostGIS EWKB flag encodings
+// of Z, M and an embedded SRID.
+
+// EWKB flag bits in the geometry type
+const EWKB_Z: u32 = 0x8000_0000;
+const EWKB_M: u32 = 0x4000_0000;
+const EWKB_SRID: u32 = 0x2000_0000;
+
+impl FeatureGeometry {
+    /// Decode a WKB geometry of type 1–6 (Point through MultiPolygon). Z values become the
+    /// elevation and M values are dropped. Coordinates are range-checked like GeoJSON input.
+    /// POINT EMPTY (NaN coordinates) decodes to an empty MultiPoint.
+    pub fn from_wkb(bytes: &[u8]) -> GeoArrowResult<FeatureGeometry> {
+        let mut reader = WkbReader { bytes, position: 0 };
+        let geometry = reader.geometry()?;
+        if reader.position != bytes.len() {
+            return Err(GeoArrowError::Serialization(format!(
+                "Trailing bytes after WKB geometry at offset {}",
+                reader.position
+            )));
+        }
+        Ok(geometry)
+    }
+}
+
+// Names of the OGC types beyond the six supported ones, for error messages
+fn unsupported_type_name(code: u32) -> &'static str {
+    match code {
+        7 => "GeometryCollection",
+        8 => "CircularString",
+        9 => "CompoundCurve",
+        10 => "CurvePolygon",
+        11 => "MultiCurve",
+        12 => "MultiSurface",
+        13 => "Curve",
+        14 => "Surface",
+        15 => "PolyhedralSurface",
+        16 => "TIN",
+        17 => "Triangle",
+        _ => "unknown",
+    }
+}
+
+// Byte order and dimensions of the geometry being read
+#[derive(Clone, Copy)]
+struct Header {
+    little_endian: bool,
+    code: u32,
+    has_z: bool,
+    has_m: bool,
+}
+
+struct WkbReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl WkbReader<'_> {
+    fn take<const N: usize>(&mut self) -> GeoArrowResult<[u8; N]> {
+        let chunk = self
+            .bytes
+            .get(self.position..self.position + N)
+            .ok_or_else(|| GeoArrowError::Serialization(format!("Truncated WKB at offset {}", self.position)))?;
+        self.position += N;
+        Ok(chunk.try_into().expect("slice has length N"))
+    }
+
+    fn u32(&mut self, header: &Header) -> GeoArrowResult<u32> {
+        let bytes = self.take::<4>()?;
+        Ok(if header.little_endian { u32::from_le_bytes(bytes) } else { u32::from_be_bytes(bytes) })
+    }
+
+    fn f64(&mut self, header: &Header) -> GeoArrowResult<f64> {
+        let bytes = self.take::<8>()?;
+        Ok(if header.little_endian { f64::from_le_bytes(bytes) } else { f64::from_be_bytes(bytes) })
+    }
+
+    fn header(&mut self) -> GeoArrowResult<Header> {
+        let little_endian = match self.take::<1>()?[0] {
+            0 => false,
+            1 => true,
+            other => {
+                return Err(GeoArrowError::Serialization(format!("Invalid WKB byte order 0x{:02x}", other)));
+            }
+        };
+        let mut header = Header { little_endian, code: 0, has_z: false, has_m: false };
+        let raw = self.u32(&header)?;
+        if raw & EWKB_SRID != 0 {
+            // The SRID isn't kept; coordinates are taken as WGS84 like every other source
+            self.u32(&header)?;
+        }
+        let iso = raw & 0x0fff_ffff;
+        header.code = iso % 1000;
+        header.has_z = raw & EWKB_Z != 0 || matches!(iso / 1000, 1 | 3);
+        header.has_m = raw & EWKB_M != 0 || matches!(iso / 1000, 2 | 3);
+        Ok(header)
+    }
+
+    fn geometry(&mut self) -> GeoArrowResult<FeatureGeometry> {
+        let header = self.header()?;
+        match header.code {
+            // POINT EMPTY has no single-point form, so it becomes an empty MultiPoint
+            1 => Ok(self.point(&header)?.map_or(FeatureGeometry::MultiPoint(Vec::new()), FeatureGeometry::Point)),
+            2 => Ok(FeatureGeometry::LineString(self.points(&header)?)),
+            3 => Ok(FeatureGeometry::Polygon(self.rings(&header)?)),
+            4 => {
+                let points = self.members(&header, 1, Self::point)?;
+                Ok(FeatureGeometry::MultiPoint(points.into_iter().flatten().collect()))
+            }
+            5 => Ok(FeatureGeometry::MultiLineString(self.members(&header, 2, Self::points)?)),
+            6 => Ok(FeatureGeometry::MultiPolygon(self.members(&header, 3, Self::rings)?)),
+            code => Err(GeoArrowError::Serialization(format!(
+                "Unsupported WKB geometry type {} ({})",
+                code,
+                unsupported_type_name(code)
+            ))),
+        }
+    }
+
+    // A point, or None for the NaN coordinates WKB writers use to encode POINT EMPTY
+    fn point(&mut self, header: &Header) -> GeoArrowResult<Option<GeoPoint>> {
+        let (x, y) = (self.f64(header)?, self.f64(header)?);
+        let elevation = if header.has_z { Some(self.f64(header)?) } else { None };
+        if header.has_m {
+            self.f64(header)?;
+        }
+        if x.is_nan() && y.is_nan() {
+            return Ok(None);
+        }
+        let point = match elevation {
+            Some(elevation) => GeoPoint::new_3d(y, x, elevation),
+            None => GeoPoint::new(y, x),
+        };
+        if !point.is_valid() {
+            return Err(GeoArrowError::Serialization(format!("Invalid WKB coordinates: {:?}", point)));
+        }
+        Ok(Some(point))
+    }
+
+    fn points(&mut self, header: &Header) -> GeoArrowResult<Vec<GeoPoint>> {
+        let count = self.u32(header)?;
+        (0..count)
+            .map(|_| {
+                self.point(header)?.ok_or_else(|| {
+                    GeoArrowError::Serialization(format!("Empty point inside WKB type {} at offset {}", header.code, self.position))
+                })
+            })
+            .collect()
+    }
+
+    fn rings(&mut self, header: &Header) -> GeoArrowResult<Vec<Vec<GeoPoint>>> {
+        let count = self.u32(header)?;
+        (0..count).map(|_| self.points(header)).collect()
+    }
+
+    // Members of a Multi* geometry are complete WKB geometries with their own byte order. Each
+    // member's header is checked against `member_code` before its body is read, so nesting
+    // can't recurse.
+    fn members<T>(
+        &mut self,
+        header: &Header,
+        member_code: u32,
+        read: impl Fn(&mut Self, &Header) -> GeoArrowResult<T>,
+    ) -> GeoArrowResult<Vec<T>> {
+        let count = self.u32(header)?;
+        (0..count)
+            .map(|_| {
+                let member = self.header()?;
+                if member.code != member_code {
+                    return Err(GeoArrowError::Serialization(format!(
+                        "WKB type {} has a member of the wrong type {}",
+                        header.code, member.code
+                    )));
+                }
+                read(self, &member)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Little-endian header followed by the coordinates, as PostGIS and GeoParquet write them
+    fn le(code: u32, body: &[f64], counts: &[u32]) -> Vec<u8> {
+        let mut bytes = vec![1];
+        bytes.extend(code.to_le_bytes());
+        counts.iter().for_each(|count| bytes.extend(count.to_le_bytes()));
+        body.iter().for_each(|value| bytes.extend(value.to_le_bytes()));
+        bytes
+    }
+
+    #[test]
+    fn test_wkb_byte_orders_and_dimensions() {
+        let mut big_endian = vec![0];
+        big_endian.extend(1u32.to_be_bytes());
+        big_endian.extend(13.4f64.to_be_bytes());
+        big_endian.extend(52.5f64.to_be_bytes());
+        assert_eq!(FeatureGeometry::from_wkb(&big_endian).unwrap(), FeatureGeometry::Point(GeoPoint::new(52.5, 13.4)));
+        assert_eq!(
+            FeatureGeometry::from_wkb(&le(1, &[13.4, 52.5], &[])).unwrap(),
+            FeatureGeometry::from_wkb(&big_endian).unwrap()
+        );
+
+        // ISO PointZ, and PostGIS EWKB with the Z flag and an SRID of 4326
+        let expected = FeatureGeometry::Point(GeoPoint::new_3d(52.5, 13.4, 34.0));
+        assert_eq!(FeatureGeometry::from_wkb(&le(1001, &[13.4, 52.5, 34.0], &[])).unwrap(), expected);
+        let ewkb = le(EWKB_Z | EWKB_SRID | 1, &[13.4, 52.5, 34.0], &[4326]);
+        assert_eq!(FeatureGeometry::from_wkb(&ewkb).unwrap(), expected);
+        // PointM drops the measure
+        let measured = le(2001, &[13.4, 52.5, 7.0], &[]);
+        assert_eq!(FeatureGeometry::from_wkb(&measured).unwrap(), FeatureGeometry::Point(GeoPoint::new(52.5, 13.4)));
+    }
+
+    #[test]
+    fn test_wkb_polygons_and_multi_geometries() {
+        let ring = [0.0, 0.0, 4.0, 0.0, 4.0, 4.0, 0.0, 0.0];
+        let polygon = le(3, &ring, &[1, 4]);
+        let FeatureGeometry::Polygon(rings) = FeatureGeometry::from_wkb(&polygon).unwrap() else {
+            panic!("expected a polygon");
+        };
+        assert_eq!(rings, vec![vec![
+            GeoPoint::new(0.0, 0.0),
+            GeoPoint::new(0.0, 4.0),
+            GeoPoint::new(4.0, 4.0),
+            GeoPoint::new(0.0, 0.0),
+        ]]);
+
+        let mut multi = le(6, &[], &[2]);
+        multi.extend(&polygon);
+        multi.extend(&polygon);
+        let FeatureGeometry::MultiPolygon(polygons) = FeatureGeometry::from_wkb(&multi).unwrap() else {
+            panic!("expected a multipolygon");
+        };
+        assert_eq!(polygons, vec![rings.clone(), rings]);
+
+        let mut mixed = le(4, &[], &[1]);
+        mixed.extend(&polygon);
+        assert!(FeatureGeometry::from_wkb(&mixed).unwrap_err().to_string().contains("wrong type"));
+        // A Multi* nested in a Multi* is rejected from its header, before anything is read
+        let mut nested = le(6, &[], &[1]);
+        nested.extend(le(6, &[], &[1]));
+        assert!(FeatureGeometry::from_wkb(&nested).unwrap_err().to_string().contains("wrong type"));
+
+        // POINT EMPTY, alone or as a member, is written with NaN coordinates
+        let empty = le(1, &[f64::NAN, f64::NAN], &[]);
+        assert_eq!(FeatureGeometry::from_wkb(&empty).unwrap(), FeatureGeometry::MultiPoint(Vec::new()));
+        let mut points = le(4, &[], &[2]);
+        points.extend(&empty);
+        points.extend(le(1, &[13.4, 52.5], &[]));
+        assert_eq!(FeatureGeometry::from_wkb(&points).unwrap(), FeatureGeometry::MultiPoint(vec![GeoPoint::new(52.5, 13.4)]));
+    }
+
+    #[test]
+    fn test_wkb_rejects_unsupported_and_malformed_input() {
+        let error = |bytes: &[u8]| FeatureGeometry::from_wkb(bytes).unwrap_err().to_string();
+        assert!(error(&le(8, &[0.0, 0.0], &[1])).contains("CircularString"));
+        assert!(error(&le(2, &[0.0, 0.0], &[2])).contains("Truncated"));
+        assert!(error(&[2, 1, 0, 0, 0]).contains("byte order"));
+        assert!(error(&le(1, &[0.0, 95.0], &[])).contains("Invalid WKB coordinates"));
+        let mut trailing = le(1, &[0.0, 0.0], &[]);
+        trailing.push(0);
+        assert!(error(&trailing).contains("Trailing"));
+    }
+}
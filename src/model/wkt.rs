@@ -0,0 +1,247 @@
+use crate::error::GeoArrowError;
+use crate::model::{FeatureGeometry, GeoArrowResult, GeoPoint};
+
+// Well-Known Text geometry support. Coordinates are written `x y`, i.e. longitude first.
+
+impl FeatureGeometry {
+    /// Parse a WKT geometry such as `POLYGON((0 0, 1 0, 1 1, 0 0))`. Keywords are
+    /// case-insensitive; a third ordinate (`Z`, or an unlabelled 3D coordinate) becomes the
+    /// elevation and `M` values are dropped. The result must pass `is_valid`.
+    pub fn from_wkt(s: &str) -> GeoArrowResult<FeatureGeometry> {
+        let mut parser = Parser { tokens: tokenize(s), position: 0 };
+        let geometry = parser.geometry()?;
+        if let Some(extra) = parser.peek() {
+            return Err(unexpected(extra));
+        }
+        if !geometry.is_valid() {
+            return Err(GeoArrowError::Serialization(format!(
+                "Invalid WKT geometry '{}': coordinates out of range or rings not closed",
+                s.trim()
+            )));
+        }
+        Ok(geometry)
+    }
+
+    /// Format as WKT. Geometries whose every point has an elevation are written as `Z`.
+    pub fn to_wkt(&self) -> String {
+        let is_3d = self.points().iter().all(|point| point.elevation.is_some());
+        let coordinate = |point: &GeoPoint| match point.elevation {
+            Some(elevation) if is_3d => format!("{} {} {}", point.lng, point.lat, elevation),
+            _ => format!("{} {}", point.lng, point.lat),
+        };
+        let path = |points: &[GeoPoint]| {
+            format!("({})", points.iter().map(coordinate).collect::<Vec<_>>().join(", "))
+        };
+        let paths = |paths: &[Vec<GeoPoint>]| {
+            format!("({})", paths.iter().map(|points| path(points)).collect::<Vec<_>>().join(", "))
+        };
+
+        let (name, body) = match self {
+            FeatureGeometry::Point(point) => ("POINT", format!("({})", coordinate(point))),
+            FeatureGeometry::LineString(points) => ("LINESTRING", path(points)),
+            FeatureGeometry::Polygon(rings) => ("POLYGON", paths(rings)),
+            FeatureGeometry::MultiPoint(points) => (
+                "MULTIPOINT",
+                format!("({})", points.iter().map(|point| format!("({})", coordinate(point))).collect::<Vec<_>>().join(", ")),
+            ),
+            FeatureGeometry::MultiLineString(lines) => ("MULTILINESTRING", paths(lines)),
+            FeatureGeometry::MultiPolygon(polygons) => (
+                "MULTIPOLYGON",
+                format!("({})", polygons.iter().map(|rings| paths(rings)).collect::<Vec<_>>().join(", ")),
+            ),
+        };
+        if is_3d {
+            format!("{} Z {}", name, body)
+        } else {
+            format!("{} {}", name, body)
+        }
+    }
+}
+
+// Which extra ordinates follow x and y
+#[derive(Clone, Copy, PartialEq)]
+enum Dimension {
+    // No keyword: a third ordinate is taken as Z
+    Unlabelled,
+    Z,
+    M,
+    Zm,
+}
+
+// Split into parentheses, commas and whitespace-separated words or numbers
+fn tokenize(s: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (i, c) in s.char_indices() {
+        if c.is_whitespace() || matches!(c, '(' | ')' | ',') {
+            if let Some(begin) = start.take() {
+                tokens.push(&s[begin..i]);
+            }
+            if !c.is_whitespace() {
+                tokens.push(&s[i..i + 1]);
+            }
+        } else if start.is_none() {
+            start = Some(i);
+        }
+    }
+    if let Some(begin) = start {
+        tokens.push(&s[begin..]);
+    }
+    tokens
+}
+
+fn unexpected(token: &str) -> GeoArrowError {
+    GeoArrowError::Serialization(format!("Unexpected WKT token '{}'", token))
+}
+
+struct Parser<'a> {
+    tokens: Vec<&'a str>,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.position).copied()
+    }
+
+    fn next(&mut self) -> GeoArrowResult<&'a str> {
+        let token = self
+            .peek()
+            .ok_or_else(|| GeoArrowError::Serialization("Unexpected end of WKT".to_string()))?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, expected: &str) -> GeoArrowResult<()> {
+        match self.next()? {
+            token if token == expected => Ok(()),
+            token => Err(unexpected(token)),
+        }
+    }
+
+    fn geometry(&mut self) -> GeoArrowResult<FeatureGeometry> {
+        let name = self.next()?;
+        let dimension = match self.peek().map(str::to_ascii_uppercase).as_deref() {
+            Some("Z") => Dimension::Z,
+            Some("M") => Dimension::M,
+            Some("ZM") => Dimension::Zm,
+            _ => Dimension::Unlabelled,
+        };
+        if dimension != Dimension::Unlabelled {
+            self.position += 1;
+        }
+        if let Some(token) = self.peek().filter(|token| token.eq_ignore_ascii_case("EMPTY")) {
+            return Err(GeoArrowError::Serialization(format!(
+                "Unexpected WKT token '{}': empty geometries are not supported",
+                token
+            )));
+        }
+
+        let coordinate = |parser: &mut Self| parser.coordinate(dimension);
+        match name.to_ascii_uppercase().as_str() {
+            "POINT" => {
+                self.expect("(")?;
+                let point = coordinate(self)?;
+                self.expect(")")?;
+                Ok(FeatureGeometry::Point(point))
+            }
+            "LINESTRING" => Ok(FeatureGeometry::LineString(self.list(coordinate)?)),
+            "POLYGON" => Ok(FeatureGeometry::Polygon(self.list(|parser| parser.list(coordinate))?)),
+            // Members may be bare (`1 2, 3 4`) or parenthesized (`(1 2), (3 4)`)
+            "MULTIPOINT" => Ok(FeatureGeometry::MultiPoint(self.list(|parser| {
+                if parser.peek() != Some("(") {
+                    return coordinate(parser);
+                }
+                parser.expect("(")?;
+                let point = coordinate(parser)?;
+                parser.expect(")")?;
+                Ok(point)
+            })?)),
+            "MULTILINESTRING" => Ok(FeatureGeometry::MultiLineString(self.list(|parser| parser.list(coordinate))?)),
+            "MULTIPOLYGON" => Ok(FeatureGeometry::MultiPolygon(
+                self.list(|parser| parser.list(|parser| parser.list(coordinate)))?,
+            )),
+            _ => Err(GeoArrowError::Serialization(format!("Unsupported WKT geometry type '{}'", name))),
+        }
+    }
+
+    // Parenthesized, comma-separated items
+    fn list<T>(&mut self, mut item: impl FnMut(&mut Self) -> GeoArrowResult<T>) -> GeoArrowResult<Vec<T>> {
+        self.expect("(")?;
+        let mut items = vec![item(self)?];
+        loop {
+            match self.next()? {
+                "," => items.push(item(self)?),
+                ")" => return Ok(items),
+                token => return Err(unexpected(token)),
+            }
+        }
+    }
+
+    fn number(&mut self) -> GeoArrowResult<f64> {
+        let token = self.next()?;
+        token.parse::<f64>().ok().filter(|value| value.is_finite()).ok_or_else(|| unexpected(token))
+    }
+
+    fn coordinate(&mut self, dimension: Dimension) -> GeoArrowResult<GeoPoint> {
+        let (x, y) = (self.number()?, self.number()?);
+        let mut extra = Vec::new();
+        while extra.len() < 2 && self.peek().is_some_and(|token| !matches!(token, "," | ")")) {
+            extra.push(self.number()?);
+        }
+        let elevation = match (dimension, extra.as_slice()) {
+            (Dimension::Unlabelled, []) | (Dimension::M, [_]) => None,
+            (Dimension::Unlabelled, [z] | [z, _]) | (Dimension::Z, [z]) | (Dimension::Zm, [z, _]) => Some(*z),
+            // Ordinate count doesn't match the dimension keyword
+            _ => return Err(unexpected(self.tokens[self.position - 1])),
+        };
+        Ok(match elevation {
+            Some(elevation) => GeoPoint::new_3d(y, x, elevation),
+            None => GeoPoint::new(y, x),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wkt_round_trip() {
+        let polygon = FeatureGeometry::from_wkt("polygon ((0 0, 10 0, 10 10, 0 10, 0 0), (2 2, 2 4, 4 4, 2 2))").unwrap();
+        let FeatureGeometry::Polygon(rings) = &polygon else { panic!("expected a polygon") };
+        assert_eq!(rings.len(), 2);
+        assert_eq!(rings[0][1], GeoPoint::new(0.0, 10.0));
+        assert_eq!(polygon.to_wkt(), "POLYGON ((0 0, 10 0, 10 10, 0 10, 0 0), (2 2, 2 4, 4 4, 2 2))");
+
+        for wkt in [
+            "POINT (13.4 52.5)",
+            "POINT Z (13.4 52.5 34)",
+            "LINESTRING (0 0, 1 1, 2 0.5)",
+            "MULTIPOINT ((1 2), (3 4))",
+            "MULTILINESTRING ((0 0, 1 1), (2 2, 3 3))",
+            "MULTIPOLYGON (((0 0, 1 0, 1 1, 0 0)), ((5 5, 6 5, 6 6, 5 5)))",
+        ] {
+            assert_eq!(FeatureGeometry::from_wkt(wkt).unwrap().to_wkt(), wkt);
+        }
+        assert_eq!(
+            FeatureGeometry::from_wkt("MULTIPOINT (1 2, 3 4)").unwrap(),
+            FeatureGeometry::from_wkt("MULTIPOINT ((1 2), (3 4))").unwrap()
+        );
+        assert_eq!(FeatureGeometry::from_wkt("POINT M (1 2 9)").unwrap(), FeatureGeometry::Point(GeoPoint::new(2.0, 1.0)));
+    }
+
+    #[test]
+    fn test_wkt_errors_name_the_offending_token() {
+        let error = |wkt: &str| FeatureGeometry::from_wkt(wkt).unwrap_err().to_string();
+        assert!(error("POINT (1 abc)").contains("'abc'"));
+        assert!(error("LINESTRING (0 0; 1 1)").contains("'0;'"));
+        assert!(error("POINT (1 2) extra").contains("'extra'"));
+        assert!(error("CIRCLE (0 0)").contains("'CIRCLE'"));
+        assert!(error("POINT EMPTY").contains("'EMPTY'"));
+        assert!(error("LINESTRING (0 0, 1 1").contains("end of WKT"));
+        // Parses, but `is_valid` rejects the unclosed ring and the out-of-range latitude
+        assert!(error("POLYGON ((0 0, 1 0, 1 1, 0 1))").contains("Invalid WKT geometry"));
+        assert!(error("POINT (0 95)").contains("Invalid WKT geometry"));
+    }
+}
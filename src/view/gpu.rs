@@ -0,0 +1,275 @@
+use crate::engine::raster::parse_color;
+use crate::engine::RenderContext;
+use crate::error::GeoArrowError;
+use crate::model::{FeatureGeometry, GeoArrowResult, PointStyle};
+use std::sync::Arc;
+use winit::window::Window;
+
+// GPU rendering through wgpu, for feature counts the canvas-2d path can't keep up with. Every
+// point is drawn as one colored triangle; other geometry types are not drawn yet.
+
+// One triangle vertex: position in normalized device coordinates and straight-alpha RGBA
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GpuVertex {
+    pub position: [f32; 2],
+    pub color: [f32; 4],
+}
+
+const VERTEX_FLOATS: usize = 6;
+const VERTEX_ATTRIBUTES: [wgpu::VertexAttribute; 2] = wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x4];
+
+const POINT_SHADER: &str = r#"
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+};
+
+@vertex
+fn vs_main(@location(0) position: vec2<f32>, @location(1) color: vec4<f32>) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = vec4<f32>(position, 0.0, 1.0);
+    out.color = color;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return in.color;
+}
+"#;
+
+fn gpu_error(message: &str, e: impl std::fmt::Display) -> GeoArrowError {
+    GeoArrowError::Wasm(format!("{}: {}", message, e))
+}
+
+/// Append a triangle for every point of `geometry`, placed with the context's world-to-screen
+/// transform and sized so the point's circle of `style.radius` pixels fits inside it. Lines and
+/// polygons add nothing.
+pub fn push_point_triangles(
+    geometry: &FeatureGeometry,
+    context: &RenderContext,
+    style: &PointStyle,
+    vertices: &mut Vec<GpuVertex>,
+) {
+    let points = match geometry {
+        FeatureGeometry::Point(point) => std::slice::from_ref(point),
+        FeatureGeometry::MultiPoint(points) => points.as_slice(),
+        _ => return,
+    };
+    let [r, g, b, a] = parse_color(&style.color);
+    let color = [r, g, b, a].map(|channel| channel as f32 / 255.0);
+    let color = [color[0], color[1], color[2], color[3] * style.opacity];
+    let (width, height) = context.canvas_size;
+    // An equilateral triangle's incircle has half its circumradius
+    let circumradius = 2.0 * style.radius;
+
+    for point in points {
+        let (x, y) = RenderContext::world_to_screen(context, point.lng, point.lat);
+        // Corners pointing up, lower right and lower left; screen y grows downwards
+        for angle in [-90.0f64, 30.0, 150.0] {
+            let (sin, cos) = angle.to_radians().sin_cos();
+            let (corner_x, corner_y) = (x + circumradius * cos, y + circumradius * sin);
+            let position = [(corner_x / width * 2.0 - 1.0) as f32, (1.0 - corner_y / height * 2.0) as f32];
+            vertices.push(GpuVertex { position, color });
+        }
+    }
+}
+
+/// A window's wgpu surface with the point pipeline, created once and reused every frame
+pub struct State {
+    window: Arc<Window>,
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    surface: wgpu::Surface<'static>,
+    config: wgpu::SurfaceConfiguration,
+    pipeline: wgpu::RenderPipeline,
+    vertex_buffer: Option<wgpu::Buffer>,
+}
+
+impl State {
+    /// Request an adapter and device for `window` and configure its surface at the window's
+    /// current size
+    pub async fn new(window: Arc<Window>) -> GeoArrowResult<State> {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+        let surface = instance
+            .create_surface(window.clone())
+            .map_err(|e| gpu_error("Failed to create surface", e))?;
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::HighPerformance,
+                force_fallback_adapter: false,
+                compatible_surface: Some(&surface),
+            })
+            .await
+            .map_err(|e| gpu_error("No GPU adapter", e))?;
+        let (device, queue) = adapter
+            .request_device(&wgpu::DeviceDescriptor {
+                label: Some("geoarrow-viz"),
+                // WebGL2 limits, so the same code runs in the browser
+                required_limits: wgpu::Limits::downlevel_webgl2_defaults().using_resolution(adapter.limits()),
+                ..Default::default()
+            })
+            .await
+            .map_err(|e| gpu_error("Failed to request GPU device", e))?;
+
+        let size = window.inner_size();
+        let config = surface
+            .get_default_config(&adapter, size.width.max(1), size.height.max(1))
+            .ok_or_else(|| GeoArrowError::Wasm("Surface is not supported by the GPU adapter".to_string()))?;
+        surface.configure(&device, &config);
+        let pipeline = point_pipeline(&device, config.format);
+
+        Ok(State { window, device, queue, surface, config, pipeline, vertex_buffer: None })
+    }
+
+    pub fn window(&self) -> &Window {
+        &self.window
+    }
+
+    /// Surface size in pixels, the canvas size features are transformed to
+    pub fn canvas_size(&self) -> (f64, f64) {
+        (self.config.width as f64, self.config.height as f64)
+    }
+
+    /// Reconfigure the surface after the window was resized; zero sizes (minimized) are ignored
+    pub fn resize(&mut self, size: winit::dpi::PhysicalSize<u32>) {
+        if size.width == 0 || size.height == 0 {
+            return;
+        }
+        self.config.width = size.width;
+        self.config.height = size.height;
+        self.surface.configure(&self.device, &self.config);
+    }
+
+    /// Upload `vertices` and draw them as triangles over a transparent frame
+    pub fn render(&mut self, vertices: &[GpuVertex]) -> GeoArrowResult<()> {
+        let frame = match self.surface.get_current_texture() {
+            Ok(frame) => frame,
+            // The surface went stale, e.g. after a resize that wasn't reported yet
+            Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                self.surface.configure(&self.device, &self.config);
+                self.surface
+                    .get_current_texture()
+                    .map_err(|e| gpu_error("Failed to acquire frame", e))?
+            }
+            Err(e) => return Err(gpu_error("Failed to acquire frame", e)),
+        };
+        let view = frame.texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let bytes: Vec<u8> = vertices
+            .iter()
+            .flat_map(|vertex| vertex.position.into_iter().chain(vertex.color))
+            .flat_map(f32::to_le_bytes)
+            .collect();
+        let buffer = self.vertex_buffer(bytes.len() as u64);
+        self.queue.write_buffer(&buffer, 0, &bytes);
+
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("points") });
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("points"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    depth_slice: None,
+                    resolve_target: None,
+                    ops: wgpu::Operations { load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT), store: wgpu::StoreOp::Store },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            if !vertices.is_empty() {
+                pass.set_pipeline(&self.pipeline);
+                pass.set_vertex_buffer(0, buffer.slice(..bytes.len() as u64));
+                pass.draw(0..vertices.len() as u32, 0..1);
+            }
+        }
+        self.queue.submit([encoder.finish()]);
+        self.window.pre_present_notify();
+        frame.present();
+        Ok(())
+    }
+
+    // Vertex buffer of at least `size` bytes, grown by doubling so steady frames reuse it
+    fn vertex_buffer(&mut self, size: u64) -> wgpu::Buffer {
+        let size = size.max(wgpu::COPY_BUFFER_ALIGNMENT);
+        if let Some(buffer) = self.vertex_buffer.as_ref().filter(|buffer| buffer.size() >= size) {
+            return buffer.clone();
+        }
+        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("point vertices"),
+            size: size.next_power_of_two(),
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        self.vertex_buffer = Some(buffer.clone());
+        buffer
+    }
+}
+
+fn point_pipeline(device: &wgpu::Device, format: wgpu::TextureFormat) -> wgpu::RenderPipeline {
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("points"),
+        source: wgpu::ShaderSource::Wgsl(POINT_SHADER.into()),
+    });
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("points"),
+        layout: None,
+        vertex: wgpu::VertexState {
+            module: &shader,
+            entry_point: Some("vs_main"),
+            compilation_options: Default::default(),
+            buffers: &[wgpu::VertexBufferLayout {
+                array_stride: (VERTEX_FLOATS * std::mem::size_of::<f32>()) as u64,
+                step_mode: wgpu::VertexStepMode::Vertex,
+                attributes: &VERTEX_ATTRIBUTES,
+            }],
+        },
+        primitive: wgpu::PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState::default(),
+        fragment: Some(wgpu::FragmentState {
+            module: &shader,
+            entry_point: Some("fs_main"),
+            compilation_options: Default::default(),
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        multiview: None,
+        cache: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{GeoBounds, GeoPoint};
+    use crate::view::view::MapStyle;
+
+    #[test]
+    fn test_point_triangles_in_device_coordinates() {
+        let context = RenderContext::new(GeoBounds::new(0.0, 0.0, 10.0, 10.0), (100.0, 100.0), 1, MapStyle::default());
+        let style = PointStyle { color: "#FF0000".to_string(), radius: 5.0, opacity: 0.5 };
+        let mut vertices = Vec::new();
+
+        push_point_triangles(&FeatureGeometry::Point(GeoPoint::new(5.0, 5.0)), &context, &style, &mut vertices);
+        assert_eq!(vertices.len(), 3);
+        assert!(vertices.iter().all(|vertex| vertex.color == [1.0, 0.0, 0.0, 0.5]));
+        // The triangle is centered on the point; its top corner sits 10px (0.2 units) above it
+        let (_, y) = RenderContext::world_to_screen(&context, 5.0, 5.0);
+        let center_y = (1.0 - y / 50.0) as f32;
+        assert!(vertices[0].position[0].abs() < 1e-6 && (vertices[0].position[1] - center_y - 0.2).abs() < 1e-6);
+        let centroid = vertices.iter().fold([0.0; 2], |sum, vertex| [sum[0] + vertex.position[0], sum[1] + vertex.position[1]]);
+        assert!(centroid[0].abs() < 1e-5 && (centroid[1] / 3.0 - center_y).abs() < 1e-5);
+
+        let multi = FeatureGeometry::MultiPoint(vec![GeoPoint::new(0.0, 0.0), GeoPoint::new(10.0, 10.0)]);
+        push_point_triangles(&multi, &context, &style, &mut vertices);
+        assert_eq!(vertices.len(), 9);
+        let line = FeatureGeometry::LineString(vec![GeoPoint::new(0.0, 0.0), GeoPoint::new(1.0, 1.0)]);
+        push_point_triangles(&line, &context, &style, &mut vertices);
+        assert_eq!(vertices.len(), 9);
+    }
+}
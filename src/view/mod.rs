@@ -1 +1,3 @@
 pub mod view;
+pub mod scene;
+pub mod gpu;
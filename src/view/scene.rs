@@ -0,0 +1,238 @@
+use std::collections::{HashMap, HashSet};
+use crate::engine::projection::{tile_at, WebMercator};
+use crate::error::GeoArrowError;
+use crate::model::{DuplicateIdPolicy, FeatureId, GeoArrowResult, GeoBounds, GeoFeature};
+
+// Retained scene: the features a MapView keeps between renders
+#[derive(Clone, Debug, Default)]
+pub struct Scene {
+    features: Vec<GeoFeature>,
+    index: HashMap<FeatureId, usize>,
+    bounds: Option<GeoBounds>,
+}
+
+impl Scene {
+    pub fn new() -> Self {
+        Scene::default()
+    }
+
+    /// Add a feature, replacing any existing feature with the same id.
+    pub fn add_feature(&mut self, feature: GeoFeature) {
+        // KeepLast never fails
+        let _ = self.add_feature_with(feature, DuplicateIdPolicy::KeepLast);
+    }
+
+    /// Add a feature, resolving an id clash according to `policy`. Returns the id the feature
+    /// is stored under, or `None` when it was dropped in favor of an existing one.
+    pub fn add_feature_with(&mut self, mut feature: GeoFeature, policy: DuplicateIdPolicy) -> GeoArrowResult<Option<FeatureId>> {
+        if let Some(&existing) = self.index.get(&feature.id) {
+            match policy {
+                DuplicateIdPolicy::Error => {
+                    return Err(GeoArrowError::Serialization(format!("Duplicate feature id: {}", feature.id)));
+                }
+                DuplicateIdPolicy::KeepFirst => return Ok(None),
+                DuplicateIdPolicy::KeepLast => {
+                    let id = feature.id.clone();
+                    self.features[existing] = feature;
+                    self.recalculate_bounds();
+                    return Ok(Some(id));
+                }
+                DuplicateIdPolicy::Rename => feature.id = self.unused_id(&feature.id),
+            }
+        }
+
+        self.bounds = Some(match self.bounds.take() {
+            Some(bounds) => merge_bounds(&bounds, &feature.bounds),
+            None => feature.bounds.clone(),
+        });
+        let id = feature.id.clone();
+        self.index.insert(id.clone(), self.features.len());
+        self.features.push(feature);
+        Ok(Some(id))
+    }
+
+    // First `<id>-<n>` not yet in the index
+    fn unused_id(&self, id: &FeatureId) -> FeatureId {
+        (1..)
+            .map(|n| FeatureId::String(format!("{}-{}", id, n)))
+            .find(|candidate| !self.index.contains_key(candidate))
+            .expect("suffix space is unbounded")
+    }
+
+    pub fn get(&self, id: &FeatureId) -> Option<&GeoFeature> {
+        self.index.get(id).map(|&i| &self.features[i])
+    }
+
+    pub fn features(&self) -> &[GeoFeature] {
+        &self.features
+    }
+
+    pub fn len(&self) -> usize {
+        self.features.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.features.is_empty()
+    }
+
+    /// Overall extent of every feature in the scene, or `None` when the scene is empty.
+    pub fn bounds(&self) -> Option<&GeoBounds> {
+        self.bounds.as_ref()
+    }
+
+    /// Apply `f` to every feature in place, then recompute each feature's bounds and the scene extent.
+    pub fn transform_features(&mut self, mut f: impl FnMut(&mut GeoFeature)) {
+        for feature in &mut self.features {
+            f(feature);
+            feature.recalculate_bounds();
+        }
+        self.recalculate_bounds();
+    }
+
+    /// Keep only the features for which `keep` returns true, rebuilding the index and extent.
+    pub fn retain(&mut self, mut keep: impl FnMut(&GeoFeature) -> bool) {
+        self.features.retain(|feature| keep(feature));
+        self.index = self
+            .features
+            .iter()
+            .enumerate()
+            .map(|(i, feature)| (feature.id.clone(), i))
+            .collect();
+        self.recalculate_bounds();
+    }
+
+    /// Replace every multi-geometry feature with its single-geometry parts (see `GeoFeature::explode`)
+    pub fn explode(&mut self) -> GeoArrowResult<()> {
+        let mut exploded = Vec::with_capacity(self.features.len());
+        for feature in &self.features {
+            exploded.extend(feature.explode()?);
+        }
+        self.features = exploded;
+        // Rebuild the id index and extent for the new feature list
+        self.retain(|_| true);
+        Ok(())
+    }
+
+    /// Drop off-screen features until at most `budget` remain, a whole tile at a time, starting
+    /// with the tiles farthest from `visible`. Features intersecting `visible` are always kept, so
+    /// the scene may stay over budget. Returns the evicted `(x, y, z)` tiles so their features
+    /// can be re-parsed from the source later.
+    pub fn evict_offscreen(&mut self, visible: &GeoBounds, budget: usize, zoom: u8) -> Vec<(u32, u32, u8)> {
+        if self.features.len() <= budget {
+            return Vec::new();
+        }
+
+        let center = bounds_center(visible);
+        let mut tiles: HashMap<(u32, u32, u8), (usize, f64)> = HashMap::new();
+        for feature in self.features.iter().filter(|f| !f.bounds.intersects(visible)) {
+            let (x, y) = bounds_center(&feature.bounds);
+            let distance = (x - center.0).hypot(y - center.1);
+            let entry = tiles.entry(feature_tile(&feature.bounds, zoom)).or_insert((0, 0.0));
+            entry.0 += 1;
+            entry.1 = entry.1.max(distance);
+        }
+
+        let mut candidates: Vec<_> = tiles.into_iter().collect();
+        candidates.sort_by(|a, b| b.1.1.total_cmp(&a.1.1));
+
+        let mut excess = self.features.len() - budget;
+        let mut evicted = HashSet::new();
+        for (tile, (count, _)) in candidates {
+            if excess == 0 {
+                break;
+            }
+            evicted.insert(tile);
+            excess = excess.saturating_sub(count);
+        }
+
+        self.retain(|feature| {
+            feature.bounds.intersects(visible) || !evicted.contains(&feature_tile(&feature.bounds, zoom))
+        });
+        evicted.into_iter().collect()
+    }
+
+    fn recalculate_bounds(&mut self) {
+        self.bounds = self
+            .features
+            .iter()
+            .map(|feature| feature.bounds.clone())
+            .reduce(|acc, bounds| merge_bounds(&acc, &bounds));
+    }
+}
+
+fn bounds_center(bounds: &GeoBounds) -> (f64, f64) {
+    ((bounds.min_x + bounds.max_x) / 2.0, (bounds.min_y + bounds.max_y) / 2.0)
+}
+
+// Tile holding the feature's center, on the Web Mercator grid `Viewport::get_required_tiles` uses by default
+fn feature_tile(bounds: &GeoBounds, zoom: u8) -> (u32, u32, u8) {
+    let z = zoom.min(20);
+    let (lng, lat) = bounds_center(bounds);
+    let (x, y) = tile_at(&WebMercator, lng, lat, z);
+    (x, y, z)
+}
+
+fn merge_bounds(a: &GeoBounds, b: &GeoBounds) -> GeoBounds {
+    GeoBounds::new(
+        a.min_x.min(b.min_x),
+        a.min_y.min(b.min_y),
+        a.max_x.max(b.max_x),
+        a.max_y.max(b.max_y),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{FeatureGeometry, GeoPoint};
+    use dashmap::DashMap;
+
+    fn point_feature(id: geojson::feature::Id, lng: f64) -> GeoFeature {
+        GeoFeature::new(
+            FeatureId::from_geojson_id(&id),
+            FeatureGeometry::Point(GeoPoint::new(0.0, lng)),
+            DashMap::new(),
+        )
+    }
+
+    #[test]
+    fn test_numeric_and_string_ids_do_not_collide() {
+        let mut scene = Scene::new();
+        scene.add_feature(point_feature(geojson::feature::Id::Number(1.into()), 10.0));
+        scene.add_feature(point_feature(geojson::feature::Id::String("1".to_string()), 20.0));
+
+        let numeric = scene.get(&FeatureId::from(1u64)).unwrap();
+        let string = scene.get(&FeatureId::from("1")).unwrap();
+        assert_eq!(numeric.bounds.min_x, 10.0);
+        assert_eq!(string.bounds.min_x, 20.0);
+        assert_eq!(numeric.id.to_string(), string.id.to_string());
+    }
+
+    #[test]
+    fn test_duplicate_id_policies() {
+        let x = || geojson::feature::Id::String("x".to_string());
+
+        let mut renamed = Scene::new();
+        renamed.add_feature_with(point_feature(x(), 10.0), DuplicateIdPolicy::Rename).unwrap();
+        renamed.add_feature_with(point_feature(x(), 20.0), DuplicateIdPolicy::Rename).unwrap();
+        let ids: Vec<String> = renamed.features().iter().map(|f| f.id.to_string()).collect();
+        assert_eq!(ids, vec!["x", "x-1"]);
+        assert_eq!(renamed.get(&FeatureId::from("x-1")).unwrap().bounds.min_x, 20.0);
+
+        let mut first = Scene::new();
+        first.add_feature_with(point_feature(x(), 10.0), DuplicateIdPolicy::KeepFirst).unwrap();
+        first.add_feature_with(point_feature(x(), 20.0), DuplicateIdPolicy::KeepFirst).unwrap();
+        assert_eq!(first.len(), 1);
+        assert_eq!(first.get(&FeatureId::from("x")).unwrap().bounds.min_x, 10.0);
+
+        let mut last = Scene::new();
+        last.add_feature(point_feature(x(), 10.0));
+        last.add_feature(point_feature(x(), 20.0));
+        assert_eq!(last.len(), 1);
+        assert_eq!(last.bounds().unwrap().min_x, 20.0);
+
+        let mut strict = Scene::new();
+        strict.add_feature_with(point_feature(x(), 10.0), DuplicateIdPolicy::Error).unwrap();
+        assert!(strict.add_feature_with(point_feature(x(), 20.0), DuplicateIdPolicy::Error).is_err());
+    }
+}
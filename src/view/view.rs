@@ -1,16 +1,23 @@
 use crate::error::GeoArrowError;
-use crate::model::{Bounds, GeoArrowFile, GeoArrowResult};
-use std::sync::Arc;
+use crate::model::{
+    features_from_collection, features_from_geojson_str, Bounds, DataSource, DuplicateIdPolicy, FeatureId, GeoArrowFile,
+    GeoArrowResult, GeoBounds, GeoFeature, GeoPoint, Layer, LayerId, LayerStyle, LineStyle, LoadOptions, PixelSize,
+    PointStyle, PolygonStyle, Tile, TileStatus, Viewport,
+};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::model::map_local_file;
+use crate::view::scene::Scene;
+use crate::engine::target::DrawTarget;
+use crate::engine::projection::{tiles_covering, Projection, WebMercator};
+use crate::engine::tiles::{render_tile, TileCache, TileInfo, TilePlaceholder, TileScheduler, MAX_TILE_ZOOM};
+use crate::engine::raster::RasterTarget;
+use crate::engine::spatial::ViewportIndex;
+use crate::engine::{now_ms, render_clipped, render_feature_batch, render_feature_geometry, transforms, FrameClock, RenderContext};
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
 use web_sys::wasm_bindgen::JsCast;
-use winit::window::Window;
-struct State {
-    window: Arc<Window>,
-    device: wgpu::Device,
-    queue: wgpu::Queue,
-    surface: wgpu::Surface<'static>,
-    size: winit::dpi::PhysicalSize<u32>,
-    surface_format: wgpu::TextureFormat,
-}
+use crate::view::gpu::{self, State};
 #[derive(Clone)]
 pub struct MapStyle {
     pub point_color: String,
@@ -19,6 +26,13 @@ pub struct MapStyle {
     pub polygon_stroke: String,
     pub point_radius: f64,
     pub line_width: f64,
+    /// Color of the message drawn when loading the map fails
+    pub error_color: String,
+    /// Smooth images and keep sub-pixel coordinates; when false, draws are snapped to whole
+    /// pixels and image smoothing is turned off for crisp, aliased output
+    pub antialias: bool,
+    /// Drawn in place of tiles that failed to load
+    pub tile_placeholder: TilePlaceholder,
 }
 impl Default for MapStyle {
     fn default() -> Self {
@@ -29,11 +43,312 @@ impl Default for MapStyle {
             polygon_stroke: "#00FF00".to_string(),
             point_radius: 3.0,
             line_width: 2.0,
+            error_color: "#cc0000".to_string(),
+            antialias: true,
+            tile_placeholder: TilePlaceholder::default(),
+        }
+    }
+}
+
+// Layer styling equivalent to the flat map style: the colors already carry any transparency,
+// so every opacity is 1.0
+impl From<&MapStyle> for LayerStyle {
+    fn from(style: &MapStyle) -> Self {
+        let defaults = LayerStyle::default();
+        LayerStyle {
+            point_style: PointStyle { color: style.point_color.clone(), radius: style.point_radius, opacity: 1.0 },
+            line_style: LineStyle {
+                color: style.line_color.clone(),
+                width: style.line_width,
+                opacity: 1.0,
+                ..defaults.line_style
+            },
+            polygon_style: PolygonStyle {
+                fill_color: style.polygon_fill.clone(),
+                stroke_color: style.polygon_stroke.clone(),
+                stroke_width: style.line_width,
+                fill_opacity: 1.0,
+                stroke_opacity: 1.0,
+                ..defaults.polygon_style
+            },
+            ..defaults
+        }
+    }
+}
+
+// Loading spinner: radius in pixels, sweep of the visible arc and turns per second
+const SPINNER_RADIUS: f64 = 16.0;
+const SPINNER_SWEEP: f64 = 1.5 * std::f64::consts::PI;
+const SPINNER_TURNS_PER_SECOND: f64 = 1.0;
+
+// Feature count above which rendering switches from canvas-2d to the GPU backend
+pub const DEFAULT_BACKEND_THRESHOLD: usize = 50_000;
+
+// Number of tiles a MapView keeps cached
+pub const DEFAULT_TILE_CACHE_SIZE: usize = 256;
+
+// Whether the view's data file has been loaded
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum MapViewStatus {
+    /// Nothing loaded yet
+    #[default]
+    Empty,
+    /// `MapView::load` is in progress
+    Loading,
+    /// The file's features are in the scene
+    Ready,
+    /// The last load failed with this message
+    Error(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RenderBackend {
+    Canvas,
+    Gpu,
+}
+
+// Entry points for each backend, so `MapView::render_with` can dispatch on the selected one.
+// Each returns the backend that actually drew, which differs when a dispatch falls back.
+pub trait BackendDispatch {
+    fn render_canvas(&self, view: &MapView) -> GeoArrowResult<RenderBackend>;
+    fn render_gpu(&self, view: &MapView) -> GeoArrowResult<RenderBackend>;
+}
+
+// Dispatch onto a DOM canvas. A canvas id carries no wgpu surface, so the GPU path falls back
+// to canvas-2d; use `MapView::render_to_gpu` with a `State` for GPU rendering.
+struct CanvasDispatch<'a> {
+    canvas_id: &'a str,
+}
+
+impl BackendDispatch for CanvasDispatch<'_> {
+    fn render_canvas(&self, view: &MapView) -> GeoArrowResult<RenderBackend> {
+        view.render_to_canvas(self.canvas_id)?;
+        Ok(RenderBackend::Canvas)
+    }
+
+    fn render_gpu(&self, view: &MapView) -> GeoArrowResult<RenderBackend> {
+        tracing::warn!("GPU backend unavailable, falling back to canvas-2d");
+        self.render_canvas(view)
+    }
+}
+
+// Offscreen buffer with every feature drawn in a color encoding its position in `ids`
+struct PickBuffer {
+    target: RasterTarget,
+    ids: Vec<FeatureId>,
+}
+
+impl PickBuffer {
+    // Index 0 is reserved for "no feature", so feature i is drawn as color i + 1
+    fn encode(index: usize) -> String {
+        format!("#{:06x}", index + 1)
+    }
+
+    fn decode([r, g, b, a]: [u8; 4]) -> Option<usize> {
+        let value = (r as usize) << 16 | (g as usize) << 8 | b as usize;
+        (a == 255 && value > 0).then(|| value - 1)
+    }
+}
+
+// Property-based draw order applied across all layers
+#[derive(Clone, Debug, PartialEq)]
+pub struct SortKey {
+    pub property: String,
+    pub descending: bool,
+}
+
+impl SortKey {
+    pub fn ascending(property: &str) -> Self {
+        SortKey { property: property.to_string(), descending: false }
+    }
+
+    pub fn descending(property: &str) -> Self {
+        SortKey { property: property.to_string(), descending: true }
+    }
+
+    // Values of different JSON types order null < bool < number < string < array < object,
+    // so any mix of types sorts consistently; features without the property draw last
+    fn compare(&self, a: &GeoFeature, b: &GeoFeature) -> std::cmp::Ordering {
+        use std::cmp::Ordering;
+
+        match (a.properties.get(&self.property), b.properties.get(&self.property)) {
+            (Some(a), Some(b)) => {
+                let ordering = compare_values(&a, &b);
+                if self.descending { ordering.reverse() } else { ordering }
+            }
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+    }
+}
+
+// Total order over JSON values: by type rank first, then by value, with arrays and objects
+// compared element by element
+fn compare_values(a: &serde_json::Value, b: &serde_json::Value) -> std::cmp::Ordering {
+    use serde_json::Value;
+
+    let rank = |value: &Value| match value {
+        Value::Null => 0,
+        Value::Bool(_) => 1,
+        Value::Number(_) => 2,
+        Value::String(_) => 3,
+        Value::Array(_) => 4,
+        Value::Object(_) => 5,
+    };
+    match (a, b) {
+        (Value::Bool(x), Value::Bool(y)) => x.cmp(y),
+        (Value::Number(x), Value::Number(y)) => {
+            x.as_f64().unwrap_or(f64::NAN).total_cmp(&y.as_f64().unwrap_or(f64::NAN))
         }
+        (Value::String(x), Value::String(y)) => x.cmp(y),
+        (Value::Array(x), Value::Array(y)) => x
+            .iter()
+            .zip(y)
+            .map(|(x, y)| compare_values(x, y))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| x.len().cmp(&y.len())),
+        (Value::Object(x), Value::Object(y)) => x
+            .iter()
+            .zip(y)
+            .map(|((kx, x), (ky, y))| kx.cmp(ky).then_with(|| compare_values(x, y)))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| x.len().cmp(&y.len())),
+        _ => rank(a).cmp(&rank(b)),
     }
 }
 
+// Summary of a numeric property across the retained scene, for legends and binning
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PropertyStats {
+    pub count: usize,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    /// Population standard deviation
+    pub stddev: f64,
+}
+
+impl PropertyStats {
+    fn from_values(values: &[f64]) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+        let count = values.len();
+        let mean = values.iter().sum::<f64>() / count as f64;
+        let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / count as f64;
+        Some(PropertyStats {
+            count,
+            min: values.iter().copied().fold(f64::INFINITY, f64::min),
+            max: values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            mean,
+            stddev: variance.sqrt(),
+        })
+    }
+}
+
+// Interaction events accumulated by `MapView::queue_event` until `MapView::process_events`
+#[derive(Clone, Debug, PartialEq)]
+pub enum MapEvent {
+    /// Move the center by a delta in degrees (longitude, latitude)
+    Pan { dx: f64, dy: f64 },
+    /// Jump to an absolute zoom level
+    Zoom(u8),
+    /// Pointer moved to a screen position
+    Hover { x: f64, y: f64 },
+}
+
+// Outcome of a `MapView::tick`: the layers reloaded and the layers whose reload failed
+#[derive(Debug, Default)]
+pub struct TickReport {
+    pub reloaded: Vec<LayerId>,
+    pub failed: Vec<(LayerId, GeoArrowError)>,
+}
+
+// Swap a layer's features for a fresh load, resolving id clashes with `policy`. Under
+// `DuplicateIdPolicy::Error` a clash fails before anything is replaced.
+fn replace_layer_features(
+    scene: &mut Scene,
+    entry: &mut LayerEntry,
+    features: Vec<GeoFeature>,
+    policy: DuplicateIdPolicy,
+) -> GeoArrowResult<()> {
+    let stale: HashSet<FeatureId> = entry.feature_ids.drain(..).collect();
+    if policy == DuplicateIdPolicy::Error {
+        let mut seen = HashSet::new();
+        let clash = features.iter()
+            .find(|feature| !seen.insert(&feature.id) || (!stale.contains(&feature.id) && scene.get(&feature.id).is_some()));
+        if let Some(feature) = clash {
+            entry.feature_ids.extend(stale);
+            return Err(GeoArrowError::Serialization(format!("Duplicate feature id: {}", feature.id)));
+        }
+    }
+
+    scene.retain(|feature| !stale.contains(&feature.id));
+    let mut added = HashSet::new();
+    for feature in features {
+        if let Some(id) = scene.add_feature_with(feature, policy)?
+            && added.insert(id.clone())
+        {
+            entry.feature_ids.push(id);
+        }
+    }
+    Ok(())
+}
+
+// Fetches a layer's features from its data source, so `MapView::tick_with` can be driven by a stub
+pub trait LayerLoader {
+    fn load(&self, layer: &Layer) -> GeoArrowResult<Vec<GeoFeature>>;
+}
+
+// Synchronous loader for local and in-memory GeoJSON sources
+pub struct SourceLoader;
+
+impl LayerLoader for SourceLoader {
+    fn load(&self, layer: &Layer) -> GeoArrowResult<Vec<GeoFeature>> {
+        let content = match &layer.data_source {
+            DataSource::Local(path) => return load_local_layer(layer, path),
+            DataSource::Memory(bytes) => String::from_utf8(bytes.clone()).map_err(|e| {
+                GeoArrowError::Serialization(format!("Layer {} is not UTF-8: {}", layer.id, e))
+            })?,
+            DataSource::Http(url) => {
+                return Err(GeoArrowError::Io(format!(
+                    "Remote layer {} ({}) cannot be refreshed synchronously",
+                    layer.id, url
+                )));
+            }
+        };
+        features_from_geojson_str(&content, &LoadOptions::default())
+    }
+}
+
+// Local layer files are parsed straight from a memory map, avoiding a heap copy of the file
+#[cfg(not(target_arch = "wasm32"))]
+fn load_local_layer(layer: &Layer, path: &std::path::Path) -> GeoArrowResult<Vec<GeoFeature>> {
+    let map = map_local_file(path)
+        .map_err(|e| GeoArrowError::Io(format!("Failed to read layer {}: {}", layer.id, e)))?;
+    let content = std::str::from_utf8(&map)
+        .map_err(|e| GeoArrowError::Serialization(format!("Layer {} is not UTF-8: {}", layer.id, e)))?;
+    features_from_geojson_str(content, &LoadOptions::default())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn load_local_layer(layer: &Layer, path: &std::path::Path) -> GeoArrowResult<Vec<GeoFeature>> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| GeoArrowError::Io(format!("Failed to read layer {}: {}", layer.id, e)))?;
+    features_from_geojson_str(&content, &LoadOptions::default())
+}
+
+// A layer registered with a view, with the features it contributed and when it last loaded
+#[derive(Debug)]
+struct LayerEntry {
+    layer: Layer,
+    feature_ids: Vec<FeatureId>,
+    last_loaded: Option<Duration>,
+}
+
 #[wasm_bindgen::prelude::wasm_bindgen]
+#[allow(dead_code)]
 pub struct MapView {
     position: (f64, f64),
     zoom: u8,
@@ -41,6 +356,36 @@ pub struct MapView {
     id: i32,
     geoarrow_file: GeoArrowFile,
     style: MapStyle,
+    scene: Scene,
+    backend_threshold: usize,
+    layers: Vec<LayerEntry>,
+    feature_budget: Option<usize>,
+    evicted_tiles: Vec<(u32, u32, u8)>,
+    events: Vec<MapEvent>,
+    hover_position: Option<(f64, f64)>,
+    tile_cache: TileCache,
+    tile_requests: TileScheduler,
+    pick_buffer: Option<PickBuffer>,
+    global_sort: Option<SortKey>,
+    status: MapViewStatus,
+    hidden: HashSet<FeatureId>,
+    loading_indicator: bool,
+    frame_time: Duration,
+    view_index: ViewportIndex,
+    hit_tolerance: (f64, f64),
+    selected: HashSet<FeatureId>,
+    hovered: Option<FeatureId>,
+    raise_interactive: bool,
+    frame_budget_ms: Option<f64>,
+    frame_clock: FrameClock,
+    draw_cap: Option<usize>,
+    coord_quantum: Option<f64>,
+    densify_threshold: Option<f64>,
+    world_copies: bool,
+    clip_mask: Option<Vec<(f64, f64)>>,
+    duplicate_ids: DuplicateIdPolicy,
+    // Index into the draw order where a pass cut short by the frame budget continues
+    render_resume: Cell<usize>,
 }
 
 impl Default for MapView {
@@ -56,6 +401,35 @@ impl Default for MapView {
                 "2023-01-01".to_string(),
             ),
             style: MapStyle::default(),
+            scene: Scene::new(),
+            backend_threshold: DEFAULT_BACKEND_THRESHOLD,
+            layers: Vec::new(),
+            feature_budget: None,
+            evicted_tiles: Vec::new(),
+            events: Vec::new(),
+            hover_position: None,
+            tile_cache: TileCache::new(DEFAULT_TILE_CACHE_SIZE),
+            tile_requests: TileScheduler::new((0.0, 0.0)),
+            pick_buffer: None,
+            global_sort: None,
+            status: MapViewStatus::Empty,
+            hidden: HashSet::new(),
+            loading_indicator: true,
+            frame_time: Duration::ZERO,
+            view_index: ViewportIndex::default(),
+            hit_tolerance: (0.0, 0.0),
+            selected: HashSet::new(),
+            hovered: None,
+            raise_interactive: false,
+            frame_budget_ms: None,
+            frame_clock: now_ms,
+            draw_cap: None,
+            coord_quantum: None,
+            densify_threshold: None,
+            world_copies: false,
+            clip_mask: None,
+            duplicate_ids: DuplicateIdPolicy::default(),
+            render_resume: Cell::new(0),
         }
     }
 }
@@ -69,9 +443,77 @@ impl MapView {
             bounds: None,
             position,
             style: MapStyle::default(),
+            scene: Scene::new(),
+            backend_threshold: DEFAULT_BACKEND_THRESHOLD,
+            layers: Vec::new(),
+            feature_budget: None,
+            evicted_tiles: Vec::new(),
+            events: Vec::new(),
+            hover_position: None,
+            tile_cache: TileCache::new(DEFAULT_TILE_CACHE_SIZE),
+            tile_requests: TileScheduler::new((0.0, 0.0)),
+            pick_buffer: None,
+            global_sort: None,
+            status: MapViewStatus::Empty,
+            hidden: HashSet::new(),
+            loading_indicator: true,
+            frame_time: Duration::ZERO,
+            view_index: ViewportIndex::default(),
+            hit_tolerance: (0.0, 0.0),
+            selected: HashSet::new(),
+            hovered: None,
+            raise_interactive: false,
+            frame_budget_ms: None,
+            frame_clock: now_ms,
+            draw_cap: None,
+            coord_quantum: None,
+            densify_threshold: None,
+            world_copies: false,
+            clip_mask: None,
+            duplicate_ids: DuplicateIdPolicy::default(),
+            render_resume: Cell::new(0),
         }
     }
 
+    pub fn status(&self) -> &MapViewStatus {
+        &self.status
+    }
+
+    /// Draw an animated spinner while loading instead of a "Loading…" label. The spinner turns
+    /// with the time passed to `tick`.
+    pub fn set_loading_indicator(&mut self, enabled: bool) {
+        self.loading_indicator = enabled;
+    }
+
+    /// Open the view's data file and load its features into the scene, moving through
+    /// `Loading` to `Ready`, or to `Error` with the failure message.
+    pub async fn load(&mut self) -> GeoArrowResult<()> {
+        self.status = MapViewStatus::Loading;
+        let result = self.load_file().await;
+        self.status = match &result {
+            Ok(()) => MapViewStatus::Ready,
+            Err(e) => MapViewStatus::Error(e.to_string()),
+        };
+        result
+    }
+
+    async fn load_file(&mut self) -> GeoArrowResult<()> {
+        self.geoarrow_file.open().await?;
+        let collection = self.geoarrow_file.get_features().await?;
+        self.load_collection(&collection, &LoadOptions::default())
+    }
+
+    /// Center the view on a geographic point. Unlike the `(x, y)` position tuple, the point
+    /// names its latitude and longitude explicitly.
+    pub fn with_center(mut self, center: GeoPoint) -> Self {
+        self.position = (center.lng, center.lat);
+        self
+    }
+
+    pub fn center(&self) -> GeoPoint {
+        GeoPoint::new(self.position.1, self.position.0)
+    }
+
     pub fn set_position(&mut self, position: (f64, f64)) {
         self.position = position;
     }
@@ -88,6 +530,669 @@ impl MapView {
         self.zoom
     }
 
+    pub fn tile_cache(&self) -> &TileCache {
+        &self.tile_cache
+    }
+
+    /// Queue the tiles covering a `canvas_size` view that aren't cached yet, replacing any
+    /// requests left from the previous view. Views deeper than `MAX_TILE_ZOOM` use its tiles.
+    pub fn request_tiles(&mut self, canvas_size: (f64, f64)) {
+        let bounds = self.render_bounds(canvas_size);
+        let z = self.zoom.min(MAX_TILE_ZOOM);
+        let center = self.bounds.as_ref().map_or(self.center(), GeoBounds::center);
+        let (x, y) = WebMercator.to_tile_space(center.lng, center.lat);
+        let tile_count = (1u32 << z) as f64;
+        self.tile_requests = TileScheduler::new((x * tile_count, y * tile_count));
+        for (x, y, z) in tiles_covering(&WebMercator, &bounds, z) {
+            let tile = TileInfo::at(x, y, z);
+            if !self.tile_cache.contains(&tile.id) {
+                self.tile_requests.schedule(tile);
+            }
+        }
+    }
+
+    /// Next tile to fetch, nearest the view center first. The tile is cached as `Loading`
+    /// until `tile_loaded` records its features.
+    pub fn next_tile_request(&mut self) -> Option<TileInfo> {
+        let request = self.tile_requests.next_tile()?;
+        let mut tile = Tile::new(request.x, request.y, request.z);
+        tile.status = TileStatus::Loading;
+        self.tile_cache.insert(request.id, tile);
+        Some(request)
+    }
+
+    /// Number of queued tile requests
+    pub fn pending_tile_requests(&self) -> usize {
+        self.tile_requests.len()
+    }
+
+    /// Cache the features fetched for a requested tile
+    pub fn tile_loaded(&mut self, request: TileInfo, features: Vec<GeoFeature>) {
+        let mut tile = Tile::new(request.x, request.y, request.z);
+        tile.features = features;
+        tile.status = TileStatus::Loaded;
+        self.tile_cache.insert(request.id, tile);
+    }
+
+    /// Record that fetching a requested tile failed (404, network error, ...); the tile then
+    /// renders the style's `tile_placeholder` instead of leaving a gap
+    pub fn tile_failed(&mut self, request: TileInfo, reason: &str) {
+        if !self.tile_cache.mark_failed(&request.id, reason) {
+            let mut tile = Tile::new(request.x, request.y, request.z);
+            tile.mark_failed(reason);
+            self.tile_cache.insert(request.id, tile);
+        }
+    }
+
+    /// Release everything the view holds between renders: cached tiles, retained features,
+    /// layers and pending events. Call this before dropping a view in a long-running page;
+    /// wasm-bindgen handles are not dropped deterministically, so resources would otherwise
+    /// linger until the JS wrapper is collected. The view stays usable but starts empty.
+    pub fn dispose(&mut self) {
+        self.tile_cache.clear();
+        self.tile_requests = TileScheduler::new((0.0, 0.0));
+        self.scene = Scene::new();
+        self.layers.clear();
+        self.events.clear();
+        self.evicted_tiles.clear();
+        self.hover_position = None;
+        self.pick_buffer = None;
+        self.status = MapViewStatus::Empty;
+        self.hidden.clear();
+        self.selected.clear();
+        self.hovered = None;
+        self.view_index = ViewportIndex::default();
+        self.render_resume.set(0);
+        tracing::debug!("Disposed map view {}", self.id);
+    }
+
+    /// Draw every visible feature in the order of `key`, regardless of layer z-index.
+    /// `None` restores per-layer ordering.
+    pub fn set_global_sort(&mut self, key: Option<SortKey>) {
+        self.global_sort = key;
+    }
+
+    /// Move selected and then hovered features to the end of the draw order, after the z-index
+    /// and global sort, so custom renderers draw them on top without a separate pass
+    pub fn set_raise_interactive(&mut self, raise: bool) {
+        self.raise_interactive = raise;
+        self.refresh_pick_buffer();
+    }
+
+    pub fn select_feature(&mut self, id: FeatureId) {
+        if self.selected.insert(id) && self.raise_interactive {
+            self.refresh_pick_buffer();
+        }
+    }
+
+    pub fn deselect_feature(&mut self, id: &FeatureId) {
+        if self.selected.remove(id) && self.raise_interactive {
+            self.refresh_pick_buffer();
+        }
+    }
+
+    pub fn clear_selection(&mut self) {
+        if !self.selected.is_empty() {
+            self.selected.clear();
+            if self.raise_interactive {
+                self.refresh_pick_buffer();
+            }
+        }
+    }
+
+    pub fn is_selected(&self, id: &FeatureId) -> bool {
+        self.selected.contains(id)
+    }
+
+    /// The feature under the pointer, if any
+    pub fn set_hovered_feature(&mut self, id: Option<FeatureId>) {
+        if self.hovered != id {
+            self.hovered = id;
+            if self.raise_interactive {
+                self.refresh_pick_buffer();
+            }
+        }
+    }
+
+    pub fn hovered_feature(&self) -> Option<&FeatureId> {
+        self.hovered.as_ref()
+    }
+
+    /// Features in the order they are drawn (last is topmost). Layers are drawn by ascending
+    /// z-index, skipping hidden ones, followed by features added outside any layer. With a global
+    /// sort, all of these are flattened into one list ordered by the sort key. Individually
+    /// hidden features are left out, and with `set_raise_interactive` selected features follow
+    /// the rest, with the hovered feature last.
+    pub fn draw_order(&self) -> Vec<&GeoFeature> {
+        let mut layers: Vec<&LayerEntry> = self.layers.iter().collect();
+        layers.sort_by_key(|entry| entry.layer.z_index);
+
+        let mut ordered = Vec::with_capacity(self.scene.len());
+        for entry in &layers {
+            if entry.layer.is_visible_at_zoom(self.zoom) {
+                ordered.extend(entry.feature_ids.iter().filter_map(|id| self.scene.get(id)));
+            }
+        }
+        let layered: HashSet<&FeatureId> = self.layers.iter().flat_map(|entry| &entry.feature_ids).collect();
+        ordered.extend(self.scene.features().iter().filter(|feature| !layered.contains(&feature.id)));
+        ordered.retain(|feature| !self.hidden.contains(&feature.id));
+
+        if let Some(key) = &self.global_sort {
+            ordered.sort_by(|a, b| key.compare(a, b));
+        }
+        if self.raise_interactive {
+            ordered.sort_by_key(|feature| {
+                (self.hovered.as_ref() == Some(&feature.id), self.selected.contains(&feature.id))
+            });
+        }
+        ordered
+    }
+
+    /// Render the pick buffer used by `pick_at`: every feature drawn offscreen in a unique color
+    /// with its layer's style, over the same extent `render_to_target` draws on a canvas of this
+    /// size. Rebuild it after the features or the view change.
+    pub fn update_pick_buffer(&mut self, width: u32, height: u32) -> GeoArrowResult<()> {
+        let canvas_size = (width as f64, height as f64);
+        let target = RasterTarget::new(width, height);
+        let mut base = RenderContext::new(self.render_bounds(canvas_size), canvas_size, self.zoom, self.style.clone());
+        base.style.antialias = false;
+        let layer_styles = self.layer_styles();
+        let mut ids = Vec::with_capacity(self.scene.len());
+        for (index, feature) in self.draw_order().into_iter().enumerate() {
+            let style = layer_styles.get(&feature.id).map_or_else(
+                || base.layer_style.clone(),
+                |style| style.at_zoom(self.zoom as f64),
+            );
+            let context = base.clone().with_layer_style(self.pick_style(style, &PickBuffer::encode(index)));
+            render_feature_geometry(feature.render_geometry()?, &context, &target)?;
+            ids.push(feature.id.clone());
+        }
+
+        self.pick_buffer = Some(PickBuffer { target, ids });
+        Ok(())
+    }
+
+    // `style` painted solid in a pick color, grown by the hit tolerance
+    fn pick_style(&self, mut style: LayerStyle, color: &str) -> LayerStyle {
+        style.point_style.color = color.to_string();
+        style.point_style.opacity = 1.0;
+        style.point_style.radius += self.hit_tolerance.0;
+        style.line_style.color = color.to_string();
+        style.line_style.opacity = 1.0;
+        style.line_style.width += 2.0 * self.hit_tolerance.1;
+        style.line_style.dash_pattern = None;
+        style.polygon_style.fill_color = color.to_string();
+        style.polygon_style.stroke_color = color.to_string();
+        style.polygon_style.fill_opacity = 1.0;
+        style.polygon_style.stroke_opacity = 1.0;
+        style.polygon_style.stroke_width += 2.0 * self.hit_tolerance.1;
+        style.shadow = None;
+        style
+    }
+
+    // Style of every feature loaded by a layer; other features use the map style
+    fn layer_styles(&self) -> HashMap<&FeatureId, &LayerStyle> {
+        self.layers.iter()
+            .flat_map(|entry| entry.feature_ids.iter().map(|id| (id, &entry.layer.style)))
+            .collect()
+    }
+
+    /// Id of the topmost feature under screen position (x, y), read in O(1) from the pick buffer.
+    /// Returns `None` outside the buffer or before `update_pick_buffer` has been called.
+    pub fn pick_at(&self, x: f64, y: f64) -> Option<FeatureId> {
+        let buffer = self.pick_buffer.as_ref()?;
+        let (width, height) = (buffer.target.width() as f64, buffer.target.height() as f64);
+        if !(0.0..width).contains(&x) || !(0.0..height).contains(&y) {
+            return None;
+        }
+        let index = PickBuffer::decode(buffer.target.pixel(x as u32, y as u32))?;
+        buffer.ids.get(index).cloned()
+    }
+
+    /// Grow each feature's clickable area by `points_px` around points and `lines_px` either side
+    /// of lines and polygon outlines, so small targets can be hit by touch. Rebuilds the pick buffer.
+    pub fn set_hit_tolerance(&mut self, points_px: f64, lines_px: f64) {
+        self.hit_tolerance = (points_px.max(0.0), lines_px.max(0.0));
+        self.refresh_pick_buffer();
+    }
+
+    /// The topmost visible feature under screen position (x, y), from the pick buffer
+    pub fn feature_at(&self, x: f64, y: f64) -> Option<&GeoFeature> {
+        let id = self.pick_at(x, y)?;
+        self.scene.get(&id).filter(|feature| !self.hidden.contains(&feature.id))
+    }
+
+    /// Hide a single feature from rendering and hit-testing, independently of its layer
+    pub fn hide_feature(&mut self, id: FeatureId) {
+        if self.hidden.insert(id) {
+            self.refresh_pick_buffer();
+        }
+    }
+
+    pub fn show_feature(&mut self, id: &FeatureId) {
+        if self.hidden.remove(id) {
+            self.refresh_pick_buffer();
+        }
+    }
+
+    /// Unhide every individually hidden feature
+    pub fn show_all(&mut self) {
+        if !self.hidden.is_empty() {
+            self.hidden.clear();
+            self.refresh_pick_buffer();
+        }
+    }
+
+    pub fn is_hidden(&self, id: &FeatureId) -> bool {
+        self.hidden.contains(id)
+    }
+
+    // Redraw an existing pick buffer at its current size so it reflects visibility changes
+    fn refresh_pick_buffer(&mut self) {
+        let Some(buffer) = &self.pick_buffer else {
+            return;
+        };
+        let (width, height) = (buffer.target.width(), buffer.target.height());
+        if let Err(e) = self.update_pick_buffer(width, height) {
+            tracing::warn!("Failed to refresh pick buffer: {}", e);
+        }
+    }
+
+    /// Queue an interaction event without touching view state
+    pub fn queue_event(&mut self, event: MapEvent) {
+        self.events.push(event);
+    }
+
+    /// Coalesce and apply the queued events: pan deltas are summed, and only the last zoom and
+    /// hover position are kept. Returns the applied events (at most one of each kind, in the
+    /// order pan, zoom, hover), so the caller can redraw once when the list is non-empty.
+    pub fn process_events(&mut self) -> Vec<MapEvent> {
+        let mut pan: Option<(f64, f64)> = None;
+        let mut zoom = None;
+        let mut hover = None;
+        for event in self.events.drain(..) {
+            match event {
+                MapEvent::Pan { dx, dy } => {
+                    let (sum_x, sum_y) = pan.unwrap_or((0.0, 0.0));
+                    pan = Some((sum_x + dx, sum_y + dy));
+                }
+                MapEvent::Zoom(level) => zoom = Some(level),
+                MapEvent::Hover { x, y } => hover = Some((x, y)),
+            }
+        }
+
+        let mut applied = Vec::new();
+        if let Some((dx, dy)) = pan {
+            self.position = (self.position.0 + dx, self.position.1 + dy);
+            applied.push(MapEvent::Pan { dx, dy });
+        }
+        if let Some(level) = zoom {
+            self.zoom = level;
+            applied.push(MapEvent::Zoom(level));
+        }
+        if let Some((x, y)) = hover {
+            self.hover_position = Some((x, y));
+            applied.push(MapEvent::Hover { x, y });
+        }
+        applied
+    }
+
+    pub fn hover_position(&self) -> Option<(f64, f64)> {
+        self.hover_position
+    }
+
+    pub fn add_feature(&mut self, feature: GeoFeature) {
+        let is_new = self.scene.get(&feature.id).is_none();
+        if is_new {
+            self.view_index.insert(&feature);
+        }
+        self.scene.add_feature(feature);
+        if self.enforce_feature_budget() || !is_new {
+            self.reindex_view();
+        }
+    }
+
+    /// Set the geographic extent currently on screen, used to decide which features to keep
+    /// resident under a feature budget and which are listed by `features_in_view`.
+    pub fn set_view_bounds(&mut self, bounds: Bounds) {
+        self.view_index.set_view(&bounds);
+        self.bounds = Some(bounds);
+        if self.enforce_feature_budget() {
+            self.reindex_view();
+        }
+    }
+
+    /// Ids of the features whose bounds intersect the view bounds; empty until view bounds are
+    /// set. Kept up to date incrementally as the view pans.
+    pub fn features_in_view(&self) -> &HashSet<FeatureId> {
+        self.view_index.in_view()
+    }
+
+    // Rebuild the in-view index after the retained features changed
+    fn reindex_view(&mut self) {
+        self.view_index = ViewportIndex::new(self.scene.features(), self.bounds.as_ref());
+    }
+
+    /// Cap the number of resident features. Once exceeded, off-screen features are evicted
+    /// tile by tile; on-screen features are never evicted. Has no effect until view bounds are set.
+    pub fn set_feature_budget(&mut self, budget: usize) {
+        self.feature_budget = Some(budget);
+        if self.enforce_feature_budget() {
+            self.reindex_view();
+        }
+    }
+
+    /// Tiles whose features were evicted to stay within the feature budget
+    pub fn evicted_tiles(&self) -> &[(u32, u32, u8)] {
+        &self.evicted_tiles
+    }
+
+    // Returns whether any features were evicted
+    fn enforce_feature_budget(&mut self) -> bool {
+        let (Some(budget), Some(visible)) = (self.feature_budget, self.bounds.as_ref()) else {
+            return false;
+        };
+        let evicted = self.scene.evict_offscreen(visible, budget, self.zoom);
+        for &tile in &evicted {
+            if !self.evicted_tiles.contains(&tile) {
+                self.evicted_tiles.push(tile);
+            }
+        }
+        !evicted.is_empty()
+    }
+
+    /// Convert a parsed collection with the given options and add it to the retained scene
+    pub fn load_collection(
+        &mut self,
+        collection: &geojson::FeatureCollection,
+        options: &LoadOptions,
+    ) -> GeoArrowResult<()> {
+        for feature in features_from_collection(collection, options)? {
+            self.scene.add_feature_with(feature, options.duplicate_ids)?;
+        }
+        self.enforce_feature_budget();
+        self.reindex_view();
+        Ok(())
+    }
+
+    pub fn features(&self) -> &[GeoFeature] {
+        self.scene.features()
+    }
+
+    /// Statistics of `key` over the retained features. Numbers and numeric strings count; other
+    /// values and features without the property are ignored. `None` when nothing is numeric.
+    pub fn property_stats(&self, key: &str) -> Option<PropertyStats> {
+        let values: Vec<f64> = self
+            .features()
+            .iter()
+            .filter_map(|feature| {
+                let value = feature.properties.get(key)?;
+                match value.value() {
+                    serde_json::Value::Number(n) => n.as_f64(),
+                    serde_json::Value::String(s) => s.trim().parse().ok(),
+                    _ => None,
+                }
+            })
+            .filter(|v: &f64| v.is_finite())
+            .collect();
+        PropertyStats::from_values(&values)
+    }
+
+    /// Split multi-geometry features into separately selectable single-geometry features.
+    /// Parts get ids `<id>-0`, `<id>-1`, ... and share the parent's properties.
+    pub fn explode_features(&mut self) -> GeoArrowResult<()> {
+        self.scene.explode()?;
+        self.enforce_feature_budget();
+        self.reindex_view();
+        Ok(())
+    }
+
+    /// Register a layer. Its features are loaded on the next `tick`.
+    pub fn add_layer(&mut self, layer: Layer) {
+        self.layers.push(LayerEntry { layer, feature_ids: Vec::new(), last_loaded: None });
+    }
+
+    /// Reload every layer whose refresh interval has elapsed at `now` (a monotonic clock
+    /// reading, e.g. `performance.now()`), reporting which layers were reloaded and which failed.
+    pub fn tick(&mut self, now: Duration) -> TickReport {
+        self.tick_with(now, &SourceLoader)
+    }
+
+    /// `tick` with an explicit loader. Only the features of reloaded layers are replaced; a layer
+    /// that fails to load keeps its features and is retried on the next tick.
+    pub fn tick_with(&mut self, now: Duration, loader: &dyn LayerLoader) -> TickReport {
+        self.frame_time = now;
+        let policy = self.duplicate_ids;
+        let mut report = TickReport::default();
+        for entry in &mut self.layers {
+            if !entry.layer.needs_refresh(entry.last_loaded, now) {
+                continue;
+            }
+
+            let loaded = loader.load(&entry.layer)
+                .and_then(|features| replace_layer_features(&mut self.scene, entry, features, policy));
+            match loaded {
+                Ok(()) => {
+                    entry.last_loaded = Some(now);
+                    report.reloaded.push(entry.layer.id.clone());
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to reload layer {}: {}", entry.layer.id, e);
+                    report.failed.push((entry.layer.id.clone(), e));
+                }
+            }
+        }
+        if self.enforce_feature_budget() || !report.reloaded.is_empty() {
+            self.reindex_view();
+        }
+        report
+    }
+
+    /// How reloaded layer features whose id is already taken are resolved
+    pub fn set_duplicate_id_policy(&mut self, policy: DuplicateIdPolicy) {
+        self.duplicate_ids = policy;
+    }
+
+    pub fn feature(&self, id: &FeatureId) -> Option<&GeoFeature> {
+        self.scene.get(id)
+    }
+
+    pub fn scene_bounds(&self) -> Option<&GeoBounds> {
+        self.scene.bounds()
+    }
+
+    /// Apply `f` to every retained feature in place (derive properties, reproject, ...).
+    /// Feature and scene bounds are recomputed afterwards.
+    pub fn transform_features(&mut self, f: impl FnMut(&mut GeoFeature)) {
+        self.scene.transform_features(f);
+        self.reindex_view();
+    }
+
+    /// Draw the map onto any `DrawTarget` of the given pixel size. Runs inside a `render` span
+    /// carrying the feature count and zoom so slow frames can be traced.
+    pub fn render_to_target(&self, target: &dyn DrawTarget, canvas_size: (f64, f64)) -> GeoArrowResult<()> {
+        let span = tracing::info_span!("render", feature_count = self.scene.len(), zoom = self.zoom);
+        let _guard = span.enter();
+
+        let (width, height) = canvas_size;
+        // A pass the frame budget cut short continues on top of what it already drew
+        let resume = self.render_resume.get();
+
+        // Clear canvas
+        if resume == 0 {
+            target.clear_rect(0.0, 0.0, width, height);
+            // Basemap tiles go underneath everything else
+            self.render_tiles(target, canvas_size)?;
+        }
+
+        // Set up basic styling
+        target.set_image_smoothing_enabled(self.style.antialias);
+        target.set_fill_style(&self.style.polygon_fill);
+        target.set_stroke_style(&self.style.polygon_stroke);
+        target.set_line_width(self.style.line_width);
+
+        let center_x = width / 2.0;
+        let center_y = height / 2.0;
+
+        // Nothing to draw yet: show what the view is waiting on instead
+        match &self.status {
+            MapViewStatus::Loading if self.loading_indicator => {
+                let turns = self.frame_time.as_secs_f64() * SPINNER_TURNS_PER_SECOND;
+                let start = turns.fract() * std::f64::consts::TAU;
+                target.set_stroke_style(&self.style.line_color);
+                target.set_line_width(self.style.line_width);
+                target.begin_path();
+                target.arc(center_x, center_y, SPINNER_RADIUS, start, start + SPINNER_SWEEP)?;
+                target.stroke();
+                return Ok(());
+            }
+            MapViewStatus::Loading => {
+                target.set_fill_style(&self.style.line_color);
+                return target.fill_text("Loading…", center_x, center_y);
+            }
+            MapViewStatus::Error(message) => {
+                target.set_fill_style(&self.style.error_color);
+                return target.fill_text(message, center_x, center_y);
+            }
+            MapViewStatus::Empty | MapViewStatus::Ready => {}
+        }
+
+        let features = self.draw_order();
+        if features.is_empty() {
+            // Crosshair marking the map center on an empty map
+            target.begin_path();
+            target.move_to(center_x - 10.0, center_y);
+            target.line_to(center_x + 10.0, center_y);
+            target.move_to(center_x, center_y - 10.0);
+            target.line_to(center_x, center_y + 10.0);
+            target.stroke();
+            return Ok(());
+        }
+
+        // Layer features are drawn with their layer's style, everything else with the map style
+        let layer_styles = self.layer_styles();
+        let style_of = |feature: &GeoFeature| layer_styles.get(&feature.id).copied();
+        let mut base = RenderContext::new(self.render_bounds(canvas_size), canvas_size, self.zoom, self.style.clone())
+            .with_clock(self.frame_clock);
+        base.coord_quantum = self.coord_quantum;
+        base.densify_threshold = self.densify_threshold;
+        base.world_copies = self.world_copies;
+        let same_style = |a: &&GeoFeature, b: &&GeoFeature| match (style_of(a), style_of(b)) {
+            (Some(a), Some(b)) => std::ptr::eq(a, b),
+            (a, b) => a.is_none() && b.is_none(),
+        };
+        if let Some(mask) = &self.clip_mask {
+            base = base.with_clip_mask(mask.clone());
+        }
+        // The scene shrank since the pass was cut short: start over
+        let start = if resume < features.len() { resume } else { 0 };
+        let pass_start = (self.frame_clock)();
+        // Where the frame budget cut the pass short, if it did
+        let stopped = Cell::new(None);
+        render_clipped(&base, target, &|target| {
+            let mut offset = start;
+            for run in features[start..].chunk_by(same_style) {
+                let mut context = match style_of(run[0]) {
+                    Some(style) => base.clone().with_layer_style(style.at_zoom(self.zoom as f64)),
+                    None => base.clone(),
+                };
+                // Every run gets what is left of the pass budget
+                if let Some(budget) = self.frame_budget_ms {
+                    context = context.with_frame_budget((budget - ((self.frame_clock)() - pass_start)).max(0.0));
+                }
+                // The run the draw cap falls in gets every feature left in the pass, so its badge
+                // counts the later runs too; nothing after it is drawn
+                let capped = self.draw_cap.filter(|&cap| offset + run.len() >= cap);
+                let batch = match capped {
+                    Some(cap) => {
+                        context = context.with_draw_cap(cap.saturating_sub(offset));
+                        &features[offset..]
+                    }
+                    None => run,
+                };
+                if let Some(next) = render_feature_batch(batch, &context, target)? {
+                    stopped.set(Some(offset + next));
+                    return Ok(());
+                }
+                if capped.is_some() {
+                    break;
+                }
+                offset += run.len();
+            }
+            Ok(())
+        })?;
+        self.render_resume.set(stopped.get().unwrap_or(0));
+        Ok(())
+    }
+
+    // Draw the cached tiles covering the view, passing each its parent for upscaling
+    fn render_tiles(&self, target: &dyn DrawTarget, canvas_size: (f64, f64)) -> GeoArrowResult<()> {
+        if self.tile_cache.is_empty() {
+            return Ok(());
+        }
+        let context = RenderContext::new(self.render_bounds(canvas_size), canvas_size, self.zoom, self.style.clone());
+        for (x, y, z) in tiles_covering(&WebMercator, &context.viewport_bounds, self.zoom.min(MAX_TILE_ZOOM)) {
+            let Some(tile) = self.tile_cache.peek(&TileInfo::at(x, y, z).id) else {
+                continue;
+            };
+            let parent = if z > 0 { self.tile_cache.peek(&TileInfo::at(x / 2, y / 2, z - 1).id) } else { None };
+            render_tile(&tile, parent.as_ref(), &context, target)?;
+        }
+        Ok(())
+    }
+
+    /// Draw at most `cap` features per pass, in draw order, and a "+N" badge counting the
+    /// rest. `None` draws every feature.
+    pub fn set_draw_cap(&mut self, cap: Option<usize>) {
+        self.draw_cap = cap;
+    }
+
+    /// Round projected coordinates to a `quantum`-pixel grid (e.g. 0.25), so sub-pixel view
+    /// changes while panning don't make features shimmer. `None` keeps exact coordinates.
+    pub fn set_coord_quantum(&mut self, quantum: Option<f64>) {
+        self.coord_quantum = quantum;
+    }
+
+    /// Insert geodesic vertices along line and ring edges longer than `max_segment_deg` before
+    /// projecting, so large shapes follow the projection's curvature. `None` draws edges as is.
+    pub fn set_densify_threshold(&mut self, max_segment_deg: Option<f64>) {
+        self.densify_threshold = max_segment_deg;
+    }
+
+    /// Repeat features in the adjacent worlds (±360° longitude) so the map wraps horizontally
+    pub fn set_world_copies(&mut self, world_copies: bool) {
+        self.world_copies = world_copies;
+    }
+
+    /// Clip every feature to a screen-space polygon, e.g. a country outline for a spotlight
+    /// effect. `None` draws over the whole canvas.
+    pub fn set_clip_mask(&mut self, mask: Option<Vec<(f64, f64)>>) {
+        self.clip_mask = mask;
+    }
+
+    /// Limit each render pass to `budget_ms` milliseconds; features left over are drawn by the
+    /// next render, on top of the unfinished frame. `None` draws every feature each pass.
+    pub fn set_frame_budget(&mut self, budget_ms: Option<f64>) {
+        self.frame_budget_ms = budget_ms;
+        self.render_resume.set(0);
+    }
+
+    /// Clock the frame budget is measured with, in milliseconds
+    pub fn set_frame_clock(&mut self, clock: FrameClock) {
+        self.frame_clock = clock;
+    }
+
+    // Geographic extent drawn onto a canvas: the view bounds when set, otherwise the Web
+    // Mercator extent around the position at the current zoom
+    fn render_bounds(&self, canvas_size: (f64, f64)) -> Bounds {
+        self.bounds.clone().unwrap_or_else(|| {
+            let size = PixelSize::new(canvas_size.0 as u32, canvas_size.1 as u32);
+            Viewport::new(self.center(), self.zoom as f64, size)
+                .map(|viewport| viewport.bounds)
+                .unwrap_or_else(|_| transforms::calculate_viewport_bounds(self.position, canvas_size, self.zoom))
+        })
+    }
+
     pub fn render_to_canvas(&self, canvas_id: &str) -> GeoArrowResult<()> {
         let document = web_sys::window()
             .ok_or_else(|| GeoArrowError::Wasm("No window".to_string()))?
@@ -107,30 +1212,7 @@ impl MapView {
             .dyn_into::<web_sys::CanvasRenderingContext2d>()
             .map_err(|_| GeoArrowError::Wasm("Context is not 2d".to_string()))?;
 
-        // Clear canvas
-        context.clear_rect(0.0, 0.0, canvas.width() as f64, canvas.height() as f64);
-
-        // Set up basic styling
-        context.set_fill_style_str(&self.style.polygon_fill);
-        context.set_stroke_style_str(&self.style.polygon_stroke);
-        context.set_line_width(self.style.line_width);
-
-        // Draw simple crosshairs to show the map center
-        let center_x = canvas.width() as f64 / 2.0;
-        let center_y = canvas.height() as f64 / 2.0;
-
-        context.begin_path();
-        context.move_to(center_x - 10.0, center_y);
-        context.line_to(center_x + 10.0, center_y);
-        context.move_to(center_x, center_y - 10.0);
-        context.line_to(center_x, center_y + 10.0);
-        context.stroke();
-
-        // TODO: Implement actual geospatial data rendering
-        // This would involve:
-        // 1. Loading data from self.geoarrow_file
-        // 2. Transforming coordinates based on self.position and self.zoom
-        // 3. Rendering features (points, lines, polygons) as tiles
+        self.render_to_target(&context, (canvas.width() as f64, canvas.height() as f64))?;
 
         tracing::info!(
             "Rendered map {} to canvas {} at position {:?}, zoom {}",
@@ -142,6 +1224,90 @@ impl MapView {
 
         Ok(())
     }
+
+    /// Render the map without a browser canvas, through the software rasterizer. Returns
+    /// `width * height` RGBA pixels, row-major from the top-left corner.
+    pub fn render_to_image(&self, width: u32, height: u32) -> GeoArrowResult<Vec<u8>> {
+        if width == 0 || height == 0 {
+            return Err(GeoArrowError::Serialization(format!("Invalid image size {}x{}", width, height)));
+        }
+        let target = RasterTarget::new(width, height);
+        self.render_to_target(&target, (width as f64, height as f64))?;
+        Ok(target.pixels())
+    }
+
+    /// `render_to_image` encoded as an RGBA PNG
+    pub fn render_to_png(&self, width: u32, height: u32) -> GeoArrowResult<Vec<u8>> {
+        let pixels = self.render_to_image(width, height)?;
+        let encode_error = |e: png::EncodingError| GeoArrowError::Serialization(format!("PNG encoding failed: {}", e));
+        let mut png = Vec::new();
+        let mut encoder = png::Encoder::new(&mut png, width, height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(encode_error)?;
+        writer.write_image_data(&pixels).map_err(encode_error)?;
+        writer.finish().map_err(encode_error)?;
+        Ok(png)
+    }
+
+    /// Draw the map through wgpu onto `state`'s window, one triangle per point. Uses the same
+    /// bounds and layer point styles as `render_to_target`; lines and polygons are skipped.
+    pub fn render_to_gpu(&self, state: &mut State) -> GeoArrowResult<()> {
+        let span = tracing::info_span!("render_gpu", feature_count = self.scene.len(), zoom = self.zoom);
+        let _guard = span.enter();
+
+        let canvas_size = state.canvas_size();
+        let context = RenderContext::new(self.render_bounds(canvas_size), canvas_size, self.zoom, self.style.clone());
+        let layer_styles: Vec<PointStyle> = self.layers.iter()
+            .map(|entry| entry.layer.style.at_zoom(self.zoom as f64).point_style)
+            .collect();
+        let style_index: HashMap<&FeatureId, usize> = self.layers.iter()
+            .enumerate()
+            .flat_map(|(index, entry)| entry.feature_ids.iter().map(move |id| (id, index)))
+            .collect();
+
+        let mut vertices = Vec::new();
+        for feature in self.draw_order() {
+            let style = style_index
+                .get(&feature.id)
+                .map_or(&context.layer_style.point_style, |&index| &layer_styles[index]);
+            gpu::push_point_triangles(feature.render_geometry()?, &context, style, &mut vertices);
+        }
+        state.render(&vertices)
+    }
+
+    /// Feature count above which `render` uses the GPU backend instead of canvas-2d
+    pub fn set_backend_threshold(&mut self, threshold: usize) {
+        self.backend_threshold = threshold;
+    }
+
+    pub fn select_backend(&self) -> RenderBackend {
+        if self.scene.len() > self.backend_threshold {
+            RenderBackend::Gpu
+        } else {
+            RenderBackend::Canvas
+        }
+    }
+
+    /// Render with the backend chosen from the retained feature count, returning the one that drew.
+    /// A canvas id has no GPU surface, so this always draws with canvas-2d.
+    pub fn render(&self, canvas_id: &str) -> GeoArrowResult<RenderBackend> {
+        self.render_with(&CanvasDispatch { canvas_id })
+    }
+
+    pub fn render_with(&self, dispatch: &dyn BackendDispatch) -> GeoArrowResult<RenderBackend> {
+        let backend = self.select_backend();
+        tracing::info!(
+            "Selected {:?} backend for {} features (threshold {})",
+            backend,
+            self.scene.len(),
+            self.backend_threshold
+        );
+        match backend {
+            RenderBackend::Canvas => dispatch.render_canvas(self),
+            RenderBackend::Gpu => dispatch.render_gpu(self),
+        }
+    }
 }
 
 #[wasm_bindgen::prelude::wasm_bindgen]
@@ -183,15 +1349,914 @@ impl MapView {
     }
 }
 
+#[cfg(test)]
 mod test {
     use super::*;
+    use crate::engine::target::{DrawCommand, RecordingTarget};
+    use crate::model::{Dms, FeatureGeometry, Hemisphere};
+    use dashmap::DashMap;
+    use std::sync::Arc;
     use wasm_bindgen_test::*;
 
     wasm_bindgen_test_configure!(run_in_browser);
 
+    // Fixtures shared across the tests below
+
+    fn square(id: &str, min: f64, size: f64) -> GeoFeature {
+        let ring = vec![
+            GeoPoint::new(min, min),
+            GeoPoint::new(min, min + size),
+            GeoPoint::new(min + size, min + size),
+            GeoPoint::new(min + size, min),
+            GeoPoint::new(min, min),
+        ];
+        GeoFeature::new(id.into(), FeatureGeometry::Polygon(vec![ring]), DashMap::new())
+    }
+
+    // Loader returning one square per layer whose `priority` property is taken from the layer name
+    struct PriorityLoader;
+
+    impl LayerLoader for PriorityLoader {
+        fn load(&self, layer: &Layer) -> GeoArrowResult<Vec<GeoFeature>> {
+            let feature = square(&layer.id, 0.0, 1.0);
+            let priority: u64 = layer.name.parse().unwrap();
+            feature.properties.insert("priority".to_string(), priority.into());
+            Ok(vec![feature])
+        }
+    }
+
     #[wasm_bindgen_test]
     fn test_map_view_creation() {
         let map_view = MapView::default();
         assert_eq!(map_view.get_zoom(), 1);
     }
+
+    #[test]
+    fn test_with_center_from_dms() {
+        let center = GeoPoint::from_dms(
+            Dms::new(51.0, 30.0, 0.0, Hemisphere::North),
+            Dms::new(0.0, 7.0, 30.0, Hemisphere::West),
+        )
+        .unwrap();
+        let map_view = MapView::default().with_center(center.clone());
+
+        assert_eq!(map_view.get_position(), (-0.125, 51.5));
+        assert_eq!(map_view.center(), center);
+    }
+
+    // Loader that counts loads and returns one feature per call, with an id per generation
+    struct CountingLoader {
+        loads: std::cell::Cell<u64>,
+    }
+
+    impl LayerLoader for CountingLoader {
+        fn load(&self, _layer: &Layer) -> GeoArrowResult<Vec<GeoFeature>> {
+            self.loads.set(self.loads.get() + 1);
+            Ok(vec![square(&format!("vehicle-{}", self.loads.get()), 0.0, 1.0)])
+        }
+    }
+
+    #[test]
+    fn test_layer_refresh_interval() {
+        let mut map_view = MapView::default();
+        map_view.add_layer(
+            Layer::new("live".to_string(), "Vehicles".to_string(), DataSource::Memory(Vec::new()))
+                .with_refresh_interval(Duration::from_secs(5)),
+        );
+        map_view.add_layer(Layer::new("static".to_string(), "Roads".to_string(), DataSource::Memory(Vec::new())));
+        let loader = CountingLoader { loads: std::cell::Cell::new(0) };
+        let secs = Duration::from_secs;
+
+        assert_eq!(map_view.tick_with(secs(0), &loader).reloaded, vec!["live", "static"]);
+        assert!(map_view.tick_with(secs(4), &loader).reloaded.is_empty());
+        assert_eq!(map_view.tick_with(secs(5), &loader).reloaded, vec!["live"]);
+
+        // The live layer's first generation was replaced, the static layer's kept
+        let ids: Vec<String> = map_view.features().iter().map(|f| f.id.to_string()).collect();
+        assert_eq!(ids, vec!["vehicle-2", "vehicle-3"]);
+    }
+
+    // Loader failing for layers named "broken" and returning one square with a shared id otherwise
+    struct FlakyLoader;
+
+    impl LayerLoader for FlakyLoader {
+        fn load(&self, layer: &Layer) -> GeoArrowResult<Vec<GeoFeature>> {
+            match layer.name.as_str() {
+                "broken" => Err(GeoArrowError::Io(format!("{} is offline", layer.id))),
+                _ => Ok(vec![square("shared", 1.0, 1.0)]),
+            }
+        }
+    }
+
+    #[test]
+    fn test_tick_reports_failed_layers_and_applies_id_policy() {
+        let layers = |view: &mut MapView| {
+            for (id, name) in [("a", "ok"), ("broken", "broken"), ("b", "ok")] {
+                view.add_layer(Layer::new(id.to_string(), name.to_string(), DataSource::Memory(Vec::new())));
+            }
+        };
+        let mut map_view = MapView::default();
+        map_view.set_view_bounds(GeoBounds::new(0.0, 0.0, 10.0, 10.0));
+        map_view.set_duplicate_id_policy(DuplicateIdPolicy::Rename);
+        layers(&mut map_view);
+
+        let report = map_view.tick_with(Duration::ZERO, &FlakyLoader);
+
+        // The failure doesn't stop the layers after it, and the view index sees their features
+        assert_eq!(report.reloaded, vec!["a", "b"]);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].0, "broken");
+        let in_view: HashSet<String> = map_view.features_in_view().iter().map(|id| id.to_string()).collect();
+        assert_eq!(in_view, HashSet::from(["shared".to_string(), "shared-1".to_string()]));
+        // The failed layer is retried on the next tick
+        assert_eq!(map_view.tick_with(Duration::ZERO, &FlakyLoader).failed.len(), 1);
+
+        // A clash under the Error policy fails that layer without touching the scene
+        let mut strict = MapView::default();
+        strict.set_duplicate_id_policy(DuplicateIdPolicy::Error);
+        layers(&mut strict);
+        let report = strict.tick_with(Duration::ZERO, &FlakyLoader);
+        assert_eq!(report.reloaded, vec!["a"]);
+        assert_eq!(report.failed.iter().map(|(id, _)| id.as_str()).collect::<Vec<_>>(), vec!["broken", "b"]);
+        assert_eq!(strict.features().len(), 1);
+    }
+
+    #[test]
+    fn test_feature_budget_evicts_offscreen_features() {
+        let mut map_view = MapView::default();
+        map_view.set_zoom(4);
+        map_view.set_view_bounds(GeoBounds::new(0.0, 0.0, 10.0, 10.0));
+        map_view.set_feature_budget(3);
+
+        map_view.add_feature(square("on-a", 1.0, 1.0));
+        map_view.add_feature(square("on-b", 5.0, 1.0));
+        map_view.add_feature(square("near", 20.0, 1.0));
+        map_view.add_feature(square("far", 60.0, 1.0));
+        map_view.add_feature(square("on-c", 8.0, 1.0));
+
+        let ids: Vec<String> = map_view.features().iter().map(|f| f.id.to_string()).collect();
+        assert_eq!(ids, vec!["on-a", "on-b", "on-c"]);
+        assert_eq!(map_view.evicted_tiles().len(), 2);
+        // The farther tile went first
+        assert!(map_view.evicted_tiles()[0].0 > map_view.evicted_tiles()[1].0);
+    }
+
+    #[test]
+    fn test_process_events_coalesces_pans() {
+        let mut map_view = MapView::default();
+        map_view.queue_event(MapEvent::Pan { dx: 1.0, dy: 0.5 });
+        map_view.queue_event(MapEvent::Pan { dx: 2.0, dy: -1.0 });
+        map_view.queue_event(MapEvent::Pan { dx: -0.5, dy: 0.25 });
+        // Nothing moves until the queue is processed
+        assert_eq!(map_view.get_position(), (0.0, 0.0));
+
+        let applied = map_view.process_events();
+
+        assert_eq!(applied, vec![MapEvent::Pan { dx: 2.5, dy: -0.25 }]);
+        assert_eq!(map_view.get_position(), (2.5, -0.25));
+        assert!(map_view.process_events().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failed_load_reports_error_status() {
+        let file = GeoArrowFile::new("./data/missing.geojson".to_string(), 0, String::new());
+        let mut map_view = MapView::new(1, file, (0.0, 0.0), 1);
+        assert_eq!(map_view.status(), &MapViewStatus::Empty);
+
+        assert!(map_view.load().await.is_err());
+
+        let MapViewStatus::Error(message) = map_view.status().clone() else {
+            panic!("expected an error status, got {:?}", map_view.status());
+        };
+        assert!(message.contains("missing.geojson"));
+
+        map_view.style.error_color = "#880000".to_string();
+        let target = RecordingTarget::new();
+        map_view.render_to_target(&target, (100.0, 100.0)).unwrap();
+        let commands = target.commands();
+        assert_eq!(
+            commands[commands.len() - 2..],
+            [DrawCommand::SetFillStyle("#880000".to_string()), DrawCommand::FillText(message, 50.0, 50.0)]
+        );
+    }
+
+    #[test]
+    fn test_panning_updates_features_in_view() {
+        let mut map_view = MapView::default();
+        for i in 0..10 {
+            map_view.add_feature(square(&format!("sq{i}"), i as f64 * 10.0, 1.0));
+        }
+        assert!(map_view.features_in_view().is_empty());
+
+        let ids = |view: &MapView| {
+            let mut ids: Vec<String> = view.features_in_view().iter().map(ToString::to_string).collect();
+            ids.sort();
+            ids
+        };
+        map_view.set_view_bounds(Bounds::new(-5.0, -5.0, 25.0, 25.0));
+        assert_eq!(ids(&map_view), ["sq0", "sq1", "sq2"]);
+
+        // Pan east: sq0 and sq1 leave, sq3 and sq4 are revealed
+        map_view.set_view_bounds(Bounds::new(15.0, 15.0, 45.0, 45.0));
+        assert_eq!(ids(&map_view), ["sq2", "sq3", "sq4"]);
+
+        // A feature added inside the view joins the set straight away
+        map_view.add_feature(square("late", 30.5, 1.0));
+        assert!(map_view.features_in_view().contains(&FeatureId::from("late")));
+
+        // Jumping to a disjoint view replaces the set
+        map_view.set_view_bounds(Bounds::new(85.0, 85.0, 95.0, 95.0));
+        assert_eq!(ids(&map_view), ["sq9"]);
+    }
+
+    #[test]
+    fn test_loading_spinner_advances_between_frames() {
+        let mut map_view = MapView { status: MapViewStatus::Loading, ..MapView::default() };
+        let arc_start = |view: &MapView| {
+            let target = RecordingTarget::new();
+            view.render_to_target(&target, (200.0, 100.0)).unwrap();
+            target.commands().into_iter().find_map(|c| match c {
+                DrawCommand::Arc(x, y, _, start, end) => {
+                    assert_eq!((x, y), (100.0, 50.0));
+                    assert!((end - start - SPINNER_SWEEP).abs() < 1e-12);
+                    Some(start)
+                }
+                _ => None,
+            })
+        };
+
+        assert!(map_view.tick(Duration::from_millis(0)).failed.is_empty());
+        let first = arc_start(&map_view).unwrap();
+        assert!(map_view.tick(Duration::from_millis(250)).failed.is_empty());
+        let second = arc_start(&map_view).unwrap();
+        assert!((second - first - std::f64::consts::FRAC_PI_2).abs() < 1e-9);
+
+        map_view.set_loading_indicator(false);
+        assert_eq!(arc_start(&map_view), None);
+    }
+
+    #[test]
+    fn test_source_loader_maps_local_layers() {
+        let content = r#"{"type":"FeatureCollection","features":[
+            {"type":"Feature","id":"a","properties":{"name":"A"},"geometry":{"type":"Point","coordinates":[1,2]}},
+            {"type":"Feature","id":"b","properties":{},"geometry":{"type":"LineString","coordinates":[[0,0],[3,4]]}}
+        ]}"#;
+        let path = std::env::temp_dir().join(format!("geoarrow-layer-{}.geojson", std::process::id()));
+        std::fs::write(&path, content).unwrap();
+
+        let local = Layer::new("local".to_string(), "Local".to_string(), DataSource::Local(path.clone()));
+        let memory = Layer::new("memory".to_string(), "Memory".to_string(), DataSource::Memory(content.as_bytes().to_vec()));
+        let mapped = SourceLoader.load(&local).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let copied = SourceLoader.load(&memory).unwrap();
+
+        assert_eq!(mapped.len(), 2);
+        for (mapped, copied) in mapped.iter().zip(&copied) {
+            assert_eq!(mapped.id, copied.id);
+            assert_eq!(mapped.geometry().unwrap(), copied.geometry().unwrap());
+        }
+        assert!(SourceLoader.load(&local).unwrap_err().to_string().contains("Failed to read layer local"));
+    }
+
+    #[test]
+    fn test_dispose_releases_cached_resources() {
+        let mut map_view = MapView::default();
+        map_view.add_feature(square("a", 0.0, 1.0));
+        map_view.add_layer(Layer::new("live".to_string(), "Vehicles".to_string(), DataSource::Memory(Vec::new())));
+        map_view.queue_event(MapEvent::Zoom(3));
+        map_view.tile_cache.insert(0, Tile::new(0, 0, 1));
+        map_view.tile_cache.insert(1, Tile::new(1, 0, 1));
+        map_view.set_view_bounds(GeoBounds::new(0.0, 0.0, 10.0, 10.0));
+        map_view.update_pick_buffer(100, 100).unwrap();
+        assert_eq!(map_view.tile_cache().len(), 2);
+        assert_eq!(map_view.pick_at(5.0, 95.0), Some(FeatureId::from("a")));
+
+        map_view.dispose();
+
+        assert!(map_view.tile_cache().is_empty());
+        assert!(map_view.tile_cache.get(&0).is_none());
+        assert!(map_view.pick_buffer.is_none());
+        assert_eq!(map_view.pick_at(5.0, 95.0), None);
+        assert!(map_view.features().is_empty());
+        assert!(map_view.scene_bounds().is_none());
+        assert!(map_view.process_events().is_empty());
+        assert!(map_view.tick(Duration::ZERO).reloaded.is_empty());
+    }
+
+    #[test]
+    fn test_tile_requests_start_at_the_view_center() {
+        let mut map_view = MapView::default().with_center(GeoPoint::new(40.0, 45.0));
+        map_view.set_zoom(2);
+        map_view.request_tiles((512.0, 512.0));
+        let pending = map_view.pending_tile_requests();
+        assert!(pending > 4);
+
+        // Tile holding the center, in the same 4x4 grid the requests use
+        let center = crate::engine::projection::tile_at(&WebMercator, 45.0, 40.0, 2);
+        let first = map_view.next_tile_request().unwrap();
+        assert_eq!((first.x, first.y, first.z), (center.0, center.1, 2));
+        assert_eq!(map_view.tile_cache.get(&first.id).unwrap().status, TileStatus::Loading);
+
+        let (x, y) = WebMercator.to_tile_space(45.0, 40.0);
+        let mut distance = 0.0;
+        while let Some(request) = map_view.next_tile_request() {
+            let next = (request.x as f64 + 0.5 - x * 4.0).hypot(request.y as f64 + 0.5 - y * 4.0);
+            assert!(next >= distance);
+            distance = next;
+        }
+        assert_eq!(map_view.tile_cache().len(), pending);
+
+        // Loaded tiles are cached and not requested again
+        map_view.tile_loaded(first, vec![square("road", 50.0, 1.0)]);
+        assert_eq!(map_view.tile_cache.get(&first.id).unwrap().status, TileStatus::Loaded);
+        map_view.request_tiles((512.0, 512.0));
+        assert_eq!(map_view.pending_tile_requests(), 0);
+    }
+
+    #[test]
+    fn test_failed_tiles_render_the_placeholder() {
+        let mut map_view = MapView::default();
+        map_view.style.tile_placeholder = TilePlaceholder::Color("#ff00ff".to_string());
+        map_view.request_tiles((512.0, 512.0));
+        let failed = map_view.next_tile_request().unwrap();
+        map_view.tile_failed(failed, "404 Not Found");
+        map_view.next_tile_request().unwrap();
+
+        let target = RecordingTarget::new();
+        map_view.render_to_target(&target, (512.0, 512.0)).unwrap();
+        let commands = target.commands();
+        let placeholder = commands.iter().position(|c| *c == DrawCommand::SetFillStyle("#ff00ff".to_string())).unwrap();
+        assert!(matches!(commands[placeholder + 1], DrawCommand::FillRect(..)));
+        // The tile still loading draws nothing
+        assert_eq!(commands.iter().filter(|c| matches!(c, DrawCommand::FillRect(..))).count(), 1);
+    }
+
+    #[test]
+    fn test_pick_at_decodes_feature_color() {
+        let mut map_view = MapView::default();
+        map_view.add_feature(square("left", 0.0, 4.0));
+        map_view.add_feature(square("right", 6.0, 4.0));
+        map_view.set_view_bounds(GeoBounds::new(0.0, 0.0, 10.0, 10.0));
+        assert_eq!(map_view.pick_at(20.0, 80.0), None);
+
+        map_view.update_pick_buffer(100, 100).unwrap();
+
+        // Screen y grows downwards: "left" is bottom-left, "right" top-right
+        assert_eq!(map_view.pick_at(20.0, 80.0), Some(FeatureId::from("left")));
+        assert_eq!(map_view.pick_at(80.0, 20.0), Some(FeatureId::from("right")));
+        assert_eq!(map_view.pick_at(80.0, 80.0), None);
+        assert_eq!(map_view.pick_at(-1.0, 80.0), None);
+    }
+
+    #[test]
+    fn test_pick_buffer_matches_the_rendered_view() {
+        let mut map_view = MapView::default();
+        let mut style = LayerStyle::default();
+        style.point_style.radius = 12.0;
+        let content = r#"{"type":"Feature","id":"null-island","properties":{},"geometry":{"type":"Point","coordinates":[0,0]}}"#;
+        let source = DataSource::Memory(content.as_bytes().to_vec());
+        map_view.add_layer(Layer::new("places".to_string(), "Places".to_string(), source).with_style(style));
+        assert!(map_view.tick(Duration::ZERO).failed.is_empty());
+
+        // No view bounds: picking uses the viewport around the position that rendering uses,
+        // which puts the point at the canvas center
+        map_view.update_pick_buffer(100, 100).unwrap();
+        assert_eq!(map_view.pick_at(50.0, 50.0), Some(FeatureId::from("null-island")));
+        // Eight pixels off is inside the layer's 12px radius, not the map style's 3px
+        assert_eq!(map_view.pick_at(58.0, 50.0), Some(FeatureId::from("null-island")));
+        assert_eq!(map_view.pick_at(64.0, 50.0), None);
+    }
+
+    #[test]
+    fn test_hidden_features_skip_rendering_and_hit_testing() {
+        let mut map_view = MapView::default();
+        map_view.add_feature(square("left", 0.0, 4.0));
+        map_view.add_feature(square("right", 6.0, 4.0));
+        map_view.set_view_bounds(GeoBounds::new(0.0, 0.0, 10.0, 10.0));
+        map_view.update_pick_buffer(100, 100).unwrap();
+        assert_eq!(map_view.feature_at(20.0, 80.0).map(|f| f.id.to_string()), Some("left".to_string()));
+
+        map_view.hide_feature(FeatureId::from("left"));
+
+        let drawn: Vec<String> = map_view.draw_order().iter().map(|f| f.id.to_string()).collect();
+        assert_eq!(drawn, vec!["right"]);
+        assert!(map_view.feature_at(20.0, 80.0).is_none());
+        assert!(map_view.feature_at(80.0, 20.0).is_some());
+
+        map_view.show_all();
+        assert!(!map_view.is_hidden(&FeatureId::from("left")));
+        assert!(map_view.feature_at(20.0, 80.0).is_some());
+    }
+
+    #[test]
+    fn test_hit_tolerance_enlarges_point_targets() {
+        let mut map_view = MapView::default();
+        map_view.add_feature(GeoFeature::new("dot".into(), FeatureGeometry::Point(GeoPoint::new(5.0, 5.0)), DashMap::new()));
+        map_view.set_view_bounds(GeoBounds::new(0.0, 0.0, 10.0, 10.0));
+        map_view.update_pick_buffer(100, 100).unwrap();
+        // The point sits at (50, 50); eight pixels off misses the default 3px radius
+        assert!(map_view.feature_at(58.0, 50.0).is_none());
+
+        map_view.set_hit_tolerance(10.0, 4.0);
+        assert_eq!(map_view.feature_at(58.0, 50.0).map(|f| f.id.to_string()), Some("dot".to_string()));
+        assert!(map_view.feature_at(70.0, 50.0).is_none());
+    }
+
+    #[test]
+    fn test_global_sort_overrides_layer_order() {
+        let mut map_view = MapView::default();
+        let source = || DataSource::Memory(Vec::new());
+        // Layer A sits above layer B but holds the lower-priority feature
+        map_view.add_layer(Layer::new("a".to_string(), "1".to_string(), source()).with_z_index(10));
+        map_view.add_layer(Layer::new("b".to_string(), "5".to_string(), source()).with_z_index(0));
+        assert!(map_view.tick_with(Duration::ZERO, &PriorityLoader).failed.is_empty());
+
+        let ids = |view: &MapView| view.draw_order().iter().map(|f| f.id.to_string()).collect::<Vec<_>>();
+        assert_eq!(ids(&map_view), vec!["b", "a"]);
+
+        map_view.set_global_sort(Some(SortKey::ascending("priority")));
+        assert_eq!(ids(&map_view), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_sort_key_orders_mixed_value_types() {
+        use serde_json::json;
+
+        let values = [
+            json!({"b": 1}), json!("b"), json!([1, 2]), json!(2.5), json!(true), json!(null),
+            json!("a"), json!([1]), json!(-1), json!(false), json!({"a": 1}),
+        ];
+        let features: Vec<GeoFeature> = values.iter().enumerate()
+            .map(|(i, value)| {
+                let properties = DashMap::new();
+                properties.insert("rank".to_string(), value.clone());
+                GeoFeature::new(i.to_string().into(), FeatureGeometry::Point(GeoPoint::new(0.0, 0.0)), properties)
+            })
+            .chain([GeoFeature::new("none".into(), FeatureGeometry::Point(GeoPoint::new(0.0, 0.0)), DashMap::new())])
+            .collect();
+
+        let key = SortKey::ascending("rank");
+        let mut sorted: Vec<&GeoFeature> = features.iter().collect();
+        sorted.sort_by(|a, b| key.compare(a, b));
+        let ranks: Vec<serde_json::Value> = sorted.iter()
+            .map(|feature| feature.properties.get("rank").map_or(json!("missing"), |value| value.clone()))
+            .collect();
+        assert_eq!(ranks, [
+            json!(null), json!(false), json!(true), json!(-1), json!(2.5), json!("a"), json!("b"),
+            json!([1]), json!([1, 2]), json!({"a": 1}), json!({"b": 1}), json!("missing"),
+        ]);
+        // Every pair agrees with its reverse, as a total order must
+        for a in &features {
+            for b in &features {
+                assert_eq!(key.compare(a, b), key.compare(b, a).reverse());
+            }
+        }
+        // Descending flips the values but still draws features without the property last
+        let key = SortKey::descending("rank");
+        sorted.sort_by(|a, b| key.compare(a, b));
+        assert_eq!(sorted.first().unwrap().properties.get("rank").map(|value| value.clone()), Some(json!({"b": 1})));
+        assert_eq!(sorted.last().unwrap().id.to_string(), "none");
+    }
+
+    #[test]
+    fn test_selected_feature_draws_above_higher_z_index() {
+        let mut map_view = MapView::default();
+        let source = || DataSource::Memory(Vec::new());
+        map_view.add_layer(Layer::new("low".to_string(), "1".to_string(), source()).with_z_index(0));
+        map_view.add_layer(Layer::new("high".to_string(), "2".to_string(), source()).with_z_index(10));
+        map_view.add_layer(Layer::new("top".to_string(), "3".to_string(), source()).with_z_index(20));
+        assert!(map_view.tick_with(Duration::ZERO, &PriorityLoader).failed.is_empty());
+
+        let ids = |view: &MapView| view.draw_order().iter().map(|f| f.id.to_string()).collect::<Vec<_>>();
+        map_view.select_feature(FeatureId::from("low"));
+        map_view.set_hovered_feature(Some(FeatureId::from("high")));
+        assert_eq!(ids(&map_view), vec!["low", "high", "top"]);
+
+        map_view.set_raise_interactive(true);
+        assert_eq!(ids(&map_view), vec!["top", "low", "high"]);
+
+        map_view.hide_feature(FeatureId::from("high"));
+        map_view.clear_selection();
+        assert_eq!(ids(&map_view), vec!["low", "top"]);
+    }
+
+    #[test]
+    fn test_explode_features_splits_multipolygon() {
+        let ring = |min: f64| {
+            vec![
+                GeoPoint::new(min, min),
+                GeoPoint::new(min + 1.0, min),
+                GeoPoint::new(min + 1.0, min + 1.0),
+                GeoPoint::new(min, min),
+            ]
+        };
+        let properties = DashMap::new();
+        properties.insert("name".to_string(), serde_json::json!("islands"));
+        let mut map_view = MapView::default();
+        map_view.add_feature(GeoFeature::new(
+            FeatureId::from("parent"),
+            FeatureGeometry::MultiPolygon(vec![vec![ring(0.0)], vec![ring(5.0)]]),
+            properties,
+        ));
+        map_view.add_feature(square("single", 10.0, 1.0));
+
+        map_view.explode_features().unwrap();
+
+        let ids: Vec<String> = map_view.features().iter().map(|f| f.id.to_string()).collect();
+        assert_eq!(ids, vec!["parent-0", "parent-1", "single"]);
+        for id in ["parent-0", "parent-1"] {
+            let part = map_view.feature(&FeatureId::from(id)).unwrap();
+            assert!(matches!(part.geometry().unwrap(), FeatureGeometry::Polygon(_)));
+            assert_eq!(*part.properties.get("name").unwrap(), serde_json::json!("islands"));
+        }
+        assert_eq!(map_view.feature(&FeatureId::from("parent-1")).unwrap().bounds.min_x, 5.0);
+    }
+
+    #[test]
+    fn test_property_stats_over_population() {
+        let mut map_view = MapView::default();
+        assert_eq!(map_view.property_stats("pop"), None);
+
+        let pops = [serde_json::json!(2), serde_json::json!(4.0), serde_json::json!("6"), serde_json::json!("n/a")];
+        for (i, pop) in pops.into_iter().enumerate() {
+            let feature = square(&format!("city-{}", i), i as f64, 1.0);
+            feature.properties.insert("pop".to_string(), pop);
+            map_view.add_feature(feature);
+        }
+        map_view.add_feature(square("no-pop", 9.0, 1.0));
+
+        let stats = map_view.property_stats("pop").unwrap();
+        assert_eq!(stats.count, 3);
+        assert_eq!(stats.min, 2.0);
+        assert_eq!(stats.max, 6.0);
+        assert_eq!(stats.mean, 4.0);
+        assert!((stats.stddev - (8.0f64 / 3.0).sqrt()).abs() < 1e-12);
+        assert_eq!(map_view.property_stats("name"), None);
+    }
+
+    #[test]
+    fn test_transform_features_adds_property() {
+        let mut map_view = MapView::default();
+        map_view.add_feature(square("a", 0.0, 1.0));
+        map_view.add_feature(square("b", 5.0, 2.0));
+        let bounds_before = map_view.scene_bounds().cloned();
+
+        map_view.transform_features(|feature| {
+            let b = &feature.bounds;
+            let area = (b.max_x - b.min_x) * (b.max_y - b.min_y);
+            feature.properties.insert("area".to_string(), serde_json::json!(area));
+        });
+
+        let areas: Vec<f64> = map_view
+            .features()
+            .iter()
+            .map(|f| f.properties.get("area").unwrap().as_f64().unwrap())
+            .collect();
+        assert_eq!(areas, vec![1.0, 4.0]);
+        assert_eq!(map_view.scene_bounds().cloned(), bounds_before);
+        assert_eq!(map_view.scene_bounds(), Some(&GeoBounds::new(0.0, 0.0, 7.0, 7.0)));
+    }
+
+    // Records the entry points called; without a GPU it draws GPU requests with canvas-2d
+    #[derive(Default)]
+    struct TrackingDispatch {
+        calls: std::cell::RefCell<Vec<RenderBackend>>,
+        no_gpu: bool,
+    }
+
+    impl BackendDispatch for TrackingDispatch {
+        fn render_canvas(&self, _view: &MapView) -> GeoArrowResult<RenderBackend> {
+            self.calls.borrow_mut().push(RenderBackend::Canvas);
+            Ok(RenderBackend::Canvas)
+        }
+
+        fn render_gpu(&self, view: &MapView) -> GeoArrowResult<RenderBackend> {
+            if self.no_gpu {
+                return self.render_canvas(view);
+            }
+            self.calls.borrow_mut().push(RenderBackend::Gpu);
+            Ok(RenderBackend::Gpu)
+        }
+    }
+
+    #[test]
+    fn test_backend_selected_by_feature_count() {
+        let mut map_view = MapView::default();
+        map_view.set_backend_threshold(2);
+        map_view.add_feature(square("a", 0.0, 1.0));
+        map_view.add_feature(square("b", 2.0, 1.0));
+
+        let dispatch = TrackingDispatch::default();
+        assert_eq!(map_view.render_with(&dispatch).unwrap(), RenderBackend::Canvas);
+
+        map_view.add_feature(square("c", 4.0, 1.0));
+        assert_eq!(map_view.render_with(&dispatch).unwrap(), RenderBackend::Gpu);
+
+        assert_eq!(*dispatch.calls.borrow(), vec![RenderBackend::Canvas, RenderBackend::Gpu]);
+
+        // A dispatch that falls back reports the backend that really drew
+        let fallback = TrackingDispatch { no_gpu: true, ..Default::default() };
+        assert_eq!(map_view.render_with(&fallback).unwrap(), RenderBackend::Canvas);
+        assert_eq!(*fallback.calls.borrow(), vec![RenderBackend::Canvas]);
+    }
+
+    #[test]
+    fn test_render_to_image_draws_every_geometry_type() {
+        let mut map_view = MapView::default();
+        map_view.add_feature(square("park", 1.0, 3.0));
+        let road = FeatureGeometry::LineString(vec![GeoPoint::new(8.0, 0.0), GeoPoint::new(8.0, 10.0)]);
+        map_view.add_feature(GeoFeature::new("road".into(), road, DashMap::new()));
+        map_view.add_feature(GeoFeature::new("well".into(), FeatureGeometry::Point(GeoPoint::new(2.0, 7.0)), DashMap::new()));
+        map_view.set_view_bounds(GeoBounds::new(0.0, 0.0, 10.0, 10.0));
+
+        let pixels = map_view.render_to_image(100, 100).unwrap();
+        assert_eq!(pixels.len(), 100 * 100 * 4);
+        let pixel = |x: usize, y: usize| {
+            let i = (y * 100 + x) * 4;
+            [pixels[i], pixels[i + 1], pixels[i + 2], pixels[i + 3]]
+        };
+        // Screen y runs down, so latitude 2.5 is row 75
+        let fill = pixel(25, 75);
+        assert!(fill[1] > 0 && fill[0] == 0 && fill[2] == 0, "{:?}", fill);
+        let line = pixel(50, 20);
+        assert!(line[2] > 200 && line[0] == 0, "{:?}", line);
+        let point = pixel(70, 80);
+        assert!(point[0] > 200 && point[2] == 0, "{:?}", point);
+        assert_eq!(pixel(95, 50), [0, 0, 0, 0]);
+
+        let png = map_view.render_to_png(100, 100).unwrap();
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        assert!(map_view.render_to_image(0, 10).is_err());
+    }
+
+    #[test]
+    fn test_render_uses_layer_style() {
+        let geojson = r#"{"type":"FeatureCollection","features":[
+            {"type":"Feature","id":"lake","properties":{},
+             "geometry":{"type":"Polygon","coordinates":[[[2,2],[8,2],[8,8],[2,8],[2,2]]]}}
+        ]}"#;
+        let mut style = LayerStyle::default();
+        style.polygon_style.fill_color = "#3366CC".to_string();
+        style.polygon_style.fill_opacity = 0.5;
+        let mut map_view = MapView::default();
+        map_view.add_layer(
+            Layer::new("water".to_string(), "Water".to_string(), DataSource::Memory(geojson.as_bytes().to_vec()))
+                .with_style(style),
+        );
+        assert!(map_view.tick(Duration::ZERO).failed.is_empty());
+        map_view.add_feature(square("plain", 0.0, 1.0));
+        map_view.set_view_bounds(GeoBounds::new(0.0, 0.0, 10.0, 10.0));
+
+        let target = RecordingTarget::new();
+        map_view.render_to_target(&target, (100.0, 100.0)).unwrap();
+
+        let commands = target.commands();
+        let position = |command: &DrawCommand| commands.iter().position(|c| c == command).unwrap();
+        let lake_fill = position(&DrawCommand::SetFillStyle("#3366CC".to_string()));
+        assert!(lake_fill < position(&DrawCommand::SetGlobalAlpha(0.5)));
+        // The feature outside any layer keeps the map style
+        let plain_fill = commands.iter().rposition(|c| *c == DrawCommand::SetFillStyle(map_view.style.polygon_fill.clone()));
+        assert!(plain_fill.unwrap() > lake_fill);
+    }
+
+    #[test]
+    fn test_render_draws_loaded_polygon() {
+        let mut map_view = MapView::default();
+        let collection: geojson::FeatureCollection = r#"{"type":"FeatureCollection","features":[
+            {"type":"Feature","id":"park","properties":{},
+             "geometry":{"type":"Polygon","coordinates":[[[2,2],[8,2],[8,8],[2,8],[2,2]]]}}
+        ]}"#
+        .parse()
+        .unwrap();
+        map_view.load_collection(&collection, &LoadOptions::default()).unwrap();
+        map_view.set_view_bounds(Bounds::new(0.0, 0.0, 10.0, 10.0));
+
+        let target = RecordingTarget::new();
+        map_view.render_to_target(&target, (100.0, 100.0)).unwrap();
+
+        let commands = target.commands();
+        assert!(commands.contains(&DrawCommand::SetFillStyle(map_view.style.polygon_fill.clone())));
+        // Mercator spacing moves latitudes by a fraction of a pixel from an even split
+        let near = |x: f64, y: f64, ex: f64, ey: f64| (x - ex).abs() < 0.5 && (y - ey).abs() < 0.5;
+        let start = commands
+            .iter()
+            .position(|c| matches!(c, DrawCommand::MoveTo(x, y) if near(*x, *y, 20.0, 80.0)))
+            .expect("polygon path");
+        assert!(matches!(commands[start + 1], DrawCommand::LineTo(x, y) if near(x, y, 80.0, 80.0)));
+        assert!(commands[start..].contains(&DrawCommand::Fill));
+
+        // Without view bounds the polygon is placed around the position at the current zoom
+        let mut centered = MapView::default().with_center(GeoPoint::new(5.0, 5.0));
+        centered.set_zoom(6);
+        centered.load_collection(&collection, &LoadOptions::default()).unwrap();
+        let target = RecordingTarget::new();
+        centered.render_to_target(&target, (512.0, 512.0)).unwrap();
+        assert!(target.commands().contains(&DrawCommand::Fill));
+        assert!(!target.commands().contains(&DrawCommand::MoveTo(246.0, 256.0)));
+    }
+
+    #[test]
+    fn test_frame_budget_resumes_render_on_next_pass() {
+        thread_local! {
+            static NOW: Cell<f64> = const { Cell::new(0.0) };
+        }
+        // Every reading advances the mock clock by 1 ms
+        let clock: FrameClock = || NOW.with(|now| {
+            now.set(now.get() + 1.0);
+            now.get()
+        });
+        let features: Vec<String> = (0..10)
+            .map(|i| format!(r#"{{"type":"Feature","id":"p{i}","properties":{{}},"geometry":{{"type":"Point","coordinates":[{i},5]}}}}"#))
+            .collect();
+        let collection: geojson::FeatureCollection =
+            format!(r#"{{"type":"FeatureCollection","features":[{}]}}"#, features.join(",")).parse().unwrap();
+        let mut map_view = MapView::default();
+        map_view.load_collection(&collection, &LoadOptions::default()).unwrap();
+        map_view.set_view_bounds(Bounds::new(0.0, 0.0, 10.0, 10.0));
+        map_view.set_frame_clock(clock);
+        map_view.set_frame_budget(Some(3.5));
+
+        let mut drawn = Vec::new();
+        for pass in 0.. {
+            let target = RecordingTarget::new();
+            map_view.render_to_target(&target, (100.0, 100.0)).unwrap();
+            let commands = target.commands();
+            // Only the first pass of the frame starts from a blank canvas
+            assert_eq!(matches!(commands[0], DrawCommand::ClearRect(..)), pass == 0);
+            let arcs: Vec<usize> = commands.iter()
+                .filter_map(|c| match c {
+                    DrawCommand::Arc(x, ..) => Some((x / 10.0).round() as usize),
+                    _ => None,
+                })
+                .collect();
+            assert!(!arcs.is_empty() && arcs.len() < 10);
+            drawn.extend(arcs);
+            if map_view.render_resume.get() == 0 {
+                break;
+            }
+        }
+        assert_eq!(drawn, (0..10).collect::<Vec<_>>());
+
+        // Without a budget the next pass starts over and draws everything
+        map_view.set_frame_budget(None);
+        let target = RecordingTarget::new();
+        map_view.render_to_target(&target, (100.0, 100.0)).unwrap();
+        assert_eq!(target.commands().iter().filter(|c| matches!(c, DrawCommand::Arc(..))).count(), 10);
+    }
+
+    #[test]
+    fn test_coord_quantum_steadies_map_output() {
+        let mut map_view = MapView::default();
+        map_view.add_feature(square("a", 1.3, 4.1));
+        map_view.add_feature(GeoFeature::new("dot".into(), FeatureGeometry::Point(GeoPoint::new(7.7, 2.2)), DashMap::new()));
+        let render = |view: &mut MapView, offset: f64| {
+            view.set_view_bounds(GeoBounds::new(offset, 0.0, 10.0 + offset, 10.0));
+            let target = RecordingTarget::new();
+            view.render_to_target(&target, (100.0, 100.0)).unwrap();
+            target.commands()
+        };
+        // 0.0001° is a 0.001px shift
+        assert_ne!(render(&mut map_view, 0.0), render(&mut map_view, 0.0001));
+
+        map_view.set_coord_quantum(Some(0.25));
+        assert_eq!(render(&mut map_view, 0.0), render(&mut map_view, 0.0001));
+    }
+
+    #[test]
+    fn test_world_copies_wrap_the_map() {
+        let mut map_view = MapView::default();
+        map_view.add_feature(GeoFeature::new("fiji".into(), FeatureGeometry::Point(GeoPoint::new(0.0, 170.0)), DashMap::new()));
+        // View reaching one world west of the antimeridian
+        map_view.set_view_bounds(GeoBounds::new(-200.0, -90.0, -100.0, 90.0));
+        let arcs = |view: &MapView| {
+            let target = RecordingTarget::new();
+            view.render_to_target(&target, (100.0, 180.0)).unwrap();
+            target.commands().into_iter()
+                .filter_map(|c| match c {
+                    DrawCommand::Arc(x, y, ..) => Some((x, y)),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        assert!(arcs(&map_view).iter().all(|&(x, _)| x > 100.0));
+
+        map_view.set_world_copies(true);
+        // 170° drawn one world to the west lands at -190°
+        assert!(arcs(&map_view).contains(&(10.0, 90.0)));
+    }
+
+    #[test]
+    fn test_clip_mask_hides_features_outside() {
+        let mut map_view = MapView::default();
+        map_view.add_feature(square("left", 0.0, 4.0));
+        map_view.add_feature(square("right", 6.0, 4.0));
+        map_view.set_view_bounds(GeoBounds::new(0.0, 0.0, 10.0, 10.0));
+        // Left half of the canvas only
+        map_view.set_clip_mask(Some(vec![(0.0, 0.0), (50.0, 0.0), (50.0, 100.0), (0.0, 100.0)]));
+        let target = RasterTarget::new(100, 100);
+
+        map_view.render_to_target(&target, (100.0, 100.0)).unwrap();
+
+        assert_ne!(target.pixel(20, 80)[3], 0);
+        assert_eq!(target.pixel(80, 20)[3], 0);
+    }
+
+    #[test]
+    fn test_draw_cap_badges_features_past_the_cap() {
+        let points = |offset: usize| {
+            let features: Vec<String> = (0..25)
+                .map(|i| format!(r#"{{"type":"Feature","id":"p{}","properties":{{}},"geometry":{{"type":"Point","coordinates":[5,5]}}}}"#, offset + i))
+                .collect();
+            DataSource::Memory(format!(r#"{{"type":"FeatureCollection","features":[{}]}}"#, features.join(",")).into_bytes())
+        };
+        let mut map_view = MapView::default();
+        map_view.add_layer(Layer::new("low".to_string(), "Low".to_string(), points(0)).with_z_index(0));
+        map_view.add_layer(Layer::new("high".to_string(), "High".to_string(), points(25)).with_z_index(1));
+        assert!(map_view.tick_with(Duration::ZERO, &SourceLoader).failed.is_empty());
+        map_view.set_view_bounds(Bounds::new(0.0, 0.0, 10.0, 10.0));
+        assert_eq!(map_view.draw_order().len(), 50);
+
+        let render = |map_view: &MapView| {
+            let target = RecordingTarget::new();
+            map_view.render_to_target(&target, (100.0, 100.0)).unwrap();
+            let commands = target.commands();
+            let arcs = commands.iter().filter(|c| matches!(c, DrawCommand::Arc(..))).count();
+            let badges: Vec<String> = commands.into_iter()
+                .filter_map(|c| match c {
+                    DrawCommand::FillText(text, ..) => Some(text),
+                    _ => None,
+                })
+                .collect();
+            (arcs, badges)
+        };
+
+        map_view.set_draw_cap(Some(10));
+        assert_eq!(render(&map_view), (10, vec!["+40".to_string()]));
+        // A cap past the first layer still draws a single badge for the second layer's rest
+        map_view.set_draw_cap(Some(30));
+        assert_eq!(render(&map_view), (30, vec!["+20".to_string()]));
+        map_view.set_draw_cap(Some(50));
+        assert_eq!(render(&map_view), (50, Vec::new()));
+        map_view.set_draw_cap(None);
+        assert_eq!(render(&map_view), (50, Vec::new()));
+    }
+
+    // Records the fields of every span entered while it is the active subscriber
+    #[derive(Clone, Default)]
+    struct SpanCapture {
+        entered: Arc<std::sync::Mutex<Vec<(String, Vec<(String, String)>)>>>,
+        fields: Arc<std::sync::Mutex<std::collections::HashMap<u64, (String, Vec<(String, String)>)>>>,
+    }
+
+    struct FieldVisitor<'a>(&'a mut Vec<(String, String)>);
+
+    impl tracing::field::Visit for FieldVisitor<'_> {
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            self.0.push((field.name().to_string(), format!("{:?}", value)));
+        }
+    }
+
+    impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for SpanCapture {
+        fn on_new_span(
+            &self,
+            attrs: &tracing::span::Attributes<'_>,
+            id: &tracing::span::Id,
+            _ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            let mut fields = Vec::new();
+            attrs.record(&mut FieldVisitor(&mut fields));
+            self.fields
+                .lock()
+                .unwrap()
+                .insert(id.into_u64(), (attrs.metadata().name().to_string(), fields));
+        }
+
+        fn on_enter(&self, id: &tracing::span::Id, _ctx: tracing_subscriber::layer::Context<'_, S>) {
+            if let Some(span) = self.fields.lock().unwrap().get(&id.into_u64()) {
+                self.entered.lock().unwrap().push(span.clone());
+            }
+        }
+    }
+
+    #[test]
+    fn test_render_enters_span_with_feature_count() {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let capture = SpanCapture::default();
+        let subscriber = tracing_subscriber::registry().with(capture.clone());
+
+        let mut map_view = MapView::default();
+        map_view.add_feature(square("a", 0.0, 1.0));
+        map_view.add_feature(square("b", 5.0, 2.0));
+        let target = RecordingTarget::new();
+
+        tracing::subscriber::with_default(subscriber, || {
+            map_view.render_to_target(&target, (100.0, 100.0)).unwrap();
+        });
+
+        let entered = capture.entered.lock().unwrap();
+        let (_, fields) = entered.iter().find(|(name, _)| name == "render").expect("render span entered");
+        assert!(fields.contains(&("feature_count".to_string(), "2".to_string())));
+        assert!(fields.contains(&("zoom".to_string(), "1".to_string())));
+    }
 }